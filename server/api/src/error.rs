@@ -1,6 +1,7 @@
 use actix_web::http::StatusCode;
 use actix_web::ResponseError;
 use thiserror::Error;
+use crate::payment::PaymentError;
 
 pub(crate) type WebResult<T> = Result<T, Error>;
 
@@ -16,16 +17,33 @@ pub enum Error {
     BadRequest(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("Failed to send mail: {0}")]
+    Mail(String),
+    #[error("Failed to write spreadsheet: {0}")]
+    Spreadsheet(#[from] crate::spreadsheet::SpreadsheetError),
+    #[error("Invalid template: {0}")]
+    Template(#[from] crate::template::TemplateError),
+    #[error("Blocking task failed: {0}")]
+    Blocking(#[from] actix_web::error::BlockingError),
+    #[error("Failed to access storage: {0}")]
+    Storage(#[from] crate::storage::StorageError),
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
 }
 
 impl ResponseError for Error {
     fn status_code(&self) -> StatusCode {
         match self {
-            Self::Dal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Dal(_) | Self::Mail(_) | Self::Spreadsheet(_) | Self::Storage(_) | Self::Blocking(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Forbidden(_) => StatusCode::FORBIDDEN,
             Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
-            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::BadRequest(_) | Self::Template(_) => StatusCode::BAD_REQUEST,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Payment(PaymentError::InvalidSignature) => StatusCode::UNAUTHORIZED,
+            Self::Payment(_) => StatusCode::BAD_GATEWAY,
         }
     }
 }
@@ -1,17 +1,79 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use actix_web::{App, HttpServer, web};
 use dal::Driver;
+use crate::mail::Mailer;
+use crate::payment::PaymentProvider;
+use crate::storage::{LocalStorage, S3Storage, StorageBackend};
 
 mod error;
 mod routable;
 mod routes;
 mod session;
 mod empty;
+mod mail;
+mod payment;
+mod spreadsheet;
+mod locale;
+mod template;
+mod ubl;
+mod storage;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub frontend_host: String,
     pub port: u16,
     pub password_pepper: String,
+    /// How long a session remains valid after it was last used
+    pub session_expiry: Duration,
+    /// SMTP configuration. If not set, emails are logged instead of sent
+    pub mail: Option<MailConfig>,
+    /// Payment provider configuration. If not set, invoices cannot be paid online
+    pub payment: Option<PaymentConfig>,
+    /// Where uploaded files, such as attachments, are stored
+    pub storage: StorageConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub username: String,
+    pub password: String,
+    /// The address emails are sent from, e.g. `InvoiceX <noreply@example.com>`
+    pub from: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaymentConfig {
+    /// The base URL of the provider's API, e.g. `https://api.mollie.com`
+    pub base_url: String,
+    pub api_key: String,
+    /// The key callbacks of the provider are signed with
+    pub webhook_secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    /// A directory on the local filesystem
+    Local {
+        path: PathBuf,
+    },
+    /// A bucket of an S3-compatible object store
+    S3(S3Config),
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// The base URL of the object store, e.g. `https://s3.eu-west-1.amazonaws.com` or `http://localhost:9000`
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Address the bucket as part of the path rather than the host name, as MinIO requires by default
+    pub path_style: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -19,14 +81,34 @@ pub struct Config {
 pub struct AppData {
     pub config: Config,
     pub driver: Driver,
+    pub mailer: Mailer,
+    pub payment_provider: Option<Arc<dyn PaymentProvider>>,
+    pub storage: StorageBackend,
 }
 
 pub(crate) type WebData = web::Data<AppData>;
 
 pub async fn start(config: Config, driver: Driver) -> std::io::Result<()> {
+    let mailer = Mailer::new(config.mail.as_ref())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let payment_provider = config.payment.as_ref()
+        .map(payment::provider)
+        .transpose()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let storage = match &config.storage {
+        StorageConfig::Local { path } => StorageBackend::Local(LocalStorage::new(path)),
+        StorageConfig::S3(s3) => StorageBackend::S3(S3Storage::new(s3)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?),
+    };
+
     let appdata = AppData {
         config: config.clone(),
-        driver
+        driver,
+        mailer,
+        payment_provider,
+        storage,
     };
 
     let data = web::Data::new(appdata);
@@ -0,0 +1,179 @@
+//! Translations and number, date and currency formatting per [Language].
+//! The translation files in `api/locales` are bundled into the binary.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use dal::entities::{Language, UnitCode};
+use time::OffsetDateTime;
+
+/// Translations of every language, flattened to dotted keys, e.g. `invoice.total`
+type Translations = HashMap<Language, HashMap<String, String>>;
+
+fn source(language: Language) -> &'static str {
+    match language {
+        Language::En => include_str!("../locales/en.toml"),
+        Language::Nl => include_str!("../locales/nl.toml"),
+        Language::De => include_str!("../locales/de.toml"),
+    }
+}
+
+fn translations() -> &'static Translations {
+    static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+    TRANSLATIONS.get_or_init(|| Language::variants()
+        .iter()
+        .map(|language| {
+            let value: toml::Value = toml::from_str(source(*language))
+                .unwrap_or_else(|e| panic!("Invalid translation file for '{}': {e}", language.code()));
+
+            let mut flattened = HashMap::new();
+            flatten("", value, &mut flattened);
+            (*language, flattened)
+        })
+        .collect()
+    )
+}
+
+fn flatten(prefix: &str, value: toml::Value, into: &mut HashMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => for (key, value) in table {
+            let key = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+            flatten(&key, value, into);
+        },
+        toml::Value::String(value) => {
+            into.insert(prefix.to_string(), value);
+        },
+        _ => panic!("Translation '{prefix}' is not a string"),
+    }
+}
+
+/// Look up a translation, falling back to English if the language lacks it,
+/// and to the key itself if English lacks it too.
+/// `{name}` placeholders are replaced by the value of the matching argument
+pub fn translate(language: Language, key: &str, args: &[(&str, &str)]) -> String {
+    let translations = translations();
+    let template = translations[&language].get(key)
+        .or_else(|| translations[&Language::En].get(key))
+        .map(String::as_str)
+        .unwrap_or(key);
+
+    args.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{name}}}"), value))
+}
+
+/// All translations within a section, e.g. `invoice`, keyed by the remainder of their key.
+/// Translations missing from the language are taken from English
+pub fn translate_section(language: Language, section: &str) -> HashMap<String, String> {
+    let prefix = format!("{section}.");
+    let translations = translations();
+    [Language::En, language].iter()
+        .flat_map(|language| translations[language].iter())
+        .filter_map(|(key, value)| key.strip_prefix(&prefix).map(|key| (key.to_string(), value.clone())))
+        .collect()
+}
+
+/// Format a number with the language's decimal and thousands separators
+pub fn format_number(language: Language, value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.decimals$}", value.abs());
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let thousands_separator = translate(language, "format.thousands_separator", &[]);
+    let mut grouped = String::new();
+    for (idx, digit) in integer.chars().enumerate() {
+        if idx > 0 && (integer.len() - idx) % 3 == 0 {
+            grouped.push_str(&thousands_separator);
+        }
+
+        grouped.push(digit);
+    }
+
+    if !fraction.is_empty() {
+        grouped.push_str(&translate(language, "format.decimal_separator", &[]));
+        grouped.push_str(fraction);
+    }
+
+    // Don't print -0.00
+    if value < 0.0 && formatted.chars().any(|x| x.is_ascii_digit() && x != '0') {
+        grouped.insert(0, '-');
+    }
+
+    grouped
+}
+
+/// Format an amount in the currency with the given ISO 4217 code
+pub fn format_currency(language: Language, amount: f64, currency: &str) -> String {
+    let symbol = match currency.to_uppercase().as_str() {
+        "EUR" => "€".to_string(),
+        "USD" => "$".to_string(),
+        "GBP" => "£".to_string(),
+        code => format!("{code} "),
+    };
+
+    let formatted = translate(language, "format.currency", &[
+        ("symbol", symbol.as_str()),
+        ("amount", &format_number(language, amount, 2)),
+    ]);
+
+    // A currency code is followed by a space, which should not be doubled or trail
+    formatted.replace("  ", " ").trim().to_string()
+}
+
+/// The name of a unit of measure as shown on invoice lines. Empty for [UnitCode::C62], which has no dimension
+pub fn format_unit(language: Language, unit: UnitCode) -> String {
+    translate(language, &format!("units.{}", unit.to_string()), &[])
+}
+
+/// Format a UNIX timestamp as a date, in UTC
+pub fn format_date(language: Language, timestamp: i64) -> String {
+    let date = OffsetDateTime::from_unix_timestamp(timestamp).unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    let month = translate(language, &format!("months.{}", u8::from(date.month())), &[]);
+
+    translate(language, "format.date", &[
+        ("day", &date.day().to_string()),
+        ("month", &month),
+        ("year", &date.year().to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_languages_complete() {
+        let translations = translations();
+        let mut english = translations[&Language::En].keys().collect::<Vec<_>>();
+        english.sort();
+
+        for language in Language::variants() {
+            let mut keys = translations[language].keys().collect::<Vec<_>>();
+            keys.sort();
+            assert_eq!(keys, english, "Translations for '{}' differ from English", language.code());
+        }
+
+        for unit in UnitCode::variants() {
+            assert!(english.contains(&&format!("units.{}", unit.to_string())), "No translation for unit {unit:?}");
+        }
+    }
+
+    #[test]
+    fn numbers_and_currency() {
+        assert_eq!(format_number(Language::En, 1234567.891, 2), "1,234,567.89");
+        assert_eq!(format_number(Language::Nl, 1234.5, 2), "1.234,50");
+        assert_eq!(format_number(Language::De, -12.0, 0), "-12");
+        assert_eq!(format_number(Language::En, -0.001, 2), "0.00");
+
+        assert_eq!(format_currency(Language::En, 1234.5, "EUR"), "€1,234.50");
+        assert_eq!(format_currency(Language::Nl, 1234.5, "EUR"), "€ 1.234,50");
+        assert_eq!(format_currency(Language::De, 1234.5, "EUR"), "1.234,50 €");
+        assert_eq!(format_currency(Language::En, 10.0, "CHF"), "CHF 10.00");
+        assert_eq!(format_currency(Language::De, 10.0, "CHF"), "10,00 CHF");
+    }
+
+    #[test]
+    fn dates() {
+        // 2024-01-31
+        let timestamp = 1706659200;
+        assert_eq!(format_date(Language::En, timestamp), "31 January 2024");
+        assert_eq!(format_date(Language::Nl, timestamp), "31 januari 2024");
+        assert_eq!(format_date(Language::De, timestamp), "31. Januar 2024");
+    }
+}
@@ -0,0 +1,68 @@
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use tracing::warn;
+use crate::error::{Error, WebResult};
+use crate::MailConfig;
+
+/// Sends emails over SMTP.
+/// If no mail configuration is provided, emails are not sent but logged instead
+#[derive(Debug, Clone)]
+pub struct Mailer {
+    transport: Option<SmtpTransport>,
+}
+
+#[derive(Debug, Clone)]
+struct SmtpTransport {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(config: Option<&MailConfig>) -> Result<Self, String> {
+        let config = match config {
+            Some(x) => x,
+            None => return Ok(Self { transport: None }),
+        };
+
+        let from = config.from.parse::<Mailbox>().map_err(|e| format!("Invalid from address: {e}"))?;
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
+            .map_err(|e| format!("Invalid SMTP relay: {e}"))?
+            .port(config.smtp_port)
+            .credentials(Credentials::new(config.username.clone(), config.password.clone()))
+            .build();
+
+        Ok(Self {
+            transport: Some(SmtpTransport {
+                transport,
+                from,
+            })
+        })
+    }
+
+    /// Send a plain-text email
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> WebResult<()> {
+        let transport = match &self.transport {
+            Some(x) => x,
+            None => {
+                warn!("Mail is not configured. Not sending mail '{subject}' to {to}:\n{body}");
+                return Ok(());
+            }
+        };
+
+        let to = Mailbox::new(None, parse_address(to)?);
+        let message = Message::builder()
+            .from(transport.from.clone())
+            .to(to)
+            .subject(subject)
+            .body(body)
+            .map_err(|e| Error::Mail(e.to_string()))?;
+
+        transport.transport.send(message).await.map_err(|e| Error::Mail(e.to_string()))?;
+        Ok(())
+    }
+}
+/// Parse an email address, without a display name
+pub fn parse_address(address: &str) -> WebResult<Address> {
+    address.parse::<Address>().map_err(|e| Error::BadRequest(format!("Invalid email address: {e}")))
+}
@@ -0,0 +1,99 @@
+//! Online payments of invoices through an external payment provider.
+//!
+//! A payment is created at the provider, which returns a checkout URL the customer is sent to.
+//! When the status of the payment changes, the provider calls back the webhook URL of the payment.
+//! The callback is authenticated with a signature, after which the provider is asked for the current status.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+use crate::PaymentConfig;
+
+mod mollie;
+
+pub use mollie::MollieProvider;
+
+#[derive(Debug, Error)]
+pub enum PaymentError {
+    #[error("Request to payment provider failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Payment provider responded with status {status}: {body}")]
+    Provider {
+        status: u16,
+        body: String,
+    },
+    #[error("Invalid response from payment provider: {0}")]
+    InvalidResponse(String),
+    #[error("Callback signature is missing or invalid")]
+    InvalidSignature,
+}
+
+/// A payment to create at the provider
+#[derive(Debug, Clone)]
+pub struct PaymentRequest {
+    pub amount: f32,
+    /// ISO 4217 currency code, e.g. `EUR`
+    pub currency: String,
+    /// Shown to the customer, e.g. the invoice number
+    pub description: String,
+    /// Our own reference, returned with every status update. E.g. the ID of the invoice
+    pub reference: String,
+    /// Where the customer is sent after paying
+    pub redirect_url: String,
+    /// Where the provider sends callbacks when the status of the payment changes
+    pub webhook_url: String,
+}
+
+/// A payment created at the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentLink {
+    /// The ID of the payment at the provider
+    pub payment_id: String,
+    /// The URL the customer pays at
+    pub checkout_url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Open,
+    Pending,
+    Paid,
+    Failed,
+    Canceled,
+    Expired,
+}
+
+/// The current state of a payment, as reported by the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUpdate {
+    pub payment_id: String,
+    /// The reference provided in the [PaymentRequest]
+    pub reference: String,
+    pub status: PaymentStatus,
+    pub amount: f32,
+    pub currency: String,
+}
+
+/// The future returned by the methods of a [PaymentProvider]
+pub type PaymentFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, PaymentError>> + Send + 'a>>;
+
+/// An external service through which customers pay online.
+/// Methods return boxed futures, so that the provider can be selected at runtime
+pub trait PaymentProvider: Debug + Send + Sync {
+    /// The name of the request header the provider sends the signature of a callback in
+    fn signature_header(&self) -> &'static str;
+
+    /// Create a payment, returning the URL the customer can pay at
+    fn create_payment<'a>(&'a self, request: &'a PaymentRequest) -> PaymentFuture<'a, PaymentLink>;
+
+    /// Handle a callback of the provider. `signature` is the value of the provider's [signature header](Self::signature_header).
+    /// Callbacks without a valid signature are rejected with [PaymentError::InvalidSignature]
+    fn handle_callback<'a>(&'a self, signature: Option<&'a str>, body: &'a [u8]) -> PaymentFuture<'a, PaymentUpdate>;
+}
+
+/// Create the payment provider selected in the configuration
+pub fn provider(config: &PaymentConfig) -> Result<Arc<dyn PaymentProvider>, String> {
+    Ok(Arc::new(MollieProvider::new(config)?))
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use crate::PaymentConfig;
+use crate::payment::{PaymentError, PaymentFuture, PaymentLink, PaymentProvider, PaymentRequest, PaymentStatus, PaymentUpdate};
+
+/// Provider with a Mollie-style REST API.
+///
+/// Callbacks only carry the ID of the payment, signed with HMAC-SHA256 over the request body in the
+/// `X-Mollie-Signature` header. The status itself is always retrieved from the API, so a callback
+/// can never change a payment by itself
+#[derive(Debug, Clone)]
+pub struct MollieProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    webhook_secret: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePayment<'a> {
+    amount: Amount,
+    description: &'a str,
+    redirect_url: &'a str,
+    webhook_url: &'a str,
+    metadata: Metadata<'a>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Amount {
+    currency: String,
+    /// Decimal string with two decimals, e.g. `12.50`
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    reference: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Payment {
+    id: String,
+    status: String,
+    amount: Amount,
+    metadata: Option<serde_json::Value>,
+    #[serde(rename = "_links")]
+    links: Option<Links>,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    checkout: Option<Link>,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Callback {
+    id: String,
+}
+
+/// How long the provider gets to respond
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+impl MollieProvider {
+    pub fn new(config: &PaymentConfig) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+        Ok(Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            webhook_secret: config.webhook_secret.clone(),
+        })
+    }
+
+    async fn get_payment(&self, payment_id: &str) -> Result<Payment, PaymentError> {
+        let response = self.client.get(format!("{}/v2/payments/{payment_id}", self.base_url))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    fn verify_signature(&self, signature: Option<&str>, body: &[u8]) -> Result<(), PaymentError> {
+        let signature = signature
+            .and_then(|x| hex::decode(x).ok())
+            .ok_or(PaymentError::InvalidSignature)?;
+
+        // HMAC accepts keys of any length
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.webhook_secret.as_bytes()).unwrap();
+        mac.update(body);
+        // Constant time comparison
+        mac.verify_slice(&signature).map_err(|_| PaymentError::InvalidSignature)
+    }
+}
+
+impl PaymentProvider for MollieProvider {
+    fn signature_header(&self) -> &'static str {
+        "X-Mollie-Signature"
+    }
+
+    fn create_payment<'a>(&'a self, request: &'a PaymentRequest) -> PaymentFuture<'a, PaymentLink> {
+        Box::pin(async move {
+            let response = self.client.post(format!("{}/v2/payments", self.base_url))
+                .bearer_auth(&self.api_key)
+                .json(&CreatePayment {
+                    amount: Amount {
+                        currency: request.currency.clone(),
+                        value: format!("{:.2}", request.amount),
+                    },
+                    description: &request.description,
+                    redirect_url: &request.redirect_url,
+                    webhook_url: &request.webhook_url,
+                    metadata: Metadata {
+                        reference: &request.reference,
+                    },
+                })
+                .send()
+                .await?;
+
+            let payment: Payment = parse_response(response).await?;
+            let checkout_url = payment.links.and_then(|x| x.checkout)
+                .ok_or_else(|| PaymentError::InvalidResponse("Payment has no checkout link".to_string()))?
+                .href;
+
+            Ok(PaymentLink {
+                payment_id: payment.id,
+                checkout_url,
+            })
+        })
+    }
+
+    fn handle_callback<'a>(&'a self, signature: Option<&'a str>, body: &'a [u8]) -> PaymentFuture<'a, PaymentUpdate> {
+        Box::pin(async move {
+            self.verify_signature(signature, body)?;
+
+            let callback: Callback = serde_json::from_slice(body)
+                .map_err(|e| PaymentError::InvalidResponse(format!("Invalid callback: {e}")))?;
+            let payment = self.get_payment(&callback.id).await?;
+
+            let reference = payment.metadata.as_ref()
+                .and_then(|x| x.get("reference"))
+                .and_then(|x| x.as_str())
+                .ok_or_else(|| PaymentError::InvalidResponse("Payment has no reference".to_string()))?
+                .to_string();
+
+            Ok(PaymentUpdate {
+                payment_id: payment.id,
+                reference,
+                status: parse_status(&payment.status)?,
+                amount: payment.amount.value.parse()
+                    .map_err(|_| PaymentError::InvalidResponse(format!("Invalid amount '{}'", payment.amount.value)))?,
+                currency: payment.amount.currency,
+            })
+        })
+    }
+}
+
+async fn parse_response(response: reqwest::Response) -> Result<Payment, PaymentError> {
+    let status = response.status();
+    let body = response.bytes().await?;
+    if !status.is_success() {
+        return Err(PaymentError::Provider {
+            status: status.as_u16(),
+            body: String::from_utf8_lossy(&body).to_string(),
+        });
+    }
+
+    serde_json::from_slice(&body).map_err(|e| PaymentError::InvalidResponse(e.to_string()))
+}
+
+fn parse_status(status: &str) -> Result<PaymentStatus, PaymentError> {
+    Ok(match status {
+        "open" => PaymentStatus::Open,
+        "pending" | "authorized" => PaymentStatus::Pending,
+        "paid" => PaymentStatus::Paid,
+        "failed" => PaymentStatus::Failed,
+        "canceled" => PaymentStatus::Canceled,
+        "expired" => PaymentStatus::Expired,
+        _ => return Err(PaymentError::InvalidResponse(format!("Unknown payment status '{status}'"))),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+    use super::*;
+
+    /// Start a mock of the provider's API on a random local port, returning its base URL
+    fn mock_server() -> String {
+        async fn create(req: HttpRequest, body: web::Json<serde_json::Value>) -> HttpResponse {
+            assert_eq!(req.headers().get("Authorization").unwrap(), "Bearer test_key");
+            assert_eq!(body["amount"]["value"], "12.50");
+            HttpResponse::Created().json(serde_json::json!({
+                "id": "tr_1",
+                "status": "open",
+                "amount": body["amount"],
+                "metadata": body["metadata"],
+                "_links": { "checkout": { "href": "https://pay.example.com/tr_1" } },
+            }))
+        }
+
+        async fn get(path: web::Path<String>) -> HttpResponse {
+            HttpResponse::Ok().json(serde_json::json!({
+                "id": path.into_inner(),
+                "status": "paid",
+                "amount": { "currency": "EUR", "value": "12.50" },
+                "metadata": { "reference": "invoice_1" },
+            }))
+        }
+
+        let server = HttpServer::new(|| App::new()
+            .route("/v2/payments", web::post().to(create))
+            .route("/v2/payments/{id}", web::get().to(get))
+        )
+            .workers(1)
+            .bind("127.0.0.1:0")
+            .unwrap();
+
+        let base_url = format!("http://{}", server.addrs()[0]);
+        actix_web::rt::spawn(server.run());
+        base_url
+    }
+
+    fn provider(base_url: String) -> MollieProvider {
+        MollieProvider::new(&PaymentConfig {
+            base_url,
+            api_key: "test_key".to_string(),
+            webhook_secret: "secret".to_string(),
+        }).unwrap()
+    }
+
+    fn sign(body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[actix_web::test]
+    async fn create_payment_and_handle_callback() {
+        let provider = provider(mock_server());
+
+        let link = provider.create_payment(&PaymentRequest {
+            amount: 12.5,
+            currency: "EUR".to_string(),
+            description: "Invoice 1".to_string(),
+            reference: "invoice_1".to_string(),
+            redirect_url: "https://example.com/paid".to_string(),
+            webhook_url: "https://example.com/callback".to_string(),
+        }).await.unwrap();
+        assert_eq!(link.checkout_url, "https://pay.example.com/tr_1");
+
+        let body = br#"{"id":"tr_1"}"#;
+        let update = provider.handle_callback(Some(&sign(body)), body).await.unwrap();
+        assert_eq!(update, PaymentUpdate {
+            payment_id: "tr_1".to_string(),
+            reference: "invoice_1".to_string(),
+            status: PaymentStatus::Paid,
+            amount: 12.5,
+            currency: "EUR".to_string(),
+        });
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_signature() {
+        // Never reaches the provider, so no server is needed
+        let provider = provider("http://127.0.0.1:1".to_string());
+        let body = br#"{"id":"tr_1"}"#;
+
+        assert!(matches!(provider.handle_callback(None, body).await, Err(PaymentError::InvalidSignature)));
+        assert!(matches!(provider.handle_callback(Some(&sign(b"other")), body).await, Err(PaymentError::InvalidSignature)));
+    }
+}
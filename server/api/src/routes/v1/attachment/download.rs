@@ -0,0 +1,40 @@
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::{Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue};
+use serde::Deserialize;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::attachment::{content_key, get_attachment};
+use crate::session::Session;
+use crate::storage::Storage;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    attachment_id: String,
+}
+
+/// Download the content of an attachment
+pub async fn download(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<HttpResponse> {
+    let attachment = get_attachment(&data.driver, &session.user(&data.driver).await?, &query.attachment_id, false).await?;
+    let content = data.storage.get(&content_key(&attachment.org_id, &attachment.content_hash)).await?
+        .ok_or(Error::NotFound("The content of the attachment is missing".to_string()))?;
+
+    let file_name = if attachment.file_name.is_ascii() {
+        DispositionParam::Filename(attachment.file_name)
+    } else {
+        DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: attachment.file_name.into_bytes(),
+        })
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(attachment.content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![file_name],
+        })
+        // Never let browsers render the content as another type than it was validated as
+        .insert_header(("X-Content-Type-Options", "nosniff"))
+        .body(content))
+}
@@ -0,0 +1,28 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::Attachment;
+use proto::AttachmentListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::attachment::{dal_attachment_to_proto, owner_access, parse_owner_type};
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    owner_type: String,
+    owner_id: String,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<AttachmentListResponse>> {
+    let owner_type = parse_owner_type(&query.owner_type)?;
+    owner_access(&data.driver, &session.user(&data.driver).await?, owner_type, &query.owner_id, false).await?;
+
+    let attachments = Attachment::list_for_owner(&data.driver, owner_type, &query.owner_id).await?.into_iter()
+        .map(dal_attachment_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(AttachmentListResponse {
+        attachments,
+    }))
+}
@@ -0,0 +1,188 @@
+use std::str::FromStr;
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use sha2::{Digest, Sha256};
+use dal::Driver;
+use dal::entities::{Attachment, AttachmentOwnerType, Entity, OrgScope, PurchaseInvoice, Supplier, User};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+use crate::routes::v1::OrgAccess;
+use crate::storage::{Storage, StorageBackend};
+
+mod download;
+mod list;
+mod remove;
+mod upload;
+
+/// The maximum size of an attachment in bytes
+const MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+/// The maximum number of attachments of a single entity
+const MAX_ATTACHMENTS_PER_OWNER: usize = 50;
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// The content types attachments may have, with the bytes content of binary types starts with.
+/// Content of text types must be valid UTF-8
+const ALLOWED_CONTENT_TYPES: [(&str, Option<&[u8]>); 7] = [
+    ("application/pdf", Some(b"%PDF-")),
+    ("image/png", Some(b"\x89PNG\r\n\x1a\n")),
+    ("image/jpeg", Some(b"\xff\xd8\xff")),
+    ("text/plain", None),
+    ("text/csv", None),
+    ("text/xml", None),
+    ("application/xml", None),
+];
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/attachment")
+            .route("", web::get().to(download::download))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .service(web::resource("/upload")
+                .app_data(web::PayloadConfig::new(MAX_ATTACHMENT_SIZE))
+                .route(web::post().to(upload::upload))
+            )
+        );
+    }
+}
+
+fn dal_attachment_to_proto(attachment: Attachment) -> proto::Attachment {
+    proto::Attachment {
+        id: attachment.id,
+        owner_type: attachment.owner_type.to_string(),
+        owner_id: attachment.owner_id,
+        file_name: attachment.file_name,
+        content_type: attachment.content_type,
+        size: attachment.size,
+        content_hash: attachment.content_hash,
+        created_at: attachment.created_at,
+    }
+}
+
+fn parse_owner_type(name: &str) -> WebResult<AttachmentOwnerType> {
+    AttachmentOwnerType::from_str(name).map_err(|_| Error::BadRequest(format!("Unknown owner type '{name}'")))
+}
+
+/// Load the user's access to the organization owning the entity.
+/// Viewing attachments requires the scope needed to view the entity, `modify` requires the scope needed to modify it
+async fn owner_access(driver: &Driver, user: &User, owner_type: AttachmentOwnerType, owner_id: &str, modify: bool) -> WebResult<OrgAccess> {
+    let not_found = || Error::NotFound(format!("{} not found", owner_type.to_string()));
+    let (org_id, view_scope, modify_scope) = match owner_type {
+        AttachmentOwnerType::PurchaseInvoice => {
+            let invoice = PurchaseInvoice::get(driver, owner_id.to_string()).await?.ok_or_else(not_found)?;
+            (invoice.org_id, OrgScope::GetPurchaseInvoice, OrgScope::ManagePurchaseInvoice)
+        },
+        AttachmentOwnerType::Supplier => {
+            let supplier = Supplier::get(driver, owner_id.to_string()).await?.ok_or_else(not_found)?;
+            (supplier.org_id, OrgScope::GetPurchaseInvoice, OrgScope::ManagePurchaseInvoice)
+        },
+    };
+
+    OrgAccess::require(driver, user, &org_id, if modify { modify_scope } else { view_scope }).await
+}
+
+/// Get an attachment, requiring the user to have access to the entity it is attached to
+async fn get_attachment(driver: &Driver, user: &User, attachment_id: &str, modify: bool) -> WebResult<Attachment> {
+    let attachment = Attachment::get(driver, attachment_id.to_string()).await?
+        .ok_or(Error::NotFound("Attachment not found".to_string()))?;
+    owner_access(driver, user, attachment.owner_type, &attachment.owner_id, modify).await?;
+
+    Ok(attachment)
+}
+
+/// The storage key of content of the organization. Content is shared by all attachments of the organization with the same hash
+fn content_key(org_id: &str, content_hash: &str) -> String {
+    format!("attachments/{org_id}/{content_hash}")
+}
+
+fn hash_content(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// Remove the stored content of removed attachments, if no other attachment of the organization uses it.
+///
+/// The check and the removal happen under the content lock, so an upload recording an attachment with the same content
+/// either is visible to the check, or records its attachment after the removal and then stores the content again
+async fn release_content(driver: &Driver, storage: &StorageBackend, org_id: &str, content_hash: &str) -> WebResult<()> {
+    let mut lock = Attachment::lock_content(driver, org_id).await?;
+    if !lock.is_content_referenced(content_hash).await? {
+        storage.delete(&content_key(org_id, content_hash)).await?;
+    }
+
+    lock.commit().await?;
+    Ok(())
+}
+
+/// Remove the stored content of the attachments of a removed entity. The attachments themselves are removed
+/// with [Attachment::remove_for_owner_with_tx], in the transaction the entity is removed in
+pub(super) async fn release_attachments(driver: &Driver, storage: &StorageBackend, attachments: &[Attachment]) -> WebResult<()> {
+    for attachment in attachments {
+        release_content(driver, storage, &attachment.org_id, &attachment.content_hash).await?;
+    }
+
+    Ok(())
+}
+
+/// Remove the stored content of all attachments of the organization. The attachments themselves are removed with the organization
+pub(super) async fn remove_org_content(storage: &StorageBackend, org_id: &str, content_hashes: &[String]) -> WebResult<()> {
+    for content_hash in content_hashes {
+        storage.delete(&content_key(org_id, content_hash)).await?;
+    }
+
+    Ok(())
+}
+
+/// Parse the `Content-Type` of an upload, requiring that it is allowed and that the content matches it
+fn parse_content_type(content_type: Option<&str>, content: &[u8]) -> WebResult<&'static str> {
+    let content_type = content_type.ok_or(Error::BadRequest("No content type provided".to_string()))?;
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+
+    let (allowed, magic) = ALLOWED_CONTENT_TYPES.iter()
+        .find(|(allowed, _)| essence.eq(allowed))
+        .ok_or_else(|| Error::BadRequest(format!("Content type '{essence}' is not allowed")))?;
+
+    let matches = match magic {
+        Some(magic) => content.starts_with(magic),
+        None => std::str::from_utf8(content).is_ok(),
+    };
+
+    if !matches {
+        return Err(Error::BadRequest(format!("The content is not of type '{allowed}'")));
+    }
+
+    Ok(allowed)
+}
+
+fn require_valid_file_name(file_name: &str) -> WebResult<()> {
+    if file_name.trim().is_empty() {
+        return Err(Error::BadRequest("File name may not be empty".to_string()));
+    }
+
+    if file_name.len() > MAX_FILE_NAME_LENGTH {
+        return Err(Error::BadRequest(format!("File name may be at most {MAX_FILE_NAME_LENGTH} characters")));
+    }
+
+    if file_name.chars().any(|x| x.is_control() || x == '/' || x == '\\') {
+        return Err(Error::BadRequest("File name may not contain control characters or path separators".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn content_types() {
+        assert_eq!(parse_content_type(Some("application/pdf"), b"%PDF-1.7 ...").unwrap(), "application/pdf");
+        assert_eq!(parse_content_type(Some("Text/Plain; charset=utf-8"), "Bon appétit".as_bytes()).unwrap(), "text/plain");
+
+        assert!(parse_content_type(None, b"%PDF-1.7").is_err());
+        assert!(parse_content_type(Some("application/pdf"), b"<html>").is_err());
+        assert!(parse_content_type(Some("text/html"), b"<html>").is_err());
+        assert!(parse_content_type(Some("text/plain"), b"\xff\xfe").is_err());
+    }
+}
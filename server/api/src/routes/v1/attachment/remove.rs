@@ -0,0 +1,36 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder};
+use proto::AttachmentRemoveRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::attachment::{get_attachment, release_content};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<AttachmentRemoveRequest>) -> WebResult<Empty> {
+    let attachment = get_attachment(&data.driver, &session.user(&data.driver).await?, &payload.attachment_id, true).await?;
+
+    let org_id = attachment.org_id.clone();
+    let attachment_id = attachment.id.clone();
+    let content_hash = attachment.content_hash.clone();
+    let diff = AuditDiff::new()
+        .removed("owner_type", attachment.owner_type.to_string())
+        .removed("owner_id", &attachment.owner_id)
+        .removed("file_name", &attachment.file_name)
+        .removed("content_hash", &attachment.content_hash);
+    let mut tx = data.driver.start_transaction().await?;
+    attachment.remove_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Attachment,
+        entity_id: attachment_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    release_content(&data.driver, &data.storage, &org_id, &content_hash).await?;
+    Ok(Empty)
+}
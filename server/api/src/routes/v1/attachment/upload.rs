@@ -0,0 +1,98 @@
+use actix_multiresponse::Payload;
+use actix_web::{HttpRequest, web};
+use actix_web::http::header::CONTENT_TYPE;
+use serde::Deserialize;
+use dal::entities::{Attachment, AttachmentBuilder, AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder};
+use proto::AttachmentUploadResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::attachment::{content_key, dal_attachment_to_proto, hash_content, MAX_ATTACHMENTS_PER_OWNER, owner_access, parse_content_type, parse_owner_type, require_valid_file_name};
+use crate::session::Session;
+use crate::storage::Storage;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    /// The type of entity to attach the file to, e.g. `PurchaseInvoice`
+    owner_type: String,
+    owner_id: String,
+    file_name: String,
+}
+
+/// Attach the file in the request body to an entity. The type of the file is taken from the `Content-Type` header.
+/// If the organization already stored a file with the same content, it is not stored again
+pub async fn upload(data: WebData, session: Session, req: HttpRequest, query: web::Query<Query>, body: web::Bytes) -> WebResult<Payload<AttachmentUploadResponse>> {
+    let owner_type = parse_owner_type(&query.owner_type)?;
+    let access = owner_access(&data.driver, &session.user(&data.driver).await?, owner_type, &query.owner_id, true).await?;
+
+    require_valid_file_name(&query.file_name)?;
+    if body.is_empty() {
+        return Err(Error::BadRequest("The file is empty".to_string()));
+    }
+
+    let content_type = parse_content_type(req.headers().get(CONTENT_TYPE).and_then(|x| x.to_str().ok()), &body)?;
+
+    if Attachment::list_for_owner(&data.driver, owner_type, &query.owner_id).await?.len() >= MAX_ATTACHMENTS_PER_OWNER {
+        return Err(Error::Conflict(format!("An entity may have at most {MAX_ATTACHMENTS_PER_OWNER} attachments")));
+    }
+
+    // The content is stored before the attachment is recorded, so the lock is only held briefly.
+    // Content is only removed under the lock, after checking that no attachment uses it. Until the attachment
+    // is recorded, the content may be removed again, so it is checked once more afterwards
+    let content_hash = hash_content(&body);
+    let key = content_key(&access.org.id, &content_hash);
+    let deduplicated = data.storage.exists(&key).await?;
+    if !deduplicated {
+        data.storage.put(&key, &body, content_type).await?;
+    }
+
+    let mut lock = Attachment::lock_content(&data.driver, &access.org.id).await?;
+    let attachment = lock.create(AttachmentBuilder {
+        org: &access.org,
+        owner_type,
+        owner_id: query.owner_id.clone(),
+        file_name: query.file_name.trim().to_string(),
+        content_type: content_type.to_string(),
+        size: body.len() as i64,
+        content_hash: content_hash.clone(),
+    }).await?;
+
+    AuditLogEntry::create(lock.transaction(), AuditLogEntryBuilder {
+        org_id: attachment.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Attachment,
+        entity_id: attachment.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("owner_type", attachment.owner_type.to_string())
+            .created("owner_id", &attachment.owner_id)
+            .created("file_name", &attachment.file_name)
+            .created("content_type", &attachment.content_type)
+            .created("size", attachment.size)
+            .created("content_hash", &attachment.content_hash),
+    }).await?;
+    lock.commit().await?;
+
+    if !data.storage.exists(&key).await? {
+        if let Err(e) = data.storage.put(&key, &body, content_type).await {
+            let mut tx = data.driver.start_transaction().await?;
+            attachment.remove_with_tx(&mut tx).await?;
+            AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+                org_id: attachment.org_id.clone(),
+                actor: session.actor(),
+                entity_type: AuditEntityType::Attachment,
+                entity_id: attachment.id.clone(),
+                action: AuditAction::Remove,
+                diff: AuditDiff::new()
+                    .removed("file_name", &attachment.file_name),
+            }).await?;
+            tx.commit().await?;
+
+            return Err(e.into());
+        }
+    }
+
+    Ok(Payload(AttachmentUploadResponse {
+        attachment: Some(dal_attachment_to_proto(attachment)),
+        deduplicated,
+    }))
+}
@@ -1,12 +1,16 @@
 use actix_multiresponse::Payload;
-use dal::entities::User;
+use actix_web::HttpRequest;
+use actix_web::http::header::USER_AGENT;
+use dal::entities::{SessionMetadata, User};
 use proto::{AuthenticationMethod, LoginRequest, LoginResponse};
 use proto::login_request::Authentication;
 use crate::error::{Error, WebResult};
+use crate::routes::v1::auth::dal_session_to_proto;
+use crate::routes::v1::org::invite::accept_invitation;
 use crate::WebData;
 
-pub async fn login(data: WebData, payload: Payload<LoginRequest>) -> WebResult<Payload<LoginResponse>> {
-    let mut user = User::get_by_email(&data.driver, &payload.email)?.ok_or(Error::Unauthorized(String::default()))?;
+pub async fn login(data: WebData, req: HttpRequest, payload: Payload<LoginRequest>) -> WebResult<Payload<LoginResponse>> {
+    let mut user = User::get_by_email(&data.driver, &payload.email).await?.ok_or(Error::Unauthorized(String::default()))?;
 
     let method = AuthenticationMethod::from_i32(payload.authentication_method).ok_or(Error::BadRequest("Invalid authentication method".to_string()))?;
     let authentication = match &payload.authentication {
@@ -18,7 +22,7 @@ pub async fn login(data: WebData, payload: Payload<LoginRequest>) -> WebResult<P
         AuthenticationMethod::Password => {
             match authentication {
                 Authentication::Password(password) => {
-                    if !user.is_password_correct(password, &data.config.password_pepper)? {
+                    if !user.is_password_correct(password, &data.config.password_pepper).await? {
                         return Err(Error::Unauthorized(String::default()));
                     }
                 },
@@ -26,14 +30,26 @@ pub async fn login(data: WebData, payload: Payload<LoginRequest>) -> WebResult<P
         }
     }
 
-    let session = user.create_session()?;
+    if let Some(token) = &payload.invitation_token {
+        accept_invitation(&data.driver, &user, token).await?;
+    }
+
+    let metadata = SessionMetadata {
+        user_agent: req.headers().get(USER_AGENT)
+            .and_then(|x| x.to_str().ok())
+            .map(|x| x.to_string()),
+        ip_address: req.connection_info().realip_remote_addr().map(|x| x.to_string()),
+    };
+
+    let session = user.create_session(data.config.session_expiry, metadata).await?;
+    let session_id = session.id.clone();
+    let session = proto::Session {
+        id: session_id.clone(),
+        ..dal_session_to_proto(session, &session_id)
+    };
 
     Ok(Payload(LoginResponse {
-        session: Some(proto::Session {
-            id: session.id,
-            expires_at: session.expires_at,
-            last_used: session.last_used
-        }),
+        session: Some(session),
         user: Some(proto::User {
             id: user.id,
             name: user.name,
@@ -1,8 +1,9 @@
 use actix_multiresponse::Payload;
-use dal::entities::{Entity, User, UserBuilder};
+use dal::entities::{User, UserBuilder};
 use proto::{AuthenticationMethod, RegisterRequest, RegisterResponse};
 use proto::register_request::Authentication;
 use crate::error::{Error, WebResult};
+use crate::routes::v1::org::invite::{audit_accepted, get_invitation_by_token, invitation_error};
 use crate::WebData;
 
 pub async fn register(data: WebData, payload: Payload<RegisterRequest>) -> WebResult<Payload<RegisterResponse>> {
@@ -23,13 +24,28 @@ pub async fn register(data: WebData, payload: Payload<RegisterRequest>) -> WebRe
         }
     };
 
-    let mut user = User::create(&data.driver, UserBuilder {
+    let invitation = match &payload.invitation_token {
+        Some(token) => Some(get_invitation_by_token(&data.driver, token).await?),
+        None => None,
+    };
+
+    let org = match &invitation {
+        Some(invitation) => Some(invitation.prepare_accept(&payload.email).await.map_err(invitation_error)?),
+        None => None,
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    let (user, _association) = User::register_with_tx(&mut tx, &data.driver, UserBuilder {
         name: payload.name.clone(),
         email: payload.email.clone(),
         authentication: user_auth,
-    })?;
+    }, invitation.as_ref().zip(org)).await?;
+
+    if let Some(invitation) = &invitation {
+        audit_accepted(&mut tx, &user, invitation).await?;
+    }
 
-    let _association = user.associate_email(&payload.email)?;
+    tx.commit().await?;
 
     // TODO send an email
 
@@ -1,5 +1,6 @@
 use actix_web::web;
 use actix_web::web::ServiceConfig;
+use dal::entities::SessionDescription;
 use crate::routable::Routable;
 
 mod login;
@@ -14,4 +15,19 @@ impl Routable for Router {
             .configure(session::Router::configure)
         );
     }
+}
+
+/// Convert a session to Proto format. `current_session` is the ID of the session used to make the request.
+/// The session token is left out, it must only be returned to the client logging in
+fn dal_session_to_proto(session: SessionDescription, current_session: &str) -> proto::Session {
+    proto::Session {
+        current: session.id.eq(current_session),
+        id: String::new(),
+        public_id: session.public_id,
+        expires_at: session.expires_at,
+        last_used: session.last_used,
+        created_at: session.created_at,
+        user_agent: session.user_agent,
+        ip_address: session.ip_address,
+    }
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use actix_multiresponse::Payload;
+use proto::SessionListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::auth::dal_session_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn list(data: WebData, session: Session) -> WebResult<Payload<SessionListResponse>> {
+    let user = session.user(&data.driver).await?;
+    let sessions = user.list_sessions().await?
+        .into_iter()
+        .map(|x| dal_session_to_proto(x, &session.id))
+        .collect::<Vec<_>>();
+
+    Ok(Payload(SessionListResponse {
+        sessions
+    }))
+}
\ No newline at end of file
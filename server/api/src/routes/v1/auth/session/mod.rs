@@ -4,12 +4,14 @@ use crate::routable::Routable;
 
 mod user;
 mod remove;
+mod list;
 
 pub struct Router;
 
 impl Routable for Router {
     fn configure(config: &mut ServiceConfig) {
         config.service(web::scope("/session")
+            .route("/list", web::get().to(list::list))
             .route("/remove", web::post().to(remove::remove))
             .route("/user", web::get().to(user::user))
         );
@@ -1,28 +1,30 @@
 use actix_web::web;
 use serde::Deserialize;
 use crate::empty::Empty;
-use crate::error::WebResult;
+use crate::error::{Error, WebResult};
 use crate::session::Session;
 use crate::WebData;
 
 #[derive(Debug, Deserialize)]
 pub struct Query {
+    /// The public ID of the session to remove, as returned by the session list
     id: Option<String>,
     all: Option<bool>,
 }
 
 pub async fn remove(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Empty> {
-    let mut user = session.user(&data.driver)?;
+    let mut user = session.user(&data.driver).await?;
 
     if let Some(id) = &query.id {
-        user.delete_session(id)?;
+        if !user.delete_session_by_public_id(id).await? {
+            return Err(Error::NotFound("Session does not exist".to_string()));
+        }
     } else if let Some(true) = &query.all {
-        user.list_sessions()?
-            .into_iter()
-            .map(|x| user.delete_session(&x.id))
-            .collect::<Result<Vec<_>, dal::Error>>()?;
+        for session in user.list_sessions().await? {
+            user.delete_session(&session.id).await?;
+        }
     } else {
-        user.delete_session(&session.id)?;
+        user.delete_session(&session.id).await?;
     }
 
     Ok(Empty)
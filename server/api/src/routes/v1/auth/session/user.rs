@@ -4,7 +4,7 @@ use crate::session::Session;
 use crate::WebData;
 
 pub async fn user(data: WebData, session: Session) -> WebResult<Payload<proto::User>> {
-    let user = session.user(&data.driver)?;
+    let user = session.user(&data.driver).await?;
     Ok(Payload(proto::User {
         id: user.id,
         name: user.name,
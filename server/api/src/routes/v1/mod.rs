@@ -1,13 +1,21 @@
+use std::str::FromStr;
 use actix_web::web;
 use actix_web::web::ServiceConfig;
+use serde::Deserialize;
 use dal::Driver;
-use dal::entities::{Org, OrgScope, User, Entity};
+use dal::entities::{Entity, Org, OrgScope, OrgUser, User};
+use dal::pagination::{DEFAULT_PAGE_SIZE, PageRequest, SortDirection, SortKey};
 use crate::error::{Error, WebResult};
 use crate::routable::Routable;
 
 mod auth;
 mod org;
 mod product;
+mod time;
+mod supplier;
+mod purchase;
+mod attachment;
+mod payment;
 
 pub struct Router;
 
@@ -17,22 +25,103 @@ impl Routable for Router {
             .configure(auth::Router::configure)
             .configure(org::Router::configure)
             .configure(product::Router::configure)
+            .configure(time::Router::configure)
+            .configure(supplier::Router::configure)
+            .configure(purchase::Router::configure)
+            .configure(attachment::Router::configure)
+            .configure(payment::Router::configure)
         );
     }
 }
 
-struct AccessResult<'a> {
-    pub accessible: bool,
-    pub org: Org<'a>
+/// The access a user has to an organization.
+/// The organization and the user's link to it are loaded once, after which any number of scopes can be checked.
+///
+/// If the organization does not exist, or the user is not part of it, a `404 Not Found` is returned,
+/// so that the existence of organizations is not leaked. If the user is part of the organization,
+/// but lacks the required scope, a `403 Forbidden` is returned.
+struct OrgAccess {
+    pub org: Org,
+    pub org_user: OrgUser,
 }
 
-/// Checks if a user can access the requested scope in the requested organization
-fn can_access<'a>(driver: &'a Driver, user: &User<'_>, org_id: &str, scope: OrgScope) -> WebResult<AccessResult<'a>> {
-    let org = Org::get(&driver, org_id.to_string())?.ok_or(Error::Unauthorized("The requested organization does not exist or the user has no access".to_string()))?;
-    let scopes = org.list_scopes(user)?;
+impl OrgAccess {
+    /// Load the user's access to the organization
+    async fn load(driver: &Driver, user: &User, org_id: &str) -> WebResult<Self> {
+        let not_found = || Error::NotFound("The requested organization does not exist or the user has no access".to_string());
 
-    Ok(AccessResult {
-        accessible: scopes.contains(&scope),
-        org
-    })
-}
\ No newline at end of file
+        let org = Org::get(driver, org_id.to_string()).await?.ok_or_else(not_found)?;
+        let org_user = org.get_user(user).await?.ok_or_else(not_found)?;
+
+        Ok(Self {
+            org,
+            org_user,
+        })
+    }
+
+    /// Load the user's access to the organization and require that the user has the provided scope
+    async fn require(driver: &Driver, user: &User, org_id: &str, scope: OrgScope) -> WebResult<Self> {
+        let access = Self::load(driver, user, org_id).await?;
+        access.check(&scope)?;
+        Ok(access)
+    }
+
+    /// Whether the user has the provided scope. Organization admins have every scope
+    fn has_scope(&self, scope: &OrgScope) -> bool {
+        self.org_user.is_org_admin || self.org_user.scopes.contains(scope)
+    }
+
+    /// Require that the user has the provided scope
+    fn check(&self, scope: &OrgScope) -> WebResult<()> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(Error::Forbidden(format!("Missing scope {}", scope.to_string())))
+        }
+    }
+
+    /// Require that the user may grant the provided scopes to others, directly, through a role or through an invitation.
+    /// Admin scopes may only be granted by organization admins
+    fn check_grantable<'a>(&self, scopes: impl IntoIterator<Item = &'a OrgScope>) -> WebResult<()> {
+        if self.org_user.is_org_admin {
+            return Ok(());
+        }
+
+        match scopes.into_iter().find(|x| OrgScope::admin_scopes().contains(x)) {
+            Some(scope) => Err(Error::Forbidden(format!("Only organization admins can grant scope {}", scope.to_string()))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Pagination parameters accepted by listing endpoints, alongside the endpoint's own query parameters
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    /// The `nextCursor` of the previous page
+    cursor: Option<String>,
+    limit: Option<u32>,
+    sort: Option<String>,
+    direction: Option<String>,
+}
+
+impl PageQuery {
+    /// Create the request for the page. Without explicit sort parameters,
+    /// the listing's default sort field and `default_direction` are used
+    fn page_request<S: SortKey + FromStr + Default>(&self, default_direction: SortDirection) -> WebResult<PageRequest<S>> {
+        let sort = match &self.sort {
+            Some(x) => S::from_str(x).map_err(|_| Error::BadRequest(format!("Cannot sort on '{x}'")))?,
+            None => S::default(),
+        };
+
+        let direction = match &self.direction {
+            Some(x) => SortDirection::from_str(x).map_err(|_| Error::BadRequest(format!("Unknown sort direction '{x}'")))?,
+            None => default_direction,
+        };
+
+        PageRequest::new(sort, direction, self.limit.unwrap_or(DEFAULT_PAGE_SIZE), self.cursor.as_deref())
+            .map_err(|e| match e {
+                dal::Error::InvalidCursor => Error::BadRequest("Invalid cursor".to_string()),
+                e => e.into(),
+            })
+    }
+}
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{AuditAction, AuditEntityType, AuditLogEntry, AuditLogFilter, AuditLogSort, OrgScope};
+use dal::pagination::SortDirection;
+use proto::OrgAuditLogResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::{OrgAccess, PageQuery};
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    actor_id: Option<String>,
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    action: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+}
+
+pub async fn audit(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<OrgAuditLogResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetAuditLog).await?;
+
+    let filter = AuditLogFilter {
+        actor_id: query.actor_id.clone(),
+        entity_type: query.entity_type.as_ref()
+            .map(|x| AuditEntityType::from_str(x).map_err(|_| Error::BadRequest(format!("Unknown entity type '{x}'"))))
+            .transpose()?,
+        entity_id: query.entity_id.clone(),
+        action: query.action.as_ref()
+            .map(|x| AuditAction::from_str(x).map_err(|_| Error::BadRequest(format!("Unknown action '{x}'"))))
+            .transpose()?,
+        since: query.since,
+        until: query.until,
+    };
+
+    // Newest entries first, unless requested otherwise
+    let page = AuditLogEntry::list_for_org(&data.driver, &access.org.id, &filter, &page.page_request::<AuditLogSort>(SortDirection::Desc)?).await?;
+    let entries = page.items.into_iter()
+        .map(|x| proto::AuditLogEntry {
+            actor_type: x.actor.actor_type().to_string(),
+            actor_id: x.actor.id().to_string(),
+            id: x.id,
+            org_id: x.org_id,
+            entity_type: x.entity_type.to_string(),
+            entity_id: x.entity_id,
+            action: x.action.to_string(),
+            diff: x.diff.to_string(),
+            created_at: x.created_at,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Payload(OrgAuditLogResponse {
+        entries,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
+    }))
+}
\ No newline at end of file
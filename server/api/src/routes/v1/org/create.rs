@@ -1,16 +1,28 @@
 use actix_multiresponse::Payload;
-use dal::entities::{Entity, Org, OrgBuilder};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Org, OrgBuilder};
 use proto::{CreateOrgRequest, CreateOrgResponse};
 use crate::error::WebResult;
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn create(data: WebData, session: Session, payload: Payload<CreateOrgRequest>) -> WebResult<Payload<CreateOrgResponse>> {
-    let user = session.user(&data.driver)?;
-    let org = Org::create(&data.driver, OrgBuilder {
+    let user = session.user(&data.driver).await?;
+    let mut tx = data.driver.start_transaction().await?;
+    let org = Org::create_with_tx(&mut tx, &data.driver, OrgBuilder {
         name: payload.name.clone(),
         creator: &user
-    })?;
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Org,
+        entity_id: org.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &org.name),
+    }).await?;
+    tx.commit().await?;
 
     Ok(Payload(CreateOrgResponse {
         org: Some(proto::Org {
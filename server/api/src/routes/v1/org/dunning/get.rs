@@ -0,0 +1,26 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{DunningLevels, OrgScope};
+use proto::DunningLevelsGetResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::dunning::dal_dunning_level_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn get(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<DunningLevelsGetResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetOrg).await?;
+    let levels = DunningLevels::get(&data.driver, &access.org).await?;
+
+    Ok(Payload(DunningLevelsGetResponse {
+        levels: levels.levels.into_iter()
+            .map(dal_dunning_level_to_proto)
+            .collect(),
+    }))
+}
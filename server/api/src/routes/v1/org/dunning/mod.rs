@@ -0,0 +1,72 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::entities::DunningLevel;
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod get;
+mod update;
+
+/// The maximum length of the subject of a reminder
+const MAX_SUBJECT_LENGTH: usize = 255;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/dunning")
+            .route("", web::get().to(get::get))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_dunning_level_to_proto(level: DunningLevel) -> proto::DunningLevel {
+    proto::DunningLevel {
+        days_after_due: level.days_after_due,
+        email_subject: level.email_subject,
+        email_body: level.email_body,
+        late_fee: level.late_fee,
+        interest_percentage: level.interest_percentage,
+    }
+}
+
+/// Validate the dunning levels of a request. Every level must have a distinct number of days after the due date
+fn proto_dunning_levels_to_dal(levels: &[proto::DunningLevel]) -> WebResult<Vec<DunningLevel>> {
+    let mut result: Vec<DunningLevel> = Vec::with_capacity(levels.len());
+    for level in levels {
+        if result.iter().any(|x| x.days_after_due == level.days_after_due) {
+            return Err(Error::BadRequest(format!("More than one level is reached {} days after the due date", level.days_after_due)));
+        }
+
+        if level.email_subject.trim().is_empty() || level.email_body.trim().is_empty() {
+            return Err(Error::BadRequest("The subject and body of a reminder may not be empty".to_string()));
+        }
+
+        if level.email_subject.len() > MAX_SUBJECT_LENGTH {
+            return Err(Error::BadRequest(format!("The subject of a reminder may be at most {MAX_SUBJECT_LENGTH} characters")));
+        }
+
+        if let Some(late_fee) = level.late_fee {
+            if !late_fee.is_finite() || late_fee < 0.0 {
+                return Err(Error::BadRequest("Late fee may not be negative".to_string()));
+            }
+        }
+
+        if let Some(interest_percentage) = level.interest_percentage {
+            if !(0.0..=100.0).contains(&interest_percentage) {
+                return Err(Error::BadRequest(format!("Interest percentage {interest_percentage} is not between 0 and 100")));
+            }
+        }
+
+        result.push(DunningLevel {
+            days_after_due: level.days_after_due,
+            email_subject: level.email_subject.clone(),
+            email_body: level.email_body.clone(),
+            late_fee: level.late_fee,
+            interest_percentage: level.interest_percentage,
+        });
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,37 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, DunningLevels, OrgScope};
+use proto::DunningLevelsUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::dunning::proto_dunning_levels_to_dal;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<DunningLevelsUpdateRequest>) -> WebResult<Empty> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::UpdateOrg).await?;
+
+    let mut levels = DunningLevels::get(&data.driver, &access.org).await?;
+    let original = levels.levels.clone();
+
+    levels.levels = proto_dunning_levels_to_dal(&payload.levels)?;
+    let mut tx = data.driver.start_transaction().await?;
+    levels.save_with_tx(&mut tx).await?;
+
+    let diff = AuditDiff::new()
+        .field("levels", &original, &levels.levels);
+
+    if !diff.is_empty() {
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: access.org.id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::DunningLevels,
+            entity_id: access.org.id.clone(),
+            action: AuditAction::Update,
+            diff,
+        }).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Empty)
+}
@@ -3,8 +3,9 @@ use actix_web::web;
 use serde::Deserialize;
 use dal::entities::OrgScope;
 use proto::GetOrgResponse;
-use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::dal_org_user_to_proto;
 use crate::session::Session;
 use crate::WebData;
 
@@ -14,32 +15,11 @@ pub struct Query {
 }
 
 pub async fn get(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<GetOrgResponse>> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &query.id, OrgScope::GetOrg)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
-    }
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.id, OrgScope::GetOrg).await?;
 
-    let org_users = access.org.list_users()?;
-    let org_users = org_users.into_iter()
-        .map(|x| {
-            let scopes = OrgScope::variants()
-                .into_iter()
-                .map(|scope| proto::OrgScope {
-                    name: scope.to_string(),
-                    enabled: x.scopes.contains(&scope)
-                })
-                .collect::<Vec<_>>();
-
-            proto::OrgUser {
-                user: Some(proto::User {
-                    name: x.user.name,
-                    id: x.user.id,
-                    email: x.user.email
-                }),
-                is_org_admin: x.is_org_admin,
-                org_scopes: scopes,
-            }
-        })
+    let org_users = access.org.list_users().await?
+        .into_iter()
+        .map(dal_org_user_to_proto)
         .collect::<Vec<_>>();
 
     Ok(Payload(GetOrgResponse {
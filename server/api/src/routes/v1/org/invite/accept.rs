@@ -0,0 +1,14 @@
+use actix_multiresponse::Payload;
+use proto::OrgInviteAcceptRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::org::invite::accept_invitation;
+use crate::session::Session;
+use crate::WebData;
+
+/// Accept an invitation as a user who is already logged in
+pub async fn accept(data: WebData, session: Session, payload: Payload<OrgInviteAcceptRequest>) -> WebResult<Empty> {
+    let user = session.user(&data.driver).await?;
+    accept_invitation(&data.driver, &user, &payload.token).await?;
+    Ok(Empty)
+}
\ No newline at end of file
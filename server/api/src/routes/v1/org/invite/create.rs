@@ -0,0 +1,67 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgInvitation, OrgInvitationBuilder, OrgScope};
+use proto::{OrgInviteCreateRequest, OrgInviteCreateResponse};
+use crate::error::{Error, WebResult};
+use crate::routes::v1::org::invite::{dal_invitation_to_proto, send_invitation, INVITATION_EXPIRY};
+use crate::routes::v1::org::{parse_language, parse_scope};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::{mail, WebData};
+
+pub async fn create(data: WebData, session: Session, payload: Payload<OrgInviteCreateRequest>) -> WebResult<Payload<OrgInviteCreateResponse>> {
+    let user = session.user(&data.driver).await?;
+    let access = OrgAccess::require(&data.driver, &user, &payload.org_id, OrgScope::OrgUserManagment).await?;
+    if payload.is_org_admin && !access.org_user.is_org_admin {
+        return Err(Error::Forbidden("Only organization admins can invite organization admins".to_string()));
+    }
+
+    // Checked before storing the invitation, so that it cannot be stored without being sent
+    mail::parse_address(&payload.email)?;
+
+    let language = parse_language(payload.language.as_deref())?;
+
+    let mut scopes = Vec::new();
+    for name in &payload.scopes {
+        let scope = parse_scope(name)?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    if scopes.is_empty() {
+        scopes = OrgScope::default_scopes().to_vec();
+    }
+
+    access.check_grantable(&scopes)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let invitation = OrgInvitation::create_with_tx(&mut tx, &data.driver, OrgInvitationBuilder {
+        org: &access.org,
+        email: payload.email.clone(),
+        is_org_admin: payload.is_org_admin,
+        scopes,
+        invited_by: &user,
+        language,
+        expiry: INVITATION_EXPIRY,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: invitation.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgInvitation,
+        entity_id: invitation.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("email", &invitation.email)
+            .created("is_org_admin", invitation.is_org_admin)
+            .created("language", invitation.language.code())
+            .created("scopes", invitation.scopes.iter().map(|x| x.to_string()).collect::<Vec<_>>()),
+    }).await?;
+    tx.commit().await?;
+
+    send_invitation(&data, &access.org, &invitation).await?;
+
+    Ok(Payload(OrgInviteCreateResponse {
+        invitation: Some(dal_invitation_to_proto(invitation)),
+    }))
+}
\ No newline at end of file
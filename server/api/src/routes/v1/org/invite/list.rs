@@ -0,0 +1,28 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgInvitation, OrgScope};
+use proto::OrgInviteListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::org::invite::dal_invitation_to_proto;
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<OrgInviteListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::OrgUserManagment).await?;
+
+    let invitations = OrgInvitation::list_for_org(&data.driver, &access.org).await?
+        .into_iter()
+        .map(dal_invitation_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(OrgInviteListResponse {
+        invitations
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::time::Duration;
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::driver::Transaction;
+use dal::entities::{Actor, AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Org, OrgInvitation, OrgScope, User};
+use crate::error::{Error, WebResult};
+use crate::locale;
+use crate::routable::Routable;
+use crate::WebData;
+
+mod accept;
+mod create;
+mod list;
+mod resend;
+mod revoke;
+
+/// The amount of days an invitation remains valid
+const INVITATION_EXPIRY_DAYS: u64 = 7;
+
+const INVITATION_EXPIRY: Duration = Duration::from_secs(INVITATION_EXPIRY_DAYS * 24 * 60 * 60);
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/invite")
+            .route("/accept", web::post().to(accept::accept))
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/resend", web::post().to(resend::resend))
+            .route("/revoke", web::post().to(revoke::revoke))
+        );
+    }
+}
+
+fn dal_invitation_to_proto(invitation: OrgInvitation) -> proto::OrgInvitation {
+    let org_scopes = OrgScope::variants()
+        .iter()
+        .map(|x| proto::OrgScope {
+            name: x.to_string(),
+            enabled: invitation.scopes.contains(x),
+        })
+        .collect::<Vec<_>>();
+
+    proto::OrgInvitation {
+        id: invitation.id,
+        org_id: invitation.org_id,
+        email: invitation.email,
+        is_org_admin: invitation.is_org_admin,
+        org_scopes,
+        invited_by: invitation.invited_by,
+        language: invitation.language.code().to_string(),
+        created_at: invitation.created_at,
+        expires_at: invitation.expires_at,
+    }
+}
+
+/// Email the invitation token to the invitee, in the language of the invitation
+async fn send_invitation(data: &WebData, org: &Org, invitation: &OrgInvitation) -> WebResult<()> {
+    let language = invitation.language;
+    let link = format!("{}/invite?token={}", data.config.frontend_host, invitation.token);
+    let expires_at = locale::format_date(language, invitation.expires_at);
+
+    let subject = locale::translate(language, "invitation.subject", &[("org", &org.name)]);
+    let body = locale::translate(language, "invitation.body", &[
+        ("org", &org.name),
+        ("link", &link),
+        ("expires_at", &expires_at),
+    ]);
+
+    data.mailer.send(&invitation.email, &subject, body).await
+}
+
+/// Accept the invitation with the provided token on behalf of `user`
+pub(in crate::routes::v1) async fn accept_invitation(driver: &Driver, user: &User, token: &str) -> WebResult<()> {
+    let invitation = get_invitation_by_token(driver, token).await?;
+    let mut org = invitation.prepare_accept(&user.email).await.map_err(invitation_error)?;
+
+    let mut tx = driver.start_transaction().await?;
+    invitation.accept_with_tx(&mut tx, &mut org, user).await?;
+    audit_accepted(&mut tx, user, &invitation).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub(in crate::routes::v1) async fn get_invitation_by_token(driver: &Driver, token: &str) -> WebResult<OrgInvitation> {
+    OrgInvitation::get_by_token(driver, token).await?.ok_or(Error::NotFound("Invitation not found".to_string()))
+}
+
+/// Map the errors of accepting an invitation to their response
+pub(in crate::routes::v1) fn invitation_error(error: dal::Error) -> Error {
+    match error {
+        dal::Error::ExpiredToken => Error::BadRequest("Invitation has expired".to_string()),
+        dal::Error::UnknownToken => Error::NotFound("Invitation not found".to_string()),
+        e => e.into(),
+    }
+}
+
+/// Record in the audit log that `user` joined the organization by accepting the invitation
+pub(in crate::routes::v1) async fn audit_accepted(tx: &mut Transaction, user: &User, invitation: &OrgInvitation) -> WebResult<()> {
+    AuditLogEntry::create(tx, AuditLogEntryBuilder {
+        org_id: invitation.org_id.clone(),
+        actor: Actor::User(user.id.clone()),
+        entity_type: AuditEntityType::OrgUser,
+        entity_id: user.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("invitation_id", &invitation.id)
+            .created("is_org_admin", invitation.is_org_admin),
+    }).await?;
+
+    Ok(())
+}
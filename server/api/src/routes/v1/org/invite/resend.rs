@@ -0,0 +1,33 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgInvitation, OrgScope};
+use proto::OrgInviteResendRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::org::invite::{send_invitation, INVITATION_EXPIRY};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+/// Generate a new token for the invitation, extend its expiry and email it again
+pub async fn resend(data: WebData, session: Session, payload: Payload<OrgInviteResendRequest>) -> WebResult<Empty> {
+    let mut invitation = OrgInvitation::get(&data.driver, payload.invitation_id.clone()).await?.ok_or(Error::NotFound("Invitation not found".to_string()))?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &invitation.org_id, OrgScope::OrgUserManagment).await?;
+
+    let previous_expires_at = invitation.expires_at;
+    let mut tx = data.driver.start_transaction().await?;
+    invitation.renew_with_tx(&mut tx, INVITATION_EXPIRY).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: invitation.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgInvitation,
+        entity_id: invitation.id.clone(),
+        action: AuditAction::Update,
+        diff: AuditDiff::new()
+            .field("expires_at", previous_expires_at, invitation.expires_at),
+    }).await?;
+    tx.commit().await?;
+
+    send_invitation(&data, &access.org, &invitation).await?;
+    Ok(Empty)
+}
\ No newline at end of file
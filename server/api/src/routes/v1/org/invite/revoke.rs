@@ -0,0 +1,30 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgInvitation, OrgScope};
+use proto::OrgInviteRevokeRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn revoke(data: WebData, session: Session, payload: Payload<OrgInviteRevokeRequest>) -> WebResult<Empty> {
+    let invitation = OrgInvitation::get(&data.driver, payload.invitation_id.clone()).await?.ok_or(Error::NotFound("Invitation not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &invitation.org_id, OrgScope::OrgUserManagment).await?;
+
+    let audit = AuditLogEntryBuilder {
+        org_id: invitation.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgInvitation,
+        entity_id: invitation.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("email", &invitation.email),
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    invitation.remove_with_tx(&mut tx).await?;
+    AuditLogEntry::create(&mut tx, audit).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
\ No newline at end of file
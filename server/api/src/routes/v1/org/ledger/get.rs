@@ -0,0 +1,24 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{LedgerAccounts, OrgScope};
+use proto::LedgerAccountsGetResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::ledger::dal_ledger_accounts_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn get(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<LedgerAccountsGetResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetOrg).await?;
+    let accounts = LedgerAccounts::get(&data.driver, &access.org).await?;
+
+    Ok(Payload(LedgerAccountsGetResponse {
+        accounts: Some(dal_ledger_accounts_to_proto(accounts)),
+    }))
+}
@@ -0,0 +1,72 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::entities::{LedgerAccounts, RevenueAccount};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod get;
+mod update;
+
+/// The maximum length of a ledger account number
+const MAX_ACCOUNT_LENGTH: usize = 32;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/ledger")
+            .route("", web::get().to(get::get))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_ledger_accounts_to_proto(accounts: LedgerAccounts) -> proto::LedgerAccounts {
+    proto::LedgerAccounts {
+        debtor_account: accounts.debtor_account,
+        default_revenue_account: accounts.default_revenue_account,
+        revenue_accounts: accounts.revenue_accounts.into_iter()
+            .map(|x| proto::RevenueAccount {
+                tax_percentage: x.tax_percentage,
+                account: x.account,
+            })
+            .collect(),
+    }
+}
+
+/// Require that an account number is not empty and fits in the database
+fn require_valid_account(account: &str) -> WebResult<()> {
+    if account.trim().is_empty() {
+        return Err(Error::BadRequest("Ledger account may not be empty".to_string()));
+    }
+
+    if account.len() > MAX_ACCOUNT_LENGTH {
+        return Err(Error::BadRequest(format!("Ledger account '{account}' is longer than {MAX_ACCOUNT_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+/// Validate the revenue accounts of a request. Every tax percentage may have only one account
+fn proto_revenue_accounts_to_dal(revenue_accounts: &[proto::RevenueAccount]) -> WebResult<Vec<RevenueAccount>> {
+    let mut result: Vec<RevenueAccount> = Vec::with_capacity(revenue_accounts.len());
+    for revenue_account in revenue_accounts {
+        require_valid_account(&revenue_account.account)?;
+
+        if !(0.0..=100.0).contains(&revenue_account.tax_percentage) {
+            return Err(Error::BadRequest(format!("Tax percentage {} is not between 0 and 100", revenue_account.tax_percentage)));
+        }
+
+        if result.iter().any(|x| x.tax_percentage.eq(&revenue_account.tax_percentage)) {
+            return Err(Error::BadRequest(format!("Tax percentage {} has more than one account", revenue_account.tax_percentage)));
+        }
+
+        result.push(RevenueAccount {
+            tax_percentage: revenue_account.tax_percentage,
+            account: revenue_account.account.clone(),
+        });
+    }
+
+    result.sort_by(|a, b| a.tax_percentage.total_cmp(&b.tax_percentage));
+    Ok(result)
+}
@@ -0,0 +1,46 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, LedgerAccounts, OrgScope};
+use proto::LedgerAccountsUpdateRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::ledger::{proto_revenue_accounts_to_dal, require_valid_account};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<LedgerAccountsUpdateRequest>) -> WebResult<Empty> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::UpdateOrg).await?;
+    let requested = payload.accounts.as_ref().ok_or(Error::BadRequest("No ledger accounts provided".to_string()))?;
+
+    for account in requested.debtor_account.iter().chain(&requested.default_revenue_account) {
+        require_valid_account(account)?;
+    }
+
+    let mut accounts = LedgerAccounts::get(&data.driver, &access.org).await?;
+    let original = accounts.clone();
+
+    accounts.debtor_account = requested.debtor_account.clone();
+    accounts.default_revenue_account = requested.default_revenue_account.clone();
+    accounts.revenue_accounts = proto_revenue_accounts_to_dal(&requested.revenue_accounts)?;
+    let mut tx = data.driver.start_transaction().await?;
+    accounts.save_with_tx(&mut tx).await?;
+
+    let diff = AuditDiff::new()
+        .field("debtor_account", &original.debtor_account, &accounts.debtor_account)
+        .field("default_revenue_account", &original.default_revenue_account, &accounts.default_revenue_account)
+        .field("revenue_accounts", &original.revenue_accounts, &accounts.revenue_accounts);
+
+    if !diff.is_empty() {
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: access.org.id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::LedgerAccounts,
+            entity_id: access.org.id.clone(),
+            action: AuditAction::Update,
+            diff,
+        }).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Empty)
+}
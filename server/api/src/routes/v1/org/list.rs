@@ -1,24 +1,28 @@
 use actix_multiresponse::Payload;
-use dal::entities::{Org, OrgScope};
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{Org, OrgFilter, OrgScope, OrgSort};
+use dal::pagination::SortDirection;
 use proto::ListOrgResponse;
 use crate::error::WebResult;
-use crate::routes::v1::can_access;
+use crate::routes::v1::PageQuery;
 use crate::session::Session;
 use crate::WebData;
 
-pub async fn list(data: WebData, session: Session) -> WebResult<Payload<ListOrgResponse>> {
-    let user = session.user(&data.driver)?;
-    let orgs = Org::list_available(&data.driver)?
-        .into_iter()
-        // Check if the user is allowed to access this org
-        .filter(|x| {
-            let access = match can_access(&data.driver, &user, &x.id, OrgScope::GetOrg) {
-                Ok(x) => x,
-                Err(_) => return false, // Skip on Err
-            };
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    name: Option<String>,
+}
 
-            access.accessible
-        })
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<ListOrgResponse>> {
+    let user = session.user(&data.driver).await?;
+    let filter = OrgFilter {
+        name: query.name.clone(),
+    };
+
+    // Only list the orgs the user is allowed to access
+    let page = Org::list_for_user(&data.driver, &user, &OrgScope::GetOrg, &filter, &page.page_request::<OrgSort>(SortDirection::Asc)?).await?;
+    let orgs = page.items.into_iter()
         .map(|x| proto::Org {
             name: x.name,
             id: x.id
@@ -26,6 +30,9 @@ pub async fn list(data: WebData, session: Session) -> WebResult<Payload<ListOrgR
         .collect::<Vec<_>>();
 
     Ok(Payload(ListOrgResponse {
-        orgs
+        orgs,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
     }))
-}
\ No newline at end of file
+}
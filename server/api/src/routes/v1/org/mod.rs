@@ -1,14 +1,22 @@
+use std::str::FromStr;
 use actix_web::web;
 use actix_web::web::ServiceConfig;
-use dal::entities::{Org, OrgScope, User};
-use crate::error::WebResult;
+use dal::entities::{Language, Org, OrgScope, OrgUser, User};
+use crate::error::{Error, WebResult};
 use crate::routable::Routable;
 
 mod get;
 mod list;
 mod create;
+mod audit;
 
 mod user;
+mod role;
+pub(super) mod invite;
+mod dunning;
+mod ledger;
+mod webhook;
+mod template;
 mod remove;
 
 pub struct Router;
@@ -17,7 +25,14 @@ impl Routable for Router {
     fn configure(config: &mut ServiceConfig) {
         config.service(web::scope("/org")
             .configure(user::Router::configure)
+            .configure(role::Router::configure)
+            .configure(invite::Router::configure)
+            .configure(dunning::Router::configure)
+            .configure(ledger::Router::configure)
+            .configure(webhook::Router::configure)
+            .configure(template::Router::configure)
             .route("", web::get().to(get::get))
+            .route("/audit", web::get().to(audit::audit))
             .route("/create", web::post().to(create::create))
             .route("/list", web::get().to(list::list))
             .route("/remove", web::post().to(remove::remove))
@@ -26,14 +41,42 @@ impl Routable for Router {
 }
 
 /// Retrieve all OrgScope's the user has for the provided organization in Proto format
-fn get_user_org_scopes_proto(user: &User<'_>, org: &Org<'_>) -> WebResult<Vec<proto::OrgScope>> {
-    let owned_scopes = org.list_scopes(user)?;
-    let org_scopes = OrgScope::variants()
+async fn get_user_org_scopes_proto(user: &User, org: &Org) -> WebResult<Vec<proto::OrgScope>> {
+    let owned_scopes = org.list_scopes(user).await?;
+    Ok(org_scopes_to_proto(&owned_scopes))
+}
+
+/// Convert the scopes a user owns to a list of all OrgScope's in Proto format
+fn org_scopes_to_proto(owned_scopes: &[OrgScope]) -> Vec<proto::OrgScope> {
+    OrgScope::variants()
         .into_iter()
         .map(|x| proto::OrgScope {
             name: x.to_string(),
-            enabled: owned_scopes.contains(&x)
+            enabled: owned_scopes.contains(x)
         })
-        .collect::<Vec<_>>();
-    Ok(org_scopes)
+        .collect::<Vec<_>>()
+}
+
+fn dal_org_user_to_proto(org_user: OrgUser) -> proto::OrgUser {
+    proto::OrgUser {
+        org_scopes: org_scopes_to_proto(&org_user.scopes),
+        user: Some(proto::User {
+            id: org_user.user.id,
+            name: org_user.user.name,
+            email: org_user.user.email
+        }),
+        is_org_admin: org_user.is_org_admin,
+    }
+}
+
+fn parse_scope(name: &str) -> WebResult<OrgScope> {
+    OrgScope::from_str(name).map_err(|_| Error::BadRequest(format!("Unknown scope '{name}'")))
+}
+
+/// Parse an optional ISO 639-1 language code, defaulting to English
+fn parse_language(code: Option<&str>) -> WebResult<Language> {
+    match code {
+        Some(code) => Language::from_code(code).ok_or(Error::BadRequest(format!("Unsupported language '{code}'"))),
+        None => Ok(Language::default()),
+    }
 }
\ No newline at end of file
@@ -1,18 +1,37 @@
 use actix_multiresponse::Payload;
-use dal::entities::{Entity, OrgScope};
+use dal::entities::{Attachment, AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
 use proto::RemoveOrgRequest;
 use crate::empty::Empty;
-use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::error::WebResult;
+use crate::routes::v1::attachment::remove_org_content;
+use crate::routes::v1::OrgAccess;
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn remove(data: WebData, session: Session, payload: Payload<RemoveOrgRequest>) -> WebResult<Empty> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &payload.org_id, OrgScope::RemoveOrg)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
-    }
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::RemoveOrg).await?;
 
-    access.org.remove()?;
+    let org = access.org;
+    let audit = AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Org,
+        entity_id: org.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("name", &org.name),
+    };
+
+    // The attachments are removed with the organization, their stored content is not
+    let content_hashes = Attachment::list_content_hashes(&data.driver, &org).await?;
+    let org_id = org.id.clone();
+    let mut tx = data.driver.start_transaction().await?;
+    org.remove_with_tx(&mut tx).await?;
+
+    // The audit log outlives the organization. Its webhooks do not, so the removal is not delivered to them
+    AuditLogEntry::create(&mut tx, audit).await?;
+    tx.commit().await?;
+
+    remove_org_content(&data.storage, &org_id, &content_hashes).await?;
     Ok(Empty)
 }
@@ -0,0 +1,71 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgRole, OrgScope, User};
+use proto::OrgRoleAssignRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn assign(data: WebData, session: Session, payload: Payload<OrgRoleAssignRequest>) -> WebResult<Empty> {
+    let (access, role, target_user) = get_role_and_user(&data, &session, &payload).await?;
+    access.check_grantable(&role.scopes)?;
+
+    let org = access.org;
+    if !org.has_user(&target_user).await? {
+        return Err(Error::NotFound("User is not part of the organization".to_string()));
+    }
+
+    if role.is_assigned(&target_user).await? {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    role.assign_with_tx(&mut tx, &target_user).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgUserRole,
+        entity_id: target_user.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("role_id", &role.id),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
+
+pub async fn unassign(data: WebData, session: Session, payload: Payload<OrgRoleAssignRequest>) -> WebResult<Empty> {
+    let (access, role, target_user) = get_role_and_user(&data, &session, &payload).await?;
+    let org = access.org;
+    if !role.is_assigned(&target_user).await? {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    role.unassign_with_tx(&mut tx, &target_user).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgUserRole,
+        entity_id: target_user.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("role_id", &role.id),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
+
+/// Retrieve the role and target user, checking that the session may manage the role's organization
+async fn get_role_and_user(data: &WebData, session: &Session, payload: &OrgRoleAssignRequest) -> WebResult<(OrgAccess, OrgRole, User)> {
+    let role = OrgRole::get(&data.driver, payload.role_id.clone()).await?.ok_or(Error::NotFound("Role not found".to_string()))?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &role.org_id, OrgScope::OrgUserManagment).await?;
+
+    let target_user = User::get(&data.driver, payload.user_id.clone()).await?.ok_or(Error::NotFound("User not found".to_string()))?;
+    Ok((access, role, target_user))
+}
\ No newline at end of file
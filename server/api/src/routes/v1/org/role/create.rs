@@ -0,0 +1,48 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgRole, OrgRoleBuilder, OrgScope};
+use proto::{OrgRoleCreateRequest, OrgRoleCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::parse_scope;
+use crate::routes::v1::org::role::{dal_role_to_proto, require_valid_name};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<OrgRoleCreateRequest>) -> WebResult<Payload<OrgRoleCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::OrgUserManagment).await?;
+
+    require_valid_name(&data.driver, &access.org, &payload.name).await?;
+
+    let mut scopes = Vec::new();
+    for name in &payload.scopes {
+        let scope = parse_scope(name)?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    access.check_grantable(&scopes)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let role = OrgRole::create_with_tx(&mut tx, &data.driver, OrgRoleBuilder {
+        org: &access.org,
+        name: payload.name.clone(),
+        scopes,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: role.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgRole,
+        entity_id: role.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &role.name)
+            .created("scopes", role.scopes.iter().map(|x| x.to_string()).collect::<Vec<_>>()),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(OrgRoleCreateResponse {
+        role: Some(dal_role_to_proto(role)),
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{Entity, OrgRole, OrgScope, User};
+use proto::OrgRoleListResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::role::dal_role_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    /// Only list the roles assigned to this user
+    user_id: Option<String>,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<OrgRoleListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetOrg).await?;
+
+    let roles = if let Some(user_id) = &query.user_id {
+        let target_user = User::get(&data.driver, user_id.clone()).await?.ok_or(Error::NotFound("User not found".to_string()))?;
+        OrgRole::list_for_user(&data.driver, &access.org, &target_user).await?
+    } else {
+        OrgRole::list_for_org(&data.driver, &access.org).await?
+    };
+
+    let roles = roles.into_iter()
+        .map(dal_role_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(OrgRoleListResponse {
+        roles
+    }))
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Org, OrgRole, OrgScope};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod assign;
+mod create;
+mod list;
+mod remove;
+mod update;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/role")
+            .route("/assign", web::post().to(assign::assign))
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/unassign", web::post().to(assign::unassign))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_role_to_proto(role: OrgRole) -> proto::OrgRole {
+    let org_scopes = OrgScope::variants()
+        .iter()
+        .map(|x| proto::OrgScope {
+            name: x.to_string(),
+            enabled: role.scopes.contains(x),
+        })
+        .collect::<Vec<_>>();
+
+    proto::OrgRole {
+        id: role.id,
+        org_id: role.org_id,
+        name: role.name,
+        org_scopes,
+    }
+}
+
+/// Require that the name is valid and not in use by another role of the organization
+async fn require_valid_name(driver: &Driver, org: &Org, name: &str) -> WebResult<()> {
+    if name.is_empty() {
+        return Err(Error::BadRequest("Role name may not be empty".to_string()));
+    }
+
+    if OrgRole::get_by_name(driver, org, name).await?.is_some() {
+        return Err(Error::Conflict(format!("Role '{name}' already exists")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgRole, OrgScope};
+use proto::OrgRoleRemoveRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<OrgRoleRemoveRequest>) -> WebResult<Empty> {
+    let role = OrgRole::get(&data.driver, payload.role_id.clone()).await?.ok_or(Error::NotFound("Role not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &role.org_id, OrgScope::OrgUserManagment).await?;
+
+    let audit = AuditLogEntryBuilder {
+        org_id: role.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgRole,
+        entity_id: role.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("name", &role.name),
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    role.remove_with_tx(&mut tx).await?;
+    AuditLogEntry::create(&mut tx, audit).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgRole, OrgScope};
+use proto::OrgRoleUpdateRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::parse_scope;
+use crate::routes::v1::org::role::require_valid_name;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<OrgRoleUpdateRequest>) -> WebResult<Empty> {
+    let mut role = OrgRole::get(&data.driver, payload.role_id.clone()).await?.ok_or(Error::NotFound("Role not found".to_string()))?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &role.org_id, OrgScope::OrgUserManagment).await?;
+
+    let mut diff = AuditDiff::new();
+
+    if let Some(name) = payload.name.as_ref().filter(|x| role.name.ne(*x)) {
+        require_valid_name(&data.driver, &access.org, name).await?;
+
+        diff = diff.field("name", &role.name, name);
+        role.name = name.clone();
+    }
+
+    for orgscope in &payload.org_scopes {
+        let scope = parse_scope(&orgscope.name)?;
+        let enabled = role.scopes.contains(&scope);
+        diff = diff.field(&orgscope.name, enabled, orgscope.enabled);
+
+        if orgscope.enabled && !enabled {
+            access.check_grantable([&scope])?;
+            role.scopes.push(scope);
+        } else if !orgscope.enabled && enabled {
+            role.scopes.retain(|x| x.ne(&scope));
+        }
+    }
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    role.update_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: role.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgRole,
+        entity_id: role.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
\ No newline at end of file
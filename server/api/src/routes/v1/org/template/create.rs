@@ -0,0 +1,40 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, InvoiceTemplate, InvoiceTemplateBuilder, OrgScope};
+use proto::{InvoiceTemplateCreateRequest, InvoiceTemplateCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::template::{require_valid_name, require_valid_template};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<InvoiceTemplateCreateRequest>) -> WebResult<Payload<InvoiceTemplateCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::ManageInvoiceTemplates).await?;
+
+    require_valid_name(&payload.name)?;
+    require_valid_template(&payload.content)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let template = InvoiceTemplate::create_with_tx(&mut tx, &data.driver, InvoiceTemplateBuilder {
+        org: &access.org,
+        name: payload.name.clone(),
+        content: payload.content.clone(),
+        is_default: payload.is_default,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: template.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::InvoiceTemplate,
+        entity_id: template.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &template.name)
+            .created("content", &template.content)
+            .created("is_default", template.is_default),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(InvoiceTemplateCreateResponse {
+        template_id: template.id,
+    }))
+}
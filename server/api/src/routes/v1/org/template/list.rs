@@ -0,0 +1,28 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{InvoiceTemplate, OrgScope};
+use proto::InvoiceTemplateListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::template::dal_template_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<InvoiceTemplateListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetOrg).await?;
+
+    let templates = InvoiceTemplate::list_for_org(&data.driver, &access.org).await?
+        .into_iter()
+        .map(dal_template_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(InvoiceTemplateListResponse {
+        templates,
+    }))
+}
@@ -0,0 +1,72 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, InvoiceTemplate};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+use crate::template::Template;
+
+mod create;
+mod list;
+mod preview;
+mod remove;
+mod update;
+
+/// The template invoices are rendered with when the organization has no default template
+const DEFAULT_TEMPLATE: &str = include_str!("../../../../../templates/invoice.html");
+
+/// The maximum size of a template in bytes
+const MAX_TEMPLATE_SIZE: usize = 128 * 1024;
+/// The maximum length of the name of a template
+const MAX_NAME_LENGTH: usize = 255;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/template")
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/preview", web::post().to(preview::preview))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_template_to_proto(template: InvoiceTemplate) -> proto::InvoiceTemplate {
+    proto::InvoiceTemplate {
+        id: template.id,
+        name: template.name,
+        content: template.content,
+        is_default: template.is_default,
+        created_at: template.created_at,
+        updated_at: template.updated_at,
+    }
+}
+
+async fn get_template(driver: &Driver, template_id: &str) -> WebResult<InvoiceTemplate> {
+    InvoiceTemplate::get(driver, template_id.to_string()).await?
+        .ok_or(Error::NotFound("Template not found".to_string()))
+}
+
+fn require_valid_name(name: &str) -> WebResult<()> {
+    if name.trim().is_empty() {
+        return Err(Error::BadRequest("Template name may not be empty".to_string()));
+    }
+
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(Error::BadRequest(format!("Template name may be at most {MAX_NAME_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+/// Require that the template is within the size limit and syntactically valid
+fn require_valid_template(content: &str) -> WebResult<Template> {
+    if content.len() > MAX_TEMPLATE_SIZE {
+        return Err(Error::BadRequest(format!("Template may be at most {MAX_TEMPLATE_SIZE} bytes")));
+    }
+
+    Ok(Template::parse(content)?)
+}
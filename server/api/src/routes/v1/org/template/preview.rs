@@ -0,0 +1,126 @@
+use actix_multiresponse::Payload;
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::{CONTENT_SECURITY_POLICY, X_CONTENT_TYPE_OPTIONS};
+use serde_json::{json, Value};
+use dal::entities::{InvoiceTemplate, Language, Org, OrgScope, UnitCode};
+use proto::InvoiceTemplatePreviewRequest;
+use crate::error::{Error, WebResult};
+use crate::locale;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::parse_language;
+use crate::routes::v1::org::template::{DEFAULT_TEMPLATE, get_template, require_valid_template};
+use crate::session::Session;
+use crate::WebData;
+
+/// Render a template with a sample invoice, returning the HTML.
+/// Renders, in order of precedence, the provided source, the provided saved template,
+/// the default template of the organization, or the built-in template.
+/// Templates are written by users, so the HTML is sandboxed and may not load anything but inline styles
+pub async fn preview(data: WebData, session: Session, payload: Payload<InvoiceTemplatePreviewRequest>) -> WebResult<HttpResponse> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::GetOrg).await?;
+    let language = parse_language(payload.language.as_deref())?;
+
+    let content = match (&payload.content, &payload.template_id) {
+        (Some(content), _) => content.clone(),
+        (None, Some(template_id)) => {
+            let template = get_template(&data.driver, template_id).await?;
+            if template.org_id.ne(&access.org.id) {
+                return Err(Error::NotFound("Template not found".to_string()));
+            }
+
+            template.content
+        },
+        (None, None) => InvoiceTemplate::get_default(&data.driver, &access.org).await?
+            .map(|x| x.content)
+            .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+    };
+
+    let template = require_valid_template(&content)?;
+    let context = sample_context(&access.org, language);
+    let html = web::block(move || template.render(&context)).await??;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((CONTENT_SECURITY_POLICY, "sandbox; default-src 'none'; style-src 'unsafe-inline'"))
+        .insert_header((X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .body(html))
+}
+
+/// A sample invoice, in the form invoices are passed to templates
+fn sample_context(org: &Org, language: Language) -> Value {
+    const CURRENCY: &str = "EUR";
+    // 2024-01-31 and 30 days later
+    const DATE: i64 = 1706659200;
+    const DUE_DATE: i64 = DATE + 30 * 24 * 60 * 60;
+
+    let lines = [
+        ("Consultancy", 12.5, UnitCode::HUR, 95.0, 21.0),
+        ("Hosting", 3.0, UnitCode::MON, 25.0, 21.0),
+        ("Reference book", 1.0, UnitCode::C62, 45.0, 9.0),
+    ];
+
+    let subtotal: f64 = lines.iter().map(|(_, quantity, _, price, _)| quantity * price).sum();
+    let tax: f64 = lines.iter().map(|(_, quantity, _, price, tax)| quantity * price * tax / 100.0).sum();
+    let total = locale::format_currency(language, subtotal + tax, CURRENCY);
+    let due_date = locale::format_date(language, DUE_DATE);
+    let number = "2024-0001";
+
+    json!({
+        "language": language.code(),
+        "labels": locale::translate_section(language, "invoice"),
+        "org": {
+            "name": &org.name,
+        },
+        "customer": {
+            "name": "Example Customer",
+            "address": "Example Street 1",
+            "postal_code": "1234 AB",
+            "city": "Amsterdam",
+            "country": "NL",
+            "vat_number": "NL000000000B01",
+        },
+        "invoice": {
+            "number": number,
+            "is_credit_note": false,
+            "date": locale::format_date(language, DATE),
+            "due_date": &due_date,
+            "currency": CURRENCY,
+            "lines": lines.iter().map(|(description, quantity, unit, price, tax)| json!({
+                "description": description,
+                "quantity": locale::format_number(language, *quantity, 2),
+                "unit": locale::format_unit(language, *unit),
+                "unit_price": locale::format_currency(language, *price, CURRENCY),
+                "tax_percentage": format!("{}%", locale::format_number(language, *tax, 0)),
+                "total": locale::format_currency(language, quantity * price, CURRENCY),
+            })).collect::<Vec<_>>(),
+            "subtotal": locale::format_currency(language, subtotal, CURRENCY),
+            "tax": locale::format_currency(language, tax, CURRENCY),
+            "total": &total,
+            "payment_terms": locale::translate(language, "invoice.payment_terms", &[
+                ("amount", &total),
+                ("due_date", &due_date),
+                ("number", number),
+            ]),
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::template::Template;
+
+    #[test]
+    fn default_template_renders() {
+        let context = json!({
+            "language": "de",
+            "labels": locale::translate_section(Language::De, "invoice"),
+            "org": { "name": "Foo" },
+            "invoice": { "number": "2024-0001", "lines": [{ "description": "Consultancy" }] },
+        });
+
+        let html = Template::parse(DEFAULT_TEMPLATE).unwrap().render(&context).unwrap();
+        assert!(html.contains("<title>Rechnung 2024-0001</title>"));
+        assert!(html.contains("<td>Consultancy</td>"));
+    }
+}
@@ -0,0 +1,36 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::InvoiceTemplateRemoveRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::template::get_template;
+use crate::session::Session;
+use crate::WebData;
+
+/// Remove a template. If it was the default, invoices are rendered with the built-in template again
+pub async fn remove(data: WebData, session: Session, payload: Payload<InvoiceTemplateRemoveRequest>) -> WebResult<Empty> {
+    let template = get_template(&data.driver, &payload.template_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &template.org_id, OrgScope::ManageInvoiceTemplates).await?;
+
+    let org_id = template.org_id.clone();
+    let template_id = template.id.clone();
+    let diff = AuditDiff::new()
+        .removed("name", &template.name)
+        .removed("content", &template.content)
+        .removed("is_default", template.is_default);
+    let mut tx = data.driver.start_transaction().await?;
+    template.remove_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id,
+        actor: session.actor(),
+        entity_type: AuditEntityType::InvoiceTemplate,
+        entity_id: template_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
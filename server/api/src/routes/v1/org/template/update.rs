@@ -0,0 +1,54 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::InvoiceTemplateUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::template::{get_template, require_valid_name, require_valid_template};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<InvoiceTemplateUpdateRequest>) -> WebResult<Empty> {
+    let mut template = get_template(&data.driver, &payload.template_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &template.org_id, OrgScope::ManageInvoiceTemplates).await?;
+
+    let original = template.clone();
+
+    if let Some(name) = &payload.name {
+        require_valid_name(name)?;
+        template.name = name.clone();
+    }
+
+    if let Some(content) = &payload.content {
+        require_valid_template(content)?;
+        template.content = content.clone();
+    }
+
+    if let Some(is_default) = payload.is_default {
+        template.is_default = is_default;
+    }
+
+    let diff = AuditDiff::new()
+        .field("name", &original.name, &template.name)
+        .field("content", &original.content, &template.content)
+        .field("is_default", original.is_default, template.is_default);
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    template.update_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: template.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::InvoiceTemplate,
+        entity_id: template.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
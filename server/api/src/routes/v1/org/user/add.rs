@@ -1,22 +1,35 @@
 use actix_multiresponse::Payload;
-use dal::entities::{OrgScope, User};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, User};
 use proto::OrgUserAddRequest;
 use crate::empty::Empty;
 use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::routes::v1::OrgAccess;
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn add(data: WebData, session: Session, payload: Payload<OrgUserAddRequest>) -> WebResult<Empty> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &payload.org_id, OrgScope::OrgUserManagment)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::OrgUserManagment).await?;
+    if payload.is_org_admin && !access.org_user.is_org_admin {
+        return Err(Error::Forbidden("Only organization admins can add organization admins".to_string()));
     }
 
-    let user = User::get_by_email(&data.driver, &payload.user_email)?.ok_or(Error::NotFound("User not found".to_string()))?;
+    let user = User::get_by_email(&data.driver, &payload.user_email).await?.ok_or(Error::NotFound("User not found".to_string()))?;
 
     let mut org = access.org;
-    org.add_user(&user, payload.is_org_admin)?;
+    let mut tx = data.driver.start_transaction().await?;
+    org.add_user_with_tx(&mut tx, &user, payload.is_org_admin, OrgScope::default_scopes()).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgUser,
+        entity_id: user.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("email", &user.email)
+            .created("is_org_admin", payload.is_org_admin),
+    }).await?;
+    tx.commit().await?;
 
     Ok(Empty)
 }
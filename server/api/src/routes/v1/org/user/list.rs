@@ -4,41 +4,37 @@ use crate::error::WebResult;
 use crate::session::Session;
 use crate::WebData;
 use serde::Deserialize;
-use dal::entities::OrgScope;
+use dal::entities::{OrgScope, OrgUserFilter, OrgUserSort};
+use dal::pagination::SortDirection;
 use proto::OrgUserListResponse;
-use crate::error::Error;
-use crate::routes::v1::org::get_user_org_scopes_proto;
-use crate::routes::v1::can_access;
+use crate::routes::v1::org::dal_org_user_to_proto;
+use crate::routes::v1::{OrgAccess, PageQuery};
 
 #[derive(Debug, Deserialize)]
 pub struct Query {
     org_id: String,
+    /// Only include users whose name or email address contains this value
+    search: Option<String>,
+    is_org_admin: Option<bool>,
 }
 
-pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<OrgUserListResponse>> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &query.org_id, OrgScope::GetOrg)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()))
-    }
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<OrgUserListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetOrg).await?;
 
-    let org = access.org;
-    let users = org.list_users()?;
-    let org_users = users.into_iter()
-        .map(|x| {
-            Ok(proto::OrgUser {
-                org_scopes: get_user_org_scopes_proto(&x.user, &org)?,
-                user: Some(proto::User {
-                    id: x.user.id,
-                    name: x.user.name,
-                    email: x.user.email
-                }),
-                is_org_admin: x.is_org_admin,
-            })
-        })
-        .collect::<WebResult<Vec<_>>>()?;
+    let filter = OrgUserFilter {
+        search: query.search.clone(),
+        is_org_admin: query.is_org_admin,
+    };
+
+    let page = access.org.list_users_page(&filter, &page.page_request::<OrgUserSort>(SortDirection::Asc)?).await?;
+    let org_users = page.items.into_iter()
+        .map(dal_org_user_to_proto)
+        .collect::<Vec<_>>();
 
     Ok(Payload(OrgUserListResponse {
-        org_users
+        org_users,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
     }))
-
 }
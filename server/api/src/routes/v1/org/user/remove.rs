@@ -1,21 +1,38 @@
 use actix_multiresponse::Payload;
-use dal::entities::{OrgScope, User, Entity};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, User};
 use proto::OrgUserRemoveRequest;
 use crate::empty::Empty;
 use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::routes::v1::OrgAccess;
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn remove(data: WebData, session: Session, payload: Payload<OrgUserRemoveRequest>) -> WebResult<Empty> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &payload.org_id, OrgScope::OrgUserManagment)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::OrgUserManagment).await?;
+
+    let target_user = User::get(&data.driver, payload.user_id.clone()).await?.ok_or(Error::NotFound("User not found".to_string()))?;
+    let target_org_user = access.org.get_user(&target_user).await?.ok_or(Error::NotFound("User is not part of the organization".to_string()))?;
+    if target_org_user.is_org_admin && !access.org_user.is_org_admin {
+        return Err(Error::Forbidden("Only organization admins can remove organization admins".to_string()));
     }
 
-    let target_user = User::get(&data.driver, payload.user_id.clone())?.ok_or(Error::NotFound("User not found".to_string()))?;
     let mut org = access.org;
-    org.remove_user(&target_user)?;
+    let mut tx = data.driver.start_transaction().await?;
+    org.remove_user_with_tx(&mut tx, &target_user).await.map_err(|e| match e {
+        dal::Error::LastOrgAdmin => Error::Conflict("The last organization admin cannot be removed".to_string()),
+        e => e.into(),
+    })?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgUser,
+        entity_id: target_user.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("email", &target_user.email),
+    }).await?;
+    tx.commit().await?;
 
     Ok(Empty)
 }
\ No newline at end of file
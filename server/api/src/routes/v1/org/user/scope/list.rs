@@ -5,7 +5,7 @@ use dal::entities::{OrgScope, User, Entity};
 use proto::OrgUserScopeListResponse;
 use crate::error::{Error, WebResult};
 use crate::routes::v1::org::get_user_org_scopes_proto;
-use crate::routes::v1::can_access;
+use crate::routes::v1::OrgAccess;
 use crate::session::Session;
 use crate::WebData;
 
@@ -16,19 +16,16 @@ pub struct Query {
 }
 
 pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<OrgUserScopeListResponse>> {
-    let user = session.user(&data.driver)?;
-    let access = can_access(&data.driver, &user, &query.org_id, OrgScope::GetOrg)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
-    }
+    let user = session.user(&data.driver).await?;
+    let access = OrgAccess::require(&data.driver, &user, &query.org_id, OrgScope::GetOrg).await?;
 
     let target_user = if let Some(user_id) = &query.user_id {
-        User::get(&data.driver, user_id.clone())?.ok_or(Error::NotFound("User not found".to_string()))?
+        User::get(&data.driver, user_id.clone()).await?.ok_or(Error::NotFound("User not found".to_string()))?
     } else {
         user
     };
 
     Ok(Payload(OrgUserScopeListResponse {
-       org_scopes: get_user_org_scopes_proto(&target_user, &access.org)?
+       org_scopes: get_user_org_scopes_proto(&target_user, &access.org).await?
     }))
 }
\ No newline at end of file
@@ -1,26 +1,62 @@
 use std::str::FromStr;
 use actix_multiresponse::Payload;
-use dal::entities::{OrgScope, User, Entity};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, User};
 use proto::OrgUserScopeSetRequest;
 use crate::empty::Empty;
 use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::routes::v1::OrgAccess;
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn set(data: WebData, session: Session, payload: Payload<OrgUserScopeSetRequest>) -> WebResult<Empty> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &payload.org_id, OrgScope::OrgUserManagment)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
-    }
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::OrgUserManagment).await?;
 
-    let target_user = User::get(&data.driver, payload.user_id.clone())?.ok_or(Error::NotFound("User does not exist".to_string()))?;
-    let mut org = access.org;
+    let target_user = User::get(&data.driver, payload.user_id.clone()).await?.ok_or(Error::NotFound("User does not exist".to_string()))?;
+    let mut org = access.org.clone();
+    if !org.has_user(&target_user).await? {
+        return Err(Error::NotFound("User is not part of the organization".to_string()));
+    }
 
+    // All changes are checked before any is made, so that a request is either applied entirely or not at all
+    let mut current_scopes = org.list_direct_scopes(&target_user).await?;
+    let mut changes = Vec::new();
     for orgscope in &payload.org_scopes {
         let scope = OrgScope::from_str(&orgscope.name).map_err(|_| Error::BadRequest(format!("Unknown scope '{}'", orgscope.name)))?;
-        org.set_scope(&target_user, &scope, orgscope.enabled)?;
+        let enabled = current_scopes.contains(&scope);
+        if enabled == orgscope.enabled {
+            continue;
+        }
+
+        if orgscope.enabled {
+            access.check_grantable([&scope])?;
+            current_scopes.push(scope.clone());
+        } else {
+            current_scopes.retain(|x| x.ne(&scope));
+        }
+
+        changes.push((scope, orgscope));
     }
 
+    if changes.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    let mut diff = AuditDiff::new();
+    for (scope, orgscope) in changes {
+        org.set_scope_with_tx(&mut tx, &target_user, &scope, orgscope.enabled).await?;
+        diff = diff.field(&orgscope.name, !orgscope.enabled, orgscope.enabled);
+    }
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: org.id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::OrgUserScope,
+        entity_id: target_user.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
     Ok(Empty)
 }
\ No newline at end of file
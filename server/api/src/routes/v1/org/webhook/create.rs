@@ -0,0 +1,39 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, Webhook, WebhookBuilder};
+use proto::{WebhookCreateRequest, WebhookCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::webhook::{require_valid_url, validate_event_types};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<WebhookCreateRequest>) -> WebResult<Payload<WebhookCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::ManageWebhooks).await?;
+
+    require_valid_url(&payload.url)?;
+    let event_types = validate_event_types(&payload.event_types)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let webhook = Webhook::create_with_tx(&mut tx, &data.driver, WebhookBuilder {
+        org: &access.org,
+        url: payload.url.clone(),
+        event_types,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: webhook.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Webhook,
+        entity_id: webhook.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("url", &webhook.url)
+            .created("event_types", &webhook.event_types),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(WebhookCreateResponse {
+        webhook_id: webhook.id,
+        secret: webhook.secret,
+    }))
+}
@@ -0,0 +1,44 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, WebhookDeliverySort};
+use dal::pagination::SortDirection;
+use proto::WebhookDeliveriesResponse;
+use crate::error::WebResult;
+use crate::routes::v1::{OrgAccess, PageQuery};
+use crate::routes::v1::org::webhook::get_webhook;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    webhook_id: String,
+}
+
+/// The delivery log of a webhook, newest deliveries first unless requested otherwise
+pub async fn deliveries(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<WebhookDeliveriesResponse>> {
+    let webhook = get_webhook(&data.driver, &query.webhook_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &webhook.org_id, OrgScope::ManageWebhooks).await?;
+
+    let page = webhook.list_deliveries(&page.page_request::<WebhookDeliverySort>(SortDirection::Desc)?).await?;
+    let deliveries = page.items.into_iter()
+        .map(|x| proto::WebhookDelivery {
+            id: x.id,
+            event_id: x.event_id,
+            event_type: x.event_type,
+            attempts: x.attempts,
+            next_attempt_at: x.next_attempt_at,
+            delivered_at: x.delivered_at,
+            last_status: x.last_status,
+            last_error: x.last_error,
+            created_at: x.created_at,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Payload(WebhookDeliveriesResponse {
+        deliveries,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
+    }))
+}
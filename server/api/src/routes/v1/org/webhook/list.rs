@@ -0,0 +1,29 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, Webhook, webhook_event_types};
+use proto::WebhookListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::webhook::dal_webhook_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<WebhookListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::ManageWebhooks).await?;
+
+    let webhooks = Webhook::list_for_org(&data.driver, &access.org).await?
+        .into_iter()
+        .map(dal_webhook_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(WebhookListResponse {
+        webhooks,
+        available_event_types: webhook_event_types(),
+    }))
+}
@@ -0,0 +1,73 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, Webhook, webhook_event_types};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod create;
+mod deliveries;
+mod list;
+mod remove;
+mod update;
+
+/// The maximum length of a webhook URL
+const MAX_URL_LENGTH: usize = 2048;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/webhook")
+            .route("/create", web::post().to(create::create))
+            .route("/deliveries", web::get().to(deliveries::deliveries))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_webhook_to_proto(webhook: Webhook) -> proto::Webhook {
+    proto::Webhook {
+        id: webhook.id,
+        url: webhook.url,
+        event_types: webhook.event_types,
+        created_at: webhook.created_at,
+    }
+}
+
+async fn get_webhook(driver: &Driver, webhook_id: &str) -> WebResult<Webhook> {
+    Webhook::get(driver, webhook_id.to_string()).await?
+        .ok_or(Error::NotFound("Webhook not found".to_string()))
+}
+
+/// Require that the URL is an absolute HTTP(S) URL
+fn require_valid_url(url: &str) -> WebResult<()> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(Error::BadRequest("Webhook URL must start with http:// or https://".to_string()));
+    }
+
+    if url.len() > MAX_URL_LENGTH {
+        return Err(Error::BadRequest(format!("Webhook URL may be at most {MAX_URL_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+/// Validate the requested event types, returning them sorted and without duplicates
+fn validate_event_types(event_types: &[String]) -> WebResult<Vec<String>> {
+    if event_types.is_empty() {
+        return Err(Error::BadRequest("A webhook must subscribe to at least one event type".to_string()));
+    }
+
+    let available = webhook_event_types();
+    if let Some(unknown) = event_types.iter().find(|x| !available.contains(x)) {
+        return Err(Error::BadRequest(format!("Unknown event type '{unknown}'")));
+    }
+
+    let mut event_types = event_types.to_vec();
+    event_types.sort();
+    event_types.dedup();
+    Ok(event_types)
+}
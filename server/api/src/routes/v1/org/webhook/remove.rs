@@ -0,0 +1,35 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::WebhookRemoveRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::webhook::get_webhook;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<WebhookRemoveRequest>) -> WebResult<Empty> {
+    let webhook = get_webhook(&data.driver, &payload.webhook_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &webhook.org_id, OrgScope::ManageWebhooks).await?;
+
+    // Removed first, so the webhook is not notified of its own removal
+    let org_id = webhook.org_id.clone();
+    let webhook_id = webhook.id.clone();
+    let diff = AuditDiff::new()
+        .removed("url", &webhook.url)
+        .removed("event_types", &webhook.event_types);
+    let mut tx = data.driver.start_transaction().await?;
+    webhook.remove_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id,
+        actor: session.actor(),
+        entity_type: AuditEntityType::Webhook,
+        entity_id: webhook_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
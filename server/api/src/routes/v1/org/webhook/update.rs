@@ -0,0 +1,48 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::WebhookUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::org::webhook::{get_webhook, require_valid_url, validate_event_types};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<WebhookUpdateRequest>) -> WebResult<Empty> {
+    let mut webhook = get_webhook(&data.driver, &payload.webhook_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &webhook.org_id, OrgScope::ManageWebhooks).await?;
+
+    let original = webhook.clone();
+
+    if let Some(url) = &payload.url {
+        require_valid_url(url)?;
+        webhook.url = url.clone();
+    }
+
+    if !payload.event_types.is_empty() {
+        webhook.event_types = validate_event_types(&payload.event_types)?;
+    }
+
+    let diff = AuditDiff::new()
+        .field("url", &original.url, &webhook.url)
+        .field("event_types", &original.event_types, &webhook.event_types);
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    webhook.update_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: webhook.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Webhook,
+        entity_id: webhook.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -0,0 +1,19 @@
+use actix_web::{HttpRequest, web};
+use tracing::info;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::WebData;
+
+/// Called by the payment provider when the status of a payment changes. Not authenticated with a session,
+/// the provider signs the request body instead. The current status is retrieved from the provider
+pub async fn callback(data: WebData, req: HttpRequest, body: web::Bytes) -> WebResult<Empty> {
+    let provider = data.payment_provider.as_ref()
+        .ok_or(Error::NotFound("Online payments are not enabled".to_string()))?;
+
+    let signature = req.headers().get(provider.signature_header()).and_then(|x| x.to_str().ok());
+    let update = provider.handle_callback(signature, &body).await?;
+
+    // Payments are not linked to invoices yet, the update is only recorded in the log
+    info!("Payment {} for {} is {:?}: {} {}", update.payment_id, update.reference, update.status, update.amount, update.currency);
+    Ok(Empty)
+}
@@ -0,0 +1,15 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use crate::routable::Routable;
+
+mod callback;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/payment")
+            .route("/callback", web::post().to(callback::callback))
+        );
+    }
+}
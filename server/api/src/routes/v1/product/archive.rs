@@ -0,0 +1,60 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, Product};
+use proto::{ProductArchiveRequest, ProductUnarchiveRequest};
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+/// Archive a product. Products are never removed, so that existing references to them stay valid
+pub async fn archive(data: WebData, session: Session, payload: Payload<ProductArchiveRequest>) -> WebResult<Empty> {
+    let mut product = Product::get(&data.driver, payload.product_id.clone()).await?.ok_or(Error::NotFound("Product not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &product.org_id, OrgScope::RemoveProduct).await?;
+
+    if product.archived_at.is_some() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    product.archive_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: product.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Product,
+        entity_id: product.id.clone(),
+        action: AuditAction::Update,
+        diff: AuditDiff::new()
+            .field("archived_at", None, product.archived_at),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
+
+pub async fn unarchive(data: WebData, session: Session, payload: Payload<ProductUnarchiveRequest>) -> WebResult<Empty> {
+    let mut product = Product::get(&data.driver, payload.product_id.clone()).await?.ok_or(Error::NotFound("Product not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &product.org_id, OrgScope::RemoveProduct).await?;
+
+    let archived_at = match product.archived_at {
+        Some(x) => x,
+        None => return Ok(Empty),
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    product.unarchive_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: product.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Product,
+        entity_id: product.id.clone(),
+        action: AuditAction::Update,
+        diff: AuditDiff::new()
+            .field("archived_at", Some(archived_at), None),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -0,0 +1,34 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, ProductCategory, ProductCategoryBuilder};
+use proto::{ProductCategoryCreateRequest, ProductCategoryCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::category::require_valid_name;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<ProductCategoryCreateRequest>) -> WebResult<Payload<ProductCategoryCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::CreateProduct).await?;
+    require_valid_name(&data.driver, &access.org, &payload.name).await?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let category = ProductCategory::create_with_tx(&mut tx, &data.driver, ProductCategoryBuilder {
+        org: &access.org,
+        name: payload.name.clone(),
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: category.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::ProductCategory,
+        entity_id: category.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &category.name),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(ProductCategoryCreateResponse {
+        category_id: category.id,
+    }))
+}
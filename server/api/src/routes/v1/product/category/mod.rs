@@ -0,0 +1,45 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Org, ProductCategory};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod create;
+mod list;
+mod remove;
+mod update;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/category")
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_category_to_proto(category: ProductCategory) -> proto::ProductCategory {
+    proto::ProductCategory {
+        id: category.id,
+        org_id: category.org_id,
+        name: category.name,
+    }
+}
+
+/// Require that the name is valid and not in use by another category of the organization
+async fn require_valid_name(driver: &Driver, org: &Org, name: &str) -> WebResult<()> {
+    if name.is_empty() {
+        return Err(Error::BadRequest("Category name may not be empty".to_string()));
+    }
+
+    if ProductCategory::get_by_name(driver, org, name).await?.is_some() {
+        return Err(Error::Conflict(format!("Category '{name}' already exists")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, ProductCategory};
+use proto::ProductCategoryRemoveRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+/// Remove a category. Products in the category are kept, without a category
+pub async fn remove(data: WebData, session: Session, payload: Payload<ProductCategoryRemoveRequest>) -> WebResult<Empty> {
+    let category = ProductCategory::get(&data.driver, payload.category_id.clone()).await?.ok_or(Error::NotFound("Product category not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &category.org_id, OrgScope::RemoveProduct).await?;
+
+    let audit = AuditLogEntryBuilder {
+        org_id: category.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::ProductCategory,
+        entity_id: category.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("name", &category.name),
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    category.remove_with_tx(&mut tx).await?;
+    AuditLogEntry::create(&mut tx, audit).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -0,0 +1,37 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, ProductCategory};
+use proto::ProductCategoryUpdateRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::category::require_valid_name;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<ProductCategoryUpdateRequest>) -> WebResult<Empty> {
+    let mut category = ProductCategory::get(&data.driver, payload.category_id.clone()).await?.ok_or(Error::NotFound("Product category not found".to_string()))?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &category.org_id, OrgScope::UpdateProduct).await?;
+
+    if category.name.eq(&payload.name) {
+        return Ok(Empty);
+    }
+
+    require_valid_name(&data.driver, &access.org, &payload.name).await?;
+
+    let original_name = std::mem::replace(&mut category.name, payload.name.clone());
+    let mut tx = data.driver.start_transaction().await?;
+    category.update_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: category.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::ProductCategory,
+        entity_id: category.id.clone(),
+        action: AuditAction::Update,
+        diff: AuditDiff::new()
+            .field("name", &original_name, &category.name),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
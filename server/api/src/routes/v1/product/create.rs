@@ -1,25 +1,53 @@
 use actix_multiresponse::Payload;
-use dal::entities::{OrgScope, Product, ProductBuilder, Entity};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, Product, ProductBuilder};
 use proto::{ProductCreateRequest, ProductCreateResponse};
-use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::{get_category, parse_unit, require_unique_code};
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn create(data: WebData, session: Session, payload: Payload<ProductCreateRequest>) -> WebResult<Payload<ProductCreateResponse>> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &payload.org_id, OrgScope::CreateProduct)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::CreateProduct).await?;
+
+    if let Some(product_code) = &payload.product_code {
+        require_unique_code(&data.driver, &access.org, product_code, None).await?;
     }
 
-    let product = Product::create(&data.driver, ProductBuilder {
+    let unit = payload.unit.as_deref().map(parse_unit).transpose()?.unwrap_or_default();
+    let category = match &payload.category_id {
+        Some(x) => Some(get_category(&data.driver, &access.org, x).await?),
+        None => None,
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    let product = Product::create_with_tx(&mut tx, &data.driver, ProductBuilder {
         name: payload.name.clone(),
         description: payload.description.clone(),
         org: &access.org,
         product_code: payload.product_code.clone(),
         price_per_unit: payload.price_per_unit,
         tax_percentage: payload.tax_percentage,
-    })?;
+        unit,
+        category: category.as_ref(),
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: product.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Product,
+        entity_id: product.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &product.name)
+            .created("description", &product.description)
+            .created("product_code", &product.product_code)
+            .created("price_per_unit", product.price_per_unit)
+            .created("tax_percentage", product.tax_percentage)
+            .created("unit", product.unit.to_string())
+            .created("category_id", &product.category_id),
+    }).await?;
+    tx.commit().await?;
 
     Ok(Payload(ProductCreateResponse {
         product_id: product.id,
@@ -0,0 +1,72 @@
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use serde::Deserialize;
+use dal::entities::{OrgScope, Product, ProductCategory, ProductFilter, ProductSort};
+use dal::pagination::{MAX_PAGE_SIZE, PageRequest, SortDirection};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::SHEET_COLUMNS;
+use crate::session::Session;
+use crate::spreadsheet::{Cell, Sheet, SheetFormat};
+use crate::{spreadsheet, WebData};
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    /// `csv` or `xlsx`
+    format: String,
+    include_archived: Option<bool>,
+}
+
+/// Export all products of the organization as a spreadsheet, in the format accepted by the import
+pub async fn export(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<HttpResponse> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetProduct).await?;
+    let format = SheetFormat::from_request(Some(&query.format), None)?;
+
+    let categories = ProductCategory::list_for_org(&data.driver, &access.org).await?;
+    let filter = ProductFilter {
+        include_archived: query.include_archived.unwrap_or(false),
+        ..ProductFilter::default()
+    };
+
+    let mut sheet = Sheet {
+        headers: SHEET_COLUMNS.iter().map(|x| x.to_string()).collect(),
+        rows: Vec::new(),
+    };
+
+    let mut cursor = None;
+    loop {
+        let page_request = PageRequest::new(ProductSort::Name, SortDirection::Asc, MAX_PAGE_SIZE, cursor.as_deref())?;
+        let page = Product::list_for_org(&data.driver, &access.org, &filter, &page_request).await?;
+
+        sheet.rows.extend(page.items.into_iter().map(|product| {
+            let category = product.category_id.as_ref()
+                .and_then(|id| categories.iter().find(|x| x.id.eq(id)))
+                .map(|x| x.name.clone());
+
+            vec![
+                Cell::from(product.product_code),
+                Cell::from(product.name),
+                Cell::from(product.description),
+                Cell::from(product.price_per_unit),
+                Cell::from(product.tax_percentage),
+                Cell::from(product.unit.to_string()),
+                Cell::from(category),
+            ]
+        }));
+
+        match page.next_cursor {
+            Some(x) => cursor = Some(x),
+            None => break,
+        }
+    }
+
+    let body = spreadsheet::write(format, &sheet)?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("products.{}", format.extension()))],
+        })
+        .body(body))
+}
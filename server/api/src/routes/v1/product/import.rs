@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use actix_multiresponse::Payload;
+use actix_web::{HttpRequest, web};
+use actix_web::http::header::CONTENT_TYPE;
+use serde::Deserialize;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, ImportedProduct, OrgScope, Product, ProductCategory, ProductImport, UnitCode};
+use proto::{ImportRowError, ProductImportResponse};
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::SHEET_COLUMNS;
+use crate::session::Session;
+use crate::spreadsheet::{Sheet, SheetFormat};
+use crate::{spreadsheet, WebData};
+
+/// The maximum size of an uploaded file in bytes
+pub const MAX_IMPORT_SIZE: usize = 10 * 1024 * 1024;
+/// The maximum number of products in a single import
+const MAX_IMPORT_ROWS: usize = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    /// `csv` or `xlsx`. If not set, the format is taken from the `Content-Type` header
+    format: Option<String>,
+    /// Validate the file and report the outcome without importing anything
+    dry_run: Option<bool>,
+}
+
+/// Import products from a CSV or XLSX file in the request body.
+/// Products with a product code already in use are updated, all others are created.
+/// Every row is validated first. If any row has errors, they are all returned and nothing is imported
+pub async fn import(data: WebData, session: Session, req: HttpRequest, query: web::Query<Query>, body: web::Bytes) -> WebResult<Payload<ProductImportResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::CreateProduct).await?;
+    access.check(&OrgScope::UpdateProduct)?;
+
+    let content_type = req.headers().get(CONTENT_TYPE).and_then(|x| x.to_str().ok());
+    let format = SheetFormat::from_request(query.format.as_deref(), content_type)?;
+    let sheet = spreadsheet::read(format, &body).map_err(|e| Error::BadRequest(format!("Failed to read file: {e}")))?;
+
+    if sheet.rows.len() > MAX_IMPORT_ROWS {
+        return Err(Error::BadRequest(format!("An import may contain at most {MAX_IMPORT_ROWS} products")));
+    }
+
+    let categories = ProductCategory::list_for_org(&data.driver, &access.org).await?.into_iter()
+        .map(|x| (x.name, x.id))
+        .collect::<HashMap<_, _>>();
+
+    let (products, errors) = validate(&sheet, &categories)?;
+    let dry_run = query.dry_run.unwrap_or(false);
+    if !errors.is_empty() {
+        return Ok(Payload(ProductImportResponse {
+            applied: false,
+            created: 0,
+            updated: 0,
+            errors,
+        }));
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    let imported = Product::import_with_tx(&mut tx, &data.driver, &access.org, products).await?;
+    let created = imported.iter().filter(|x| matches!(x, ImportedProduct::Created(_))).count();
+    let updated = imported.len() - created;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        for imported in imported {
+            let (product, action, diff) = match imported {
+                ImportedProduct::Created(product) => {
+                    let diff = AuditDiff::new()
+                        .created("name", &product.name)
+                        .created("description", &product.description)
+                        .created("product_code", &product.product_code)
+                        .created("price_per_unit", product.price_per_unit)
+                        .created("tax_percentage", product.tax_percentage)
+                        .created("unit", product.unit.to_string())
+                        .created("category_id", &product.category_id);
+                    (product, AuditAction::Create, diff)
+                },
+                ImportedProduct::Updated { original, product } => {
+                    let diff = AuditDiff::new()
+                        .field("name", &original.name, &product.name)
+                        .field("description", &original.description, &product.description)
+                        .field("price_per_unit", original.price_per_unit, product.price_per_unit)
+                        .field("tax_percentage", original.tax_percentage, product.tax_percentage)
+                        .field("unit", original.unit.to_string(), product.unit.to_string())
+                        .field("category_id", &original.category_id, &product.category_id);
+                    (product, AuditAction::Update, diff)
+                }
+            };
+
+            if diff.is_empty() {
+                continue;
+            }
+
+            AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+                org_id: product.org_id.clone(),
+                actor: session.actor(),
+                entity_type: AuditEntityType::Product,
+                entity_id: product.id.clone(),
+                action,
+                diff,
+            }).await?;
+        }
+
+        tx.commit().await?;
+    }
+
+    Ok(Payload(ProductImportResponse {
+        applied: !dry_run,
+        created: created as u32,
+        updated: updated as u32,
+        errors: Vec::new(),
+    }))
+}
+
+/// Validate every row of the sheet. `categories` maps category names to their ID.
+/// Returns the products to import and the errors found, of which there are none if all rows are valid
+fn validate(sheet: &Sheet<String>, categories: &HashMap<String, String>) -> WebResult<(Vec<ProductImport>, Vec<ImportRowError>)> {
+    let mut columns = HashMap::new();
+    for (idx, header) in sheet.headers.iter().enumerate() {
+        let header = header.to_lowercase();
+        if !SHEET_COLUMNS.contains(&header.as_str()) {
+            return Err(Error::BadRequest(format!("Unknown column '{header}', expected any of {}", SHEET_COLUMNS.join(", "))));
+        }
+
+        if columns.insert(header.clone(), idx).is_some() {
+            return Err(Error::BadRequest(format!("Column '{header}' appears more than once")));
+        }
+    }
+
+    for required in ["name", "price_per_unit"] {
+        if !columns.contains_key(required) {
+            return Err(Error::BadRequest(format!("Missing required column '{required}'")));
+        }
+    }
+
+    let mut products = Vec::with_capacity(sheet.rows.len());
+    let mut errors = Vec::new();
+    // Product codes seen so far, with the row they appeared in
+    let mut product_codes: HashMap<&str, u32> = HashMap::new();
+
+    for (idx, row) in sheet.rows.iter().enumerate() {
+        // The header is row 1
+        let row_number = idx as u32 + 2;
+        let get = |column: &str| columns.get(column)
+            .and_then(|idx| row.get(*idx))
+            .map(String::as_str)
+            .filter(|x| !x.is_empty());
+        let mut error = |column: &str, message: String| errors.push(ImportRowError {
+            row: row_number,
+            column: Some(column.to_string()),
+            message,
+        });
+
+        let name = get("name");
+        if name.is_none() {
+            error("name", "A name is required".to_string());
+        }
+
+        let price_per_unit = match get("price_per_unit").map(f32::from_str) {
+            Some(Ok(x)) if x.is_finite() && x >= 0.0 => Some(x),
+            Some(_) => {
+                error("price_per_unit", "Must be a number of at least 0".to_string());
+                None
+            },
+            None => {
+                error("price_per_unit", "A price is required".to_string());
+                None
+            }
+        };
+
+        let tax_percentage = match get("tax_percentage").map(f32::from_str) {
+            Some(Ok(x)) if x.is_finite() => Some(x),
+            Some(_) => {
+                error("tax_percentage", "Must be a number".to_string());
+                None
+            },
+            None => None,
+        };
+
+        let unit = match get("unit") {
+            Some(x) => UnitCode::from_str(x).unwrap_or_else(|_| {
+                error("unit", format!("Unknown or unsupported unit code '{x}'"));
+                UnitCode::default()
+            }),
+            None => UnitCode::default(),
+        };
+
+        let category_id = get("category").and_then(|x| {
+            let id = categories.get(x).cloned();
+            if id.is_none() {
+                error("category", format!("Unknown category '{x}'"));
+            }
+            id
+        });
+
+        let product_code = get("product_code");
+        if let Some(product_code) = product_code {
+            if let Some(previous) = product_codes.insert(product_code, row_number) {
+                error("product_code", format!("Product code '{product_code}' also appears in row {previous}"));
+            }
+        }
+
+        if let (Some(name), Some(price_per_unit)) = (name, price_per_unit) {
+            products.push(ProductImport {
+                product_code: product_code.map(str::to_string),
+                name: name.to_string(),
+                description: get("description").map(str::to_string),
+                price_per_unit,
+                tax_percentage,
+                unit,
+                category_id,
+            });
+        }
+    }
+
+    Ok((products, errors))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sheet(rows: &[&[&str]]) -> Sheet<String> {
+        Sheet {
+            headers: vec!["Product_Code".to_string(), "name".to_string(), "price_per_unit".to_string(), "category".to_string()],
+            rows: rows.iter().map(|row| row.iter().map(|x| x.to_string()).collect()).collect(),
+        }
+    }
+
+    #[test]
+    fn reports_every_row_error() {
+        let categories = HashMap::from([("Tools".to_string(), "c1".to_string())]);
+        let (products, errors) = validate(&sheet(&[
+            &["A1", "Hammer", "12.5", "Tools"],
+            &["A1", "", "-1", "Toys"],
+        ]), &categories).unwrap();
+
+        assert_eq!(products.len(), 1);
+        assert_eq!(products[0].category_id.as_deref(), Some("c1"));
+
+        let errors = errors.iter().map(|x| (x.row, x.column.clone().unwrap())).collect::<Vec<_>>();
+        assert_eq!(errors, vec![
+            (3, "name".to_string()),
+            (3, "price_per_unit".to_string()),
+            (3, "category".to_string()),
+            (3, "product_code".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let mut sheet = sheet(&[]);
+        sheet.headers.push("colour".to_string());
+        assert!(matches!(validate(&sheet, &HashMap::new()), Err(Error::BadRequest(_))));
+    }
+}
@@ -1,10 +1,11 @@
 use actix_multiresponse::Payload;
 use actix_web::web;
 use serde::Deserialize;
-use dal::entities::{OrgScope, Product};
+use dal::entities::{OrgScope, Product, ProductFilter, ProductSort};
+use dal::pagination::SortDirection;
 use proto::ProductListResponse;
-use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::error::WebResult;
+use crate::routes::v1::{OrgAccess, PageQuery};
 use crate::routes::v1::product::dal_product_to_proto;
 use crate::session::Session;
 use crate::WebData;
@@ -12,20 +13,33 @@ use crate::WebData;
 #[derive(Debug, Deserialize)]
 pub struct Query {
     org_id: String,
+    search: Option<String>,
+    category_id: Option<String>,
+    min_price: Option<f32>,
+    max_price: Option<f32>,
+    include_archived: Option<bool>,
 }
 
-pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<ProductListResponse>> {
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &query.org_id, OrgScope::GetProduct)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()))?;
-    }
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<ProductListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetProduct).await?;
 
-    let products = Product::list_for_org(&data.driver, &access.org)?
-        .into_iter()
+    let filter = ProductFilter {
+        search: query.search.clone(),
+        category_id: query.category_id.clone(),
+        min_price: query.min_price,
+        max_price: query.max_price,
+        include_archived: query.include_archived.unwrap_or(false),
+    };
+
+    let page = Product::list_for_org(&data.driver, &access.org, &filter, &page.page_request::<ProductSort>(SortDirection::Asc)?).await?;
+    let products = page.items.into_iter()
         .map(|x| dal_product_to_proto(&access.org, x))
         .collect::<Vec<_>>();
 
     Ok(Payload(ProductListResponse {
-        products
+        products,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
     }))
 }
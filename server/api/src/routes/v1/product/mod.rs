@@ -1,12 +1,20 @@
+use std::str::FromStr;
 use actix_web::web;
 use actix_web::web::ServiceConfig;
-use dal::entities::{Org, Product};
+use dal::Driver;
+use dal::entities::{Entity, Org, Product, ProductCategory, UnitCode};
+use crate::error::{Error, WebResult};
 use crate::routable::Routable;
 
+mod archive;
+mod category;
 mod create;
+mod export;
 mod get;
+mod import;
 mod list;
-mod remove;
+mod price;
+mod pricelist;
 mod update;
 
 pub struct Router;
@@ -14,16 +22,29 @@ pub struct Router;
 impl Routable for Router {
     fn configure(config: &mut ServiceConfig) {
         config.service(web::scope("/product")
+            .configure(category::Router::configure)
+            .configure(pricelist::Router::configure)
             .route("", web::get().to(get::get))
+            .route("/archive", web::post().to(archive::archive))
             .route("/create", web::post().to(create::create))
+            .route("/export", web::get().to(export::export))
+            .service(web::resource("/import")
+                .app_data(web::PayloadConfig::new(import::MAX_IMPORT_SIZE))
+                .route(web::post().to(import::import))
+            )
             .route("/list", web::get().to(list::list))
-            .route("/remove", web::post().to(remove::remove))
+            .route("/price", web::get().to(price::price))
+            .route("/unarchive", web::post().to(archive::unarchive))
             .route("/update", web::post().to(update::update))
         );
     }
 }
 
-fn dal_product_to_proto(org: &Org<'_>, product: Product<'_>) -> proto::Product {
+/// The columns of product spreadsheets, both for importing and exporting.
+/// Categories are referred to by name
+const SHEET_COLUMNS: [&str; 7] = ["product_code", "name", "description", "price_per_unit", "tax_percentage", "unit", "category"];
+
+fn dal_product_to_proto(org: &Org, product: Product) -> proto::Product {
     proto::Product {
         id: product.id,
         name: product.name,
@@ -34,6 +55,29 @@ fn dal_product_to_proto(org: &Org<'_>, product: Product<'_>) -> proto::Product {
         }),
         product_code: product.product_code,
         tax_percentage: product.tax_percentage,
-        price_per_unit: product.price_per_unit
+        price_per_unit: product.price_per_unit,
+        unit: product.unit.to_string(),
+        category_id: product.category_id,
+        archived_at: product.archived_at,
+    }
+}
+
+fn parse_unit(code: &str) -> WebResult<UnitCode> {
+    UnitCode::from_str(code).map_err(|_| Error::BadRequest(format!("Unknown or unsupported unit code '{code}'")))
+}
+
+/// Get a product category of the organization
+async fn get_category(driver: &Driver, org: &Org, category_id: &str) -> WebResult<ProductCategory> {
+    match ProductCategory::get(driver, category_id.to_string()).await? {
+        Some(x) if x.org_id.eq(&org.id) => Ok(x),
+        _ => Err(Error::NotFound("Product category not found".to_string())),
     }
-}
\ No newline at end of file
+}
+
+/// Require that no other product in the organization uses the product code
+async fn require_unique_code(driver: &Driver, org: &Org, product_code: &str, product_id: Option<&str>) -> WebResult<()> {
+    match Product::get_by_code(driver, org, product_code).await? {
+        Some(x) if product_id.ne(&Some(x.id.as_str())) => Err(Error::Conflict(format!("Product code '{product_code}' is already in use"))),
+        _ => Ok(()),
+    }
+}
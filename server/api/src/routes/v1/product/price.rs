@@ -0,0 +1,47 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{Entity, OrgScope, Product};
+use proto::ProductPriceResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::get_price_list;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    product_id: String,
+    quantity: Option<f32>,
+    /// The price list which applies, e.g. the price list of a customer
+    price_list_id: Option<String>,
+    /// UNIX timestamp at which the price applies. Defaults to now
+    at: Option<i64>,
+}
+
+/// Determine the price per unit of a product, taking price lists and volume tiers into account
+pub async fn price(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<ProductPriceResponse>> {
+    let product = Product::get(&data.driver, query.product_id.clone()).await?.ok_or(Error::NotFound("Product not found".to_string()))?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &product.org_id, OrgScope::GetProduct).await?;
+
+    let price_list = match &query.price_list_id {
+        Some(x) => match get_price_list(&data.driver, x).await? {
+            price_list if price_list.org_id.eq(&product.org_id) => Some(price_list),
+            _ => return Err(Error::NotFound("Price list not found".to_string())),
+        },
+        None => None,
+    };
+
+    let quantity = query.quantity.unwrap_or(1.0);
+    let at = query.at.unwrap_or_else(|| time::OffsetDateTime::now_utc().unix_timestamp());
+
+    let price_per_unit = product.unit_price(price_list.as_ref(), quantity, at);
+    let price_list_id = price_list
+        .filter(|x| x.price_for(&product.id, quantity, at).is_some())
+        .map(|x| x.id);
+
+    Ok(Payload(ProductPriceResponse {
+        price_per_unit,
+        price_list_id,
+    }))
+}
@@ -0,0 +1,39 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, PriceList, PriceListBuilder};
+use proto::{PriceListCreateRequest, PriceListCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::{require_valid_name, require_valid_period};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<PriceListCreateRequest>) -> WebResult<Payload<PriceListCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::CreateProduct).await?;
+    require_valid_name(&data.driver, &access.org, &payload.name).await?;
+    require_valid_period(payload.valid_from, payload.valid_until)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let price_list = PriceList::create_with_tx(&mut tx, &data.driver, PriceListBuilder {
+        org: &access.org,
+        name: payload.name.clone(),
+        valid_from: payload.valid_from,
+        valid_until: payload.valid_until,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: price_list.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::PriceList,
+        entity_id: price_list.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &price_list.name)
+            .created("valid_from", price_list.valid_from)
+            .created("valid_until", price_list.valid_until),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(PriceListCreateResponse {
+        price_list_id: price_list.id,
+    }))
+}
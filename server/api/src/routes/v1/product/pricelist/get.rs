@@ -0,0 +1,24 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::OrgScope;
+use proto::PriceListGetResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::{dal_price_list_to_proto, get_price_list};
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    price_list_id: String,
+}
+
+pub async fn get(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<PriceListGetResponse>> {
+    let price_list = get_price_list(&data.driver, &query.price_list_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &price_list.org_id, OrgScope::GetProduct).await?;
+
+    Ok(Payload(PriceListGetResponse {
+        price_list: Some(dal_price_list_to_proto(price_list)),
+    }))
+}
@@ -0,0 +1,69 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, Org, PriceList};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+
+mod create;
+mod get;
+mod list;
+mod prices;
+mod remove;
+mod update;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/pricelist")
+            .route("", web::get().to(get::get))
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/prices", web::post().to(prices::prices))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_price_list_to_proto(price_list: PriceList) -> proto::PriceList {
+    proto::PriceList {
+        id: price_list.id,
+        org_id: price_list.org_id,
+        name: price_list.name,
+        valid_from: price_list.valid_from,
+        valid_until: price_list.valid_until,
+        prices: price_list.prices.into_iter()
+            .map(|x| proto::TierPrice {
+                product_id: x.product_id,
+                min_quantity: x.min_quantity,
+                price_per_unit: x.price_per_unit,
+            })
+            .collect(),
+    }
+}
+
+pub(super) async fn get_price_list(driver: &Driver, price_list_id: &str) -> WebResult<PriceList> {
+    PriceList::get(driver, price_list_id.to_string()).await?.ok_or(Error::NotFound("Price list not found".to_string()))
+}
+
+/// Require that the name is valid and not in use by another price list of the organization
+async fn require_valid_name(driver: &Driver, org: &Org, name: &str) -> WebResult<()> {
+    if name.is_empty() {
+        return Err(Error::BadRequest("Price list name may not be empty".to_string()));
+    }
+
+    if PriceList::get_by_name(driver, org, name).await?.is_some() {
+        return Err(Error::Conflict(format!("Price list '{name}' already exists")));
+    }
+
+    Ok(())
+}
+
+fn require_valid_period(valid_from: Option<i64>, valid_until: Option<i64>) -> WebResult<()> {
+    match (valid_from, valid_until) {
+        (Some(from), Some(until)) if from >= until => Err(Error::BadRequest("The validity period must end after it starts".to_string())),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,71 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, Product, TierPrice};
+use proto::PriceListSetPricesRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::get_price_list;
+use crate::session::Session;
+use crate::WebData;
+
+/// Replace the prices of a product in a price list
+pub async fn prices(data: WebData, session: Session, payload: Payload<PriceListSetPricesRequest>) -> WebResult<Empty> {
+    let mut price_list = get_price_list(&data.driver, &payload.price_list_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &price_list.org_id, OrgScope::UpdateProduct).await?;
+
+    let product = match Product::get(&data.driver, payload.product_id.clone()).await? {
+        Some(x) if x.org_id.eq(&price_list.org_id) => x,
+        _ => return Err(Error::NotFound("Product not found".to_string())),
+    };
+
+    let mut tiers: Vec<TierPrice> = Vec::with_capacity(payload.tiers.len());
+    for tier in &payload.tiers {
+        if ![tier.min_quantity, tier.price_per_unit].iter().all(|x| x.is_finite()) {
+            return Err(Error::BadRequest("Quantities and prices must be finite numbers".to_string()));
+        }
+
+        if tier.min_quantity < 0.0 || tier.price_per_unit < 0.0 {
+            return Err(Error::BadRequest("Quantities and prices may not be negative".to_string()));
+        }
+
+        if tiers.iter().any(|x| x.min_quantity == tier.min_quantity) {
+            return Err(Error::BadRequest(format!("More than one price for a minimum quantity of {}", tier.min_quantity)));
+        }
+
+        tiers.push(TierPrice {
+            product_id: product.id.clone(),
+            min_quantity: tier.min_quantity,
+            price_per_unit: tier.price_per_unit,
+        });
+    }
+
+    let original = price_list.prices.iter()
+        .filter(|x| x.product_id.eq(&product.id))
+        .map(|x| (x.min_quantity, x.price_per_unit))
+        .collect::<Vec<_>>();
+
+    let mut tx = data.driver.start_transaction().await?;
+    price_list.set_prices_with_tx(&mut tx, &product, tiers).await?;
+
+    let new = price_list.prices.iter()
+        .filter(|x| x.product_id.eq(&product.id))
+        .map(|x| (x.min_quantity, x.price_per_unit))
+        .collect::<Vec<_>>();
+
+    let diff = AuditDiff::new()
+        .field(&format!("prices.{}", product.id), original, new);
+
+    if !diff.is_empty() {
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: price_list.org_id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::PriceList,
+            entity_id: price_list.id.clone(),
+            action: AuditAction::Update,
+            diff,
+        }).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Empty)
+}
@@ -0,0 +1,31 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::PriceListRemoveRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::get_price_list;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<PriceListRemoveRequest>) -> WebResult<Empty> {
+    let price_list = get_price_list(&data.driver, &payload.price_list_id).await?;
+    OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &price_list.org_id, OrgScope::RemoveProduct).await?;
+
+    let audit = AuditLogEntryBuilder {
+        org_id: price_list.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::PriceList,
+        entity_id: price_list.id.clone(),
+        action: AuditAction::Remove,
+        diff: AuditDiff::new()
+            .removed("name", &price_list.name),
+    };
+
+    let mut tx = data.driver.start_transaction().await?;
+    price_list.remove_with_tx(&mut tx).await?;
+    AuditLogEntry::create(&mut tx, audit).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -0,0 +1,58 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::PriceListUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::pricelist::{get_price_list, require_valid_name, require_valid_period};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<PriceListUpdateRequest>) -> WebResult<Empty> {
+    let mut price_list = get_price_list(&data.driver, &payload.price_list_id).await?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &price_list.org_id, OrgScope::UpdateProduct).await?;
+
+    let original = price_list.clone();
+
+    if let Some(name) = &payload.name {
+        if price_list.name.ne(name) {
+            require_valid_name(&data.driver, &access.org, name).await?;
+            price_list.name = name.clone();
+        }
+    }
+
+    if let Some(true) = payload.remove_valid_from {
+        price_list.valid_from = None;
+    } else if let Some(valid_from) = payload.valid_from {
+        price_list.valid_from = Some(valid_from);
+    }
+
+    if let Some(true) = payload.remove_valid_until {
+        price_list.valid_until = None;
+    } else if let Some(valid_until) = payload.valid_until {
+        price_list.valid_until = Some(valid_until);
+    }
+
+    require_valid_period(price_list.valid_from, price_list.valid_until)?;
+    let mut tx = data.driver.start_transaction().await?;
+    price_list.update_with_tx(&mut tx).await?;
+
+    let diff = AuditDiff::new()
+        .field("name", &original.name, &price_list.name)
+        .field("valid_from", original.valid_from, price_list.valid_from)
+        .field("valid_until", original.valid_until, price_list.valid_until);
+
+    if !diff.is_empty() {
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: price_list.org_id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::PriceList,
+            entity_id: price_list.id.clone(),
+            action: AuditAction::Update,
+            diff,
+        }).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Empty)
+}
@@ -1,18 +1,18 @@
 use actix_multiresponse::Payload;
-use dal::entities::{Entity, OrgScope, Product};
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, Product};
 use proto::ProductUpdateRequest;
 use crate::empty::Empty;
 use crate::error::{Error, WebResult};
-use crate::routes::v1::can_access;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::product::{get_category, parse_unit, require_unique_code};
 use crate::session::Session;
 use crate::WebData;
 
 pub async fn update(data: WebData, session: Session, payload: Payload<ProductUpdateRequest>) -> WebResult<Empty> {
-    let mut product = Product::get(&data.driver, payload.product_id.clone())?.ok_or(Error::NotFound("Product not found".to_string()))?;
-    let access = can_access(&data.driver, &session.user(&data.driver)?, &product.org_id, OrgScope::RemoveProduct)?;
-    if !access.accessible {
-        return Err(Error::Forbidden(String::default()));
-    }
+    let mut product = Product::get(&data.driver, payload.product_id.clone()).await?.ok_or(Error::NotFound("Product not found".to_string()))?;
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &product.org_id, OrgScope::UpdateProduct).await?;
+
+    let original = product.clone();
 
     if let Some(name) = &payload.name {
         product.name = name.clone();
@@ -38,10 +38,21 @@ pub async fn update(data: WebData, session: Session, payload: Payload<ProductUpd
         }
     } else {
         if let Some(product_code) = &payload.product_code {
+            require_unique_code(&data.driver, &access.org, product_code, Some(&product.id)).await?;
             product.product_code = Some(product_code.clone())
         }
     }
 
+    if let Some(unit) = &payload.unit {
+        product.unit = parse_unit(unit)?;
+    }
+
+    if let Some(true) = payload.remove_category {
+        product.category_id = None;
+    } else if let Some(category_id) = &payload.category_id {
+        product.category_id = Some(get_category(&data.driver, &access.org, category_id).await?.id);
+    }
+
     if let Some(remove_tax_percentage) = payload.remove_tax_percentage {
         if remove_tax_percentage {
             product.tax_percentage = None;
@@ -52,6 +63,29 @@ pub async fn update(data: WebData, session: Session, payload: Payload<ProductUpd
         }
     }
 
-    product.update()?;
+    let mut tx = data.driver.start_transaction().await?;
+    product.update_with_tx(&mut tx).await?;
+
+    let diff = AuditDiff::new()
+        .field("name", &original.name, &product.name)
+        .field("description", &original.description, &product.description)
+        .field("product_code", &original.product_code, &product.product_code)
+        .field("price_per_unit", original.price_per_unit, product.price_per_unit)
+        .field("tax_percentage", original.tax_percentage, product.tax_percentage)
+        .field("unit", original.unit.to_string(), product.unit.to_string())
+        .field("category_id", &original.category_id, &product.category_id);
+
+    if !diff.is_empty() {
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: product.org_id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::Product,
+            entity_id: product.id.clone(),
+            action: AuditAction::Update,
+            diff,
+        }).await?;
+    }
+
+    tx.commit().await?;
     Ok(Empty)
 }
@@ -0,0 +1,65 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, PurchaseInvoice, PurchaseInvoiceBuilder, PurchaseInvoiceDetails};
+use proto::{PurchaseInvoiceCreateRequest, PurchaseInvoiceCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::purchase::{duplicate_error, parse_currency, require_valid_amounts, require_valid_description, require_valid_invoice_number, start_of_day};
+use crate::routes::v1::supplier::get_supplier;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<PurchaseInvoiceCreateRequest>) -> WebResult<Payload<PurchaseInvoiceCreateResponse>> {
+    let user = session.user(&data.driver).await?;
+    let (supplier, access) = get_supplier(&data.driver, &user, &payload.supplier_id, OrgScope::ManagePurchaseInvoice).await?;
+
+    require_valid_invoice_number(&payload.invoice_number)?;
+    let currency = parse_currency(&payload.currency)?;
+    let deductible_vat_amount = payload.deductible_vat_amount.unwrap_or(payload.vat_amount);
+    require_valid_amounts(payload.net_amount, payload.vat_amount, deductible_vat_amount)?;
+
+    if let Some(description) = &payload.description {
+        require_valid_description(description)?;
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    let invoice = PurchaseInvoice::create_with_tx(&mut tx, &data.driver, PurchaseInvoiceBuilder {
+        org: &access.org,
+        supplier: &supplier,
+        details: PurchaseInvoiceDetails {
+            invoice_number: payload.invoice_number.trim().to_string(),
+            issue_date: start_of_day(payload.issue_date),
+            due_date: payload.due_date.map(start_of_day),
+            currency,
+            net_amount: payload.net_amount,
+            vat_amount: payload.vat_amount,
+            deductible_vat_amount,
+            description: payload.description.clone(),
+        },
+    }).await.map_err(duplicate_error)?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: invoice.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::PurchaseInvoice,
+        entity_id: invoice.id.clone(),
+        action: AuditAction::Create,
+        diff: invoice_created_diff(&invoice),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(PurchaseInvoiceCreateResponse {
+        purchase_invoice_id: invoice.id,
+    }))
+}
+
+pub(super) fn invoice_created_diff(invoice: &PurchaseInvoice) -> AuditDiff {
+    AuditDiff::new()
+        .created("supplier_id", &invoice.supplier_id)
+        .created("invoice_number", &invoice.invoice_number)
+        .created("issue_date", invoice.issue_date)
+        .created("due_date", invoice.due_date)
+        .created("currency", &invoice.currency)
+        .created("net_amount", invoice.net_amount)
+        .created("vat_amount", invoice.vat_amount)
+        .created("deductible_vat_amount", invoice.deductible_vat_amount)
+        .created("description", &invoice.description)
+}
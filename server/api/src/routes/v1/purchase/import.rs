@@ -0,0 +1,98 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, PurchaseInvoice, PurchaseInvoiceDetails, SupplierDetails};
+use proto::PurchaseInvoiceImportResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::purchase::{dal_purchase_invoice_to_proto, duplicate_error, parse_currency, require_valid_amounts, require_valid_description, require_valid_invoice_number};
+use crate::routes::v1::purchase::create::invoice_created_diff;
+use crate::routes::v1::supplier::{dal_supplier_to_proto, normalize_vat_number};
+use crate::session::Session;
+use crate::{ubl, WebData};
+
+/// The maximum size of an uploaded e-invoice in bytes
+pub const MAX_IMPORT_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    /// Read the invoice and report the outcome without storing anything
+    dry_run: Option<bool>,
+}
+
+/// Record a purchase invoice from a UBL 2.1 e-invoice in the request body.
+/// The supplier is matched on its VAT number, or on its name if the invoice has no VAT number,
+/// and created if no supplier matches. The full VAT amount is recorded as deductible
+pub async fn import(data: WebData, session: Session, query: web::Query<Query>, body: web::Bytes) -> WebResult<Payload<PurchaseInvoiceImportResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::ManagePurchaseInvoice).await?;
+
+    let parsed = ubl::parse(&body).map_err(|e| Error::BadRequest(format!("Failed to read e-invoice: {e}")))?;
+
+    require_valid_invoice_number(&parsed.invoice_number)?;
+    let currency = parse_currency(&parsed.currency)?;
+    let (net_amount, vat_amount) = (parsed.net_amount as f32, parsed.vat_amount as f32);
+    require_valid_amounts(net_amount, vat_amount, vat_amount)?;
+    if let Some(note) = &parsed.note {
+        require_valid_description(note)?;
+    }
+
+    let supplier = SupplierDetails {
+        name: parsed.supplier.name.trim().to_string(),
+        vat_number: parsed.supplier.vat_number.as_deref().map(normalize_vat_number),
+        email: parsed.supplier.email,
+        address: parsed.supplier.address,
+    };
+
+    let details = PurchaseInvoiceDetails {
+        invoice_number: parsed.invoice_number.trim().to_string(),
+        issue_date: parsed.issue_date,
+        due_date: parsed.due_date,
+        currency,
+        net_amount,
+        vat_amount,
+        deductible_vat_amount: vat_amount,
+        description: parsed.note,
+    };
+
+    let dry_run = query.dry_run.unwrap_or(false);
+    let mut tx = data.driver.start_transaction().await?;
+    let imported = PurchaseInvoice::import_with_tx(&mut tx, &data.driver, &access.org, supplier, details).await.map_err(duplicate_error)?;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        if imported.supplier_created {
+            AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+                org_id: access.org.id.clone(),
+                actor: session.actor(),
+                entity_type: AuditEntityType::Supplier,
+                entity_id: imported.supplier.id.clone(),
+                action: AuditAction::Create,
+                diff: AuditDiff::new()
+                    .created("name", &imported.supplier.name)
+                    .created("vat_number", &imported.supplier.vat_number)
+                    .created("email", &imported.supplier.email)
+                    .created("address", &imported.supplier.address),
+            }).await?;
+        }
+
+        AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+            org_id: access.org.id.clone(),
+            actor: session.actor(),
+            entity_type: AuditEntityType::PurchaseInvoice,
+            entity_id: imported.invoice.id.clone(),
+            action: AuditAction::Create,
+            diff: invoice_created_diff(&imported.invoice),
+        }).await?;
+
+        tx.commit().await?;
+    }
+
+    Ok(Payload(PurchaseInvoiceImportResponse {
+        applied: !dry_run,
+        purchase_invoice: Some(dal_purchase_invoice_to_proto(imported.invoice)),
+        supplier: Some(dal_supplier_to_proto(imported.supplier)),
+        supplier_created: imported.supplier_created,
+    }))
+}
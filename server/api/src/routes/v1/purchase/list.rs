@@ -0,0 +1,41 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, PurchaseInvoice, PurchaseInvoiceFilter, PurchaseInvoiceSort};
+use dal::pagination::SortDirection;
+use proto::PurchaseInvoiceListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::{OrgAccess, PageQuery};
+use crate::routes::v1::purchase::dal_purchase_invoice_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    supplier_id: Option<String>,
+    from: Option<i64>,
+    until: Option<i64>,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<PurchaseInvoiceListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetPurchaseInvoice).await?;
+
+    let filter = PurchaseInvoiceFilter {
+        supplier_id: query.supplier_id.clone(),
+        from: query.from,
+        until: query.until,
+    };
+
+    let page = PurchaseInvoice::list_for_org(&data.driver, &access.org, &filter, &page.page_request::<PurchaseInvoiceSort>(SortDirection::Desc)?).await?;
+    let purchase_invoices = page.items.into_iter()
+        .map(dal_purchase_invoice_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(PurchaseInvoiceListResponse {
+        purchase_invoices,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
+    }))
+}
@@ -0,0 +1,133 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, OrgScope, PurchaseInvoice, User};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+use crate::routes::v1::OrgAccess;
+
+mod create;
+mod import;
+mod list;
+mod remove;
+mod update;
+mod vat;
+
+const MAX_INVOICE_NUMBER_LENGTH: usize = 64;
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/purchase")
+            .route("/create", web::post().to(create::create))
+            .service(web::resource("/import")
+                .app_data(web::PayloadConfig::new(import::MAX_IMPORT_SIZE))
+                .route(web::post().to(import::import))
+            )
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+            .route("/vat", web::get().to(vat::vat))
+        );
+    }
+}
+
+fn dal_purchase_invoice_to_proto(invoice: PurchaseInvoice) -> proto::PurchaseInvoice {
+    proto::PurchaseInvoice {
+        id: invoice.id,
+        supplier_id: invoice.supplier_id,
+        invoice_number: invoice.invoice_number,
+        issue_date: invoice.issue_date,
+        due_date: invoice.due_date,
+        currency: invoice.currency,
+        net_amount: invoice.net_amount,
+        vat_amount: invoice.vat_amount,
+        deductible_vat_amount: invoice.deductible_vat_amount,
+        description: invoice.description,
+        created_at: invoice.created_at,
+    }
+}
+
+/// Get a purchase invoice, requiring the user to have the provided scope in its organization
+async fn get_purchase_invoice(driver: &Driver, user: &User, purchase_invoice_id: &str, scope: OrgScope) -> WebResult<PurchaseInvoice> {
+    let invoice = PurchaseInvoice::get(driver, purchase_invoice_id.to_string()).await?
+        .ok_or(Error::NotFound("Purchase invoice not found".to_string()))?;
+    OrgAccess::require(driver, user, &invoice.org_id, scope).await?;
+
+    Ok(invoice)
+}
+
+/// Map a duplicate invoice number to a `409 Conflict`
+fn duplicate_error(error: dal::Error) -> Error {
+    match error {
+        dal::Error::DuplicatePurchaseInvoice => Error::Conflict("The supplier already has a purchase invoice with this number".to_string()),
+        e => e.into(),
+    }
+}
+
+fn require_valid_invoice_number(invoice_number: &str) -> WebResult<()> {
+    if invoice_number.trim().is_empty() {
+        return Err(Error::BadRequest("Invoice number may not be empty".to_string()));
+    }
+
+    if invoice_number.len() > MAX_INVOICE_NUMBER_LENGTH {
+        return Err(Error::BadRequest(format!("Invoice number may be at most {MAX_INVOICE_NUMBER_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+/// Require a three letter ISO 4217 currency code, returning it in uppercase
+fn parse_currency(currency: &str) -> WebResult<String> {
+    if currency.len() != 3 || !currency.chars().all(|x| x.is_ascii_alphabetic()) {
+        return Err(Error::BadRequest(format!("Invalid currency code '{currency}'")));
+    }
+
+    Ok(currency.to_uppercase())
+}
+
+/// Require finite amounts, and a deductible VAT amount between zero and the VAT amount.
+/// Amounts may be negative, e.g. for a supplier's credit note, in which case the deductible VAT amount must be too
+fn require_valid_amounts(net_amount: f32, vat_amount: f32, deductible_vat_amount: f32) -> WebResult<()> {
+    if ![net_amount, vat_amount, deductible_vat_amount].iter().all(|x| x.is_finite()) {
+        return Err(Error::BadRequest("Amounts must be finite numbers".to_string()));
+    }
+
+    let (low, high) = if vat_amount < 0.0 { (vat_amount, 0.0) } else { (0.0, vat_amount) };
+    if deductible_vat_amount < low || deductible_vat_amount > high {
+        return Err(Error::BadRequest("The deductible VAT amount must be between zero and the VAT amount".to_string()));
+    }
+
+    Ok(())
+}
+
+fn require_valid_description(description: &str) -> WebResult<()> {
+    if description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(Error::BadRequest(format!("Description may be at most {MAX_DESCRIPTION_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+/// Truncate a UNIX timestamp to the start of its day, in UTC
+fn start_of_day(timestamp: i64) -> i64 {
+    timestamp.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn amounts() {
+        assert!(require_valid_amounts(100.0, 21.0, 21.0).is_ok());
+        assert!(require_valid_amounts(100.0, 21.0, 0.0).is_ok());
+        assert!(require_valid_amounts(-100.0, -21.0, -10.5).is_ok());
+        assert!(require_valid_amounts(100.0, 21.0, 21.5).is_err());
+        assert!(require_valid_amounts(100.0, 21.0, -1.0).is_err());
+        assert!(require_valid_amounts(f32::NAN, 21.0, 21.0).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use actix_multiresponse::Payload;
+use dal::entities::{Attachment, AttachmentOwnerType, AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::PurchaseInvoiceRemoveRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::attachment::release_attachments;
+use crate::routes::v1::purchase::get_purchase_invoice;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<PurchaseInvoiceRemoveRequest>) -> WebResult<Empty> {
+    let user = session.user(&data.driver).await?;
+    let invoice = get_purchase_invoice(&data.driver, &user, &payload.purchase_invoice_id, OrgScope::ManagePurchaseInvoice).await?;
+
+    let org_id = invoice.org_id.clone();
+    let invoice_id = invoice.id.clone();
+    let diff = AuditDiff::new()
+        .removed("supplier_id", &invoice.supplier_id)
+        .removed("invoice_number", &invoice.invoice_number)
+        .removed("issue_date", invoice.issue_date)
+        .removed("currency", &invoice.currency)
+        .removed("net_amount", invoice.net_amount)
+        .removed("vat_amount", invoice.vat_amount)
+        .removed("deductible_vat_amount", invoice.deductible_vat_amount);
+    let mut tx = data.driver.start_transaction().await?;
+    invoice.remove_with_tx(&mut tx).await?;
+    let attachments = Attachment::remove_for_owner_with_tx(&mut tx, &data.driver, AttachmentOwnerType::PurchaseInvoice, &invoice_id).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id,
+        actor: session.actor(),
+        entity_type: AuditEntityType::PurchaseInvoice,
+        entity_id: invoice_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    release_attachments(&data.driver, &data.storage, &attachments).await?;
+    Ok(Empty)
+}
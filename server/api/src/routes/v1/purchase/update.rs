@@ -0,0 +1,88 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::PurchaseInvoiceUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::purchase::{duplicate_error, get_purchase_invoice, parse_currency, require_valid_amounts, require_valid_description, require_valid_invoice_number, start_of_day};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<PurchaseInvoiceUpdateRequest>) -> WebResult<Empty> {
+    let user = session.user(&data.driver).await?;
+    let mut invoice = get_purchase_invoice(&data.driver, &user, &payload.purchase_invoice_id, OrgScope::ManagePurchaseInvoice).await?;
+    let original = invoice.clone();
+
+    if let Some(invoice_number) = &payload.invoice_number {
+        require_valid_invoice_number(invoice_number)?;
+        invoice.invoice_number = invoice_number.trim().to_string();
+    }
+
+    if let Some(issue_date) = payload.issue_date {
+        invoice.issue_date = start_of_day(issue_date);
+    }
+
+    if let Some(true) = payload.remove_due_date {
+        invoice.due_date = None;
+    } else if let Some(due_date) = payload.due_date {
+        invoice.due_date = Some(start_of_day(due_date));
+    }
+
+    if let Some(currency) = &payload.currency {
+        invoice.currency = parse_currency(currency)?;
+    }
+
+    if let Some(net_amount) = payload.net_amount {
+        invoice.net_amount = net_amount;
+    }
+
+    // Unless set explicitly, a fully deductible VAT amount remains fully deductible when the VAT amount changes
+    if let Some(vat_amount) = payload.vat_amount {
+        if payload.deductible_vat_amount.is_none() && invoice.deductible_vat_amount == invoice.vat_amount {
+            invoice.deductible_vat_amount = vat_amount;
+        }
+
+        invoice.vat_amount = vat_amount;
+    }
+
+    if let Some(deductible_vat_amount) = payload.deductible_vat_amount {
+        invoice.deductible_vat_amount = deductible_vat_amount;
+    }
+
+    require_valid_amounts(invoice.net_amount, invoice.vat_amount, invoice.deductible_vat_amount)?;
+
+    if let Some(true) = payload.remove_description {
+        invoice.description = None;
+    } else if let Some(description) = &payload.description {
+        require_valid_description(description)?;
+        invoice.description = Some(description.clone());
+    }
+
+    let diff = AuditDiff::new()
+        .field("invoice_number", &original.invoice_number, &invoice.invoice_number)
+        .field("issue_date", original.issue_date, invoice.issue_date)
+        .field("due_date", original.due_date, invoice.due_date)
+        .field("currency", &original.currency, &invoice.currency)
+        .field("net_amount", original.net_amount, invoice.net_amount)
+        .field("vat_amount", original.vat_amount, invoice.vat_amount)
+        .field("deductible_vat_amount", original.deductible_vat_amount, invoice.deductible_vat_amount)
+        .field("description", &original.description, &invoice.description);
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    invoice.update_with_tx(&mut tx).await.map_err(duplicate_error)?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: invoice.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::PurchaseInvoice,
+        entity_id: invoice.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
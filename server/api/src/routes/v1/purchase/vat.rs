@@ -0,0 +1,40 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, PurchaseInvoice};
+use proto::PurchaseVatResponse;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    /// Only invoices issued on or after this UNIX timestamp
+    from: i64,
+    /// Only invoices issued on or before this UNIX timestamp
+    until: i64,
+}
+
+/// Sum the purchase invoices issued within a period per currency, providing the input VAT of the VAT return
+pub async fn vat(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<PurchaseVatResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetPurchaseInvoice).await?;
+    if query.from > query.until {
+        return Err(Error::BadRequest("The start of the period must not be after its end".to_string()));
+    }
+
+    let totals = PurchaseInvoice::vat_totals(&data.driver, &access.org, query.from, query.until).await?.into_iter()
+        .map(|x| proto::PurchaseVatTotals {
+            currency: x.currency,
+            invoice_count: x.invoice_count,
+            net_amount: x.net_amount,
+            vat_amount: x.vat_amount,
+            deductible_vat_amount: x.deductible_vat_amount,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Payload(PurchaseVatResponse {
+        totals,
+    }))
+}
@@ -0,0 +1,55 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, Supplier, SupplierBuilder};
+use proto::{SupplierCreateRequest, SupplierCreateResponse};
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::supplier::{normalize_vat_number, require_unique_vat_number, require_valid_address, require_valid_email, require_valid_name, require_valid_vat_number};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<SupplierCreateRequest>) -> WebResult<Payload<SupplierCreateResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::ManagePurchaseInvoice).await?;
+
+    require_valid_name(&payload.name)?;
+
+    let vat_number = payload.vat_number.as_deref().map(normalize_vat_number);
+    if let Some(vat_number) = &vat_number {
+        require_valid_vat_number(vat_number)?;
+        require_unique_vat_number(&data.driver, &access.org, vat_number, None).await?;
+    }
+
+    if let Some(email) = &payload.email {
+        require_valid_email(email)?;
+    }
+
+    if let Some(address) = &payload.address {
+        require_valid_address(address)?;
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    let supplier = Supplier::create_with_tx(&mut tx, &data.driver, SupplierBuilder {
+        org: &access.org,
+        name: payload.name.trim().to_string(),
+        vat_number,
+        email: payload.email.clone(),
+        address: payload.address.clone(),
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: supplier.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Supplier,
+        entity_id: supplier.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("name", &supplier.name)
+            .created("vat_number", &supplier.vat_number)
+            .created("email", &supplier.email)
+            .created("address", &supplier.address),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(SupplierCreateResponse {
+        supplier_id: supplier.id,
+    }))
+}
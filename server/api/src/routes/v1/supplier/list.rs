@@ -0,0 +1,26 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, Supplier};
+use proto::SupplierListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::supplier::dal_supplier_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+}
+
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>) -> WebResult<Payload<SupplierListResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &query.org_id, OrgScope::GetPurchaseInvoice).await?;
+    let suppliers = Supplier::list_for_org(&data.driver, &access.org).await?.into_iter()
+        .map(dal_supplier_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(SupplierListResponse {
+        suppliers,
+    }))
+}
@@ -0,0 +1,99 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, Org, OrgScope, Supplier, User};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+use crate::routes::v1::OrgAccess;
+
+mod create;
+mod list;
+mod remove;
+mod update;
+
+/// The maximum length of the name, email address and VAT number of a supplier
+const MAX_FIELD_LENGTH: usize = 255;
+/// The maximum length of the address of a supplier
+const MAX_ADDRESS_LENGTH: usize = 1024;
+const MAX_VAT_NUMBER_LENGTH: usize = 32;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/supplier")
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+pub(super) fn dal_supplier_to_proto(supplier: Supplier) -> proto::Supplier {
+    proto::Supplier {
+        id: supplier.id,
+        name: supplier.name,
+        vat_number: supplier.vat_number,
+        email: supplier.email,
+        address: supplier.address,
+        created_at: supplier.created_at,
+    }
+}
+
+/// Get a supplier, requiring the user to have the provided scope in its organization
+pub(super) async fn get_supplier(driver: &Driver, user: &User, supplier_id: &str, scope: OrgScope) -> WebResult<(Supplier, OrgAccess)> {
+    let supplier = Supplier::get(driver, supplier_id.to_string()).await?
+        .ok_or(Error::NotFound("Supplier not found".to_string()))?;
+    let access = OrgAccess::require(driver, user, &supplier.org_id, scope).await?;
+
+    Ok((supplier, access))
+}
+
+/// Normalize a VAT number to the form suppliers are matched on: uppercase, without spaces, dots or dashes
+pub(super) fn normalize_vat_number(vat_number: &str) -> String {
+    vat_number.chars()
+        .filter(|x| !x.is_whitespace() && *x != '.' && *x != '-')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Require that no other supplier of the organization has the VAT number
+async fn require_unique_vat_number(driver: &Driver, org: &Org, vat_number: &str, supplier_id: Option<&str>) -> WebResult<()> {
+    match Supplier::get_by_vat_number(driver, org, vat_number).await? {
+        Some(existing) if Some(existing.id.as_str()).ne(&supplier_id) => Err(Error::Conflict(format!("A supplier with VAT number '{vat_number}' already exists"))),
+        _ => Ok(()),
+    }
+}
+
+fn require_valid_name(name: &str) -> WebResult<()> {
+    if name.trim().is_empty() {
+        return Err(Error::BadRequest("Name may not be empty".to_string()));
+    }
+
+    require_max_length("Name", name, MAX_FIELD_LENGTH)
+}
+
+fn require_valid_vat_number(vat_number: &str) -> WebResult<()> {
+    if vat_number.is_empty() {
+        return Err(Error::BadRequest("VAT number may not be empty".to_string()));
+    }
+
+    require_max_length("VAT number", vat_number, MAX_VAT_NUMBER_LENGTH)
+}
+
+fn require_valid_email(email: &str) -> WebResult<()> {
+    require_max_length("Email address", email, MAX_FIELD_LENGTH)
+}
+
+fn require_valid_address(address: &str) -> WebResult<()> {
+    require_max_length("Address", address, MAX_ADDRESS_LENGTH)
+}
+
+fn require_max_length(field: &str, value: &str, max: usize) -> WebResult<()> {
+    if value.len() > max {
+        return Err(Error::BadRequest(format!("{field} may be at most {max} characters")));
+    }
+
+    Ok(())
+}
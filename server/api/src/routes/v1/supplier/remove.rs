@@ -0,0 +1,42 @@
+use actix_multiresponse::Payload;
+use dal::entities::{Attachment, AttachmentOwnerType, AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope, PurchaseInvoice};
+use proto::SupplierRemoveRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::attachment::release_attachments;
+use crate::routes::v1::supplier::get_supplier;
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<SupplierRemoveRequest>) -> WebResult<Empty> {
+    let user = session.user(&data.driver).await?;
+    let (supplier, _) = get_supplier(&data.driver, &user, &payload.supplier_id, OrgScope::ManagePurchaseInvoice).await?;
+
+    if PurchaseInvoice::exists_for_supplier(&data.driver, &supplier).await? {
+        return Err(Error::Conflict("The supplier has purchase invoices".to_string()));
+    }
+
+    let org_id = supplier.org_id.clone();
+    let supplier_id = supplier.id.clone();
+    let diff = AuditDiff::new()
+        .removed("name", &supplier.name)
+        .removed("vat_number", &supplier.vat_number)
+        .removed("email", &supplier.email)
+        .removed("address", &supplier.address);
+    let mut tx = data.driver.start_transaction().await?;
+    supplier.remove_with_tx(&mut tx).await?;
+    let attachments = Attachment::remove_for_owner_with_tx(&mut tx, &data.driver, AttachmentOwnerType::Supplier, &supplier_id).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id,
+        actor: session.actor(),
+        entity_type: AuditEntityType::Supplier,
+        entity_id: supplier_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    release_attachments(&data.driver, &data.storage, &attachments).await?;
+    Ok(Empty)
+}
@@ -0,0 +1,67 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, OrgScope};
+use proto::SupplierUpdateRequest;
+use crate::empty::Empty;
+use crate::error::WebResult;
+use crate::routes::v1::supplier::{get_supplier, normalize_vat_number, require_unique_vat_number, require_valid_address, require_valid_email, require_valid_name, require_valid_vat_number};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<SupplierUpdateRequest>) -> WebResult<Empty> {
+    let user = session.user(&data.driver).await?;
+    let (mut supplier, access) = get_supplier(&data.driver, &user, &payload.supplier_id, OrgScope::ManagePurchaseInvoice).await?;
+    let original = supplier.clone();
+
+    if let Some(name) = &payload.name {
+        require_valid_name(name)?;
+        supplier.name = name.trim().to_string();
+    }
+
+    if let Some(true) = payload.remove_vat_number {
+        supplier.vat_number = None;
+    } else if let Some(vat_number) = &payload.vat_number {
+        let vat_number = normalize_vat_number(vat_number);
+        require_valid_vat_number(&vat_number)?;
+        require_unique_vat_number(&data.driver, &access.org, &vat_number, Some(&supplier.id)).await?;
+        supplier.vat_number = Some(vat_number);
+    }
+
+    if let Some(true) = payload.remove_email {
+        supplier.email = None;
+    } else if let Some(email) = &payload.email {
+        require_valid_email(email)?;
+        supplier.email = Some(email.clone());
+    }
+
+    if let Some(true) = payload.remove_address {
+        supplier.address = None;
+    } else if let Some(address) = &payload.address {
+        require_valid_address(address)?;
+        supplier.address = Some(address.clone());
+    }
+
+    let diff = AuditDiff::new()
+        .field("name", &original.name, &supplier.name)
+        .field("vat_number", &original.vat_number, &supplier.vat_number)
+        .field("email", &original.email, &supplier.email)
+        .field("address", &original.address, &supplier.address);
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    supplier.update_with_tx(&mut tx).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: supplier.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::Supplier,
+        entity_id: supplier.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
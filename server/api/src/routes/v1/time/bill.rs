@@ -0,0 +1,69 @@
+use std::str::FromStr;
+use actix_multiresponse::Payload;
+use dal::entities::{group_time_entries, OrgScope, TimeEntry, TimeEntryGrouping, TimeEntryLine};
+use proto::{TimeEntryBillRequest, TimeEntryBillResponse};
+use time::OffsetDateTime;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::time::billing_error;
+use crate::session::Session;
+use crate::WebData;
+
+/// The maximum number of time entries billed at once
+const MAX_BILLED_ENTRIES: usize = 1000;
+
+/// Turn time entries into invoice lines. Only dry runs are accepted: the lines are not stored anywhere yet,
+/// so entries marked as billed would be lost until invoices exist
+pub async fn bill(data: WebData, session: Session, payload: Payload<TimeEntryBillRequest>) -> WebResult<Payload<TimeEntryBillResponse>> {
+    let access = OrgAccess::require(&data.driver, &session.user(&data.driver).await?, &payload.org_id, OrgScope::ManageTimeEntries).await?;
+
+    let grouping = match &payload.grouping {
+        Some(x) => TimeEntryGrouping::from_str(x).map_err(|_| Error::BadRequest(format!("Unknown grouping '{x}'")))?,
+        None => TimeEntryGrouping::default(),
+    };
+
+    if !payload.dry_run {
+        return Err(Error::BadRequest("Time entries can only be billed as a dry run until invoices are supported".to_string()));
+    }
+
+    if payload.time_entry_ids.is_empty() {
+        return Err(Error::BadRequest("No time entries provided".to_string()));
+    }
+
+    if payload.time_entry_ids.len() > MAX_BILLED_ENTRIES {
+        return Err(Error::BadRequest(format!("At most {MAX_BILLED_ENTRIES} time entries can be billed at once")));
+    }
+
+    let entries = TimeEntry::list_billable(&data.driver, &access.org, &payload.time_entry_ids).await
+        .map_err(billing_error)?;
+
+    let lines = group_time_entries(&entries, grouping)
+        .into_iter()
+        .map(line_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(TimeEntryBillResponse {
+        lines,
+        billing_id: None,
+    }))
+}
+
+fn line_to_proto(line: TimeEntryLine) -> proto::TimeEntryLine {
+    let projects = line.projects.join(", ");
+    let description = match line.work_date.and_then(|x| OffsetDateTime::from_unix_timestamp(x).ok()) {
+        Some(date) => format!("{}: {projects}", date.date()),
+        None => projects,
+    };
+
+    proto::TimeEntryLine {
+        description,
+        hours: line.hours(),
+        amount: line.amount(),
+        projects: line.projects,
+        work_date: line.work_date,
+        duration_minutes: line.duration_minutes,
+        hourly_rate: line.hourly_rate,
+        details: line.descriptions,
+        time_entry_ids: line.time_entry_ids,
+    }
+}
@@ -0,0 +1,60 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder, Entity, OrgScope, TimeEntry, TimeEntryBuilder, User};
+use proto::{TimeEntryCreateRequest, TimeEntryCreateResponse};
+use crate::error::{Error, WebResult};
+use crate::routes::v1::OrgAccess;
+use crate::routes::v1::time::{require_valid_duration, require_valid_project, require_valid_rate, start_of_day};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn create(data: WebData, session: Session, payload: Payload<TimeEntryCreateRequest>) -> WebResult<Payload<TimeEntryCreateResponse>> {
+    let user = session.user(&data.driver).await?;
+    let access = OrgAccess::require(&data.driver, &user, &payload.org_id, OrgScope::TrackTime).await?;
+
+    let worker = match &payload.user_id {
+        Some(user_id) if user_id.ne(&user.id) => {
+            access.check(&OrgScope::ManageTimeEntries)?;
+            let worker = User::get(&data.driver, user_id.clone()).await?;
+            match worker {
+                Some(worker) if access.org.get_user(&worker).await?.is_some() => worker,
+                _ => return Err(Error::NotFound("User is not part of the organization".to_string())),
+            }
+        },
+        _ => user,
+    };
+
+    require_valid_project(&payload.project)?;
+    require_valid_duration(payload.duration_minutes)?;
+    require_valid_rate(payload.hourly_rate)?;
+
+    let mut tx = data.driver.start_transaction().await?;
+    let entry = TimeEntry::create_with_tx(&mut tx, &data.driver, TimeEntryBuilder {
+        org: &access.org,
+        user: &worker,
+        project: payload.project.trim().to_string(),
+        work_date: start_of_day(payload.work_date),
+        duration_minutes: payload.duration_minutes,
+        description: payload.description.clone(),
+        hourly_rate: payload.hourly_rate,
+    }).await?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: entry.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::TimeEntry,
+        entity_id: entry.id.clone(),
+        action: AuditAction::Create,
+        diff: AuditDiff::new()
+            .created("user_id", &entry.user_id)
+            .created("project", &entry.project)
+            .created("work_date", entry.work_date)
+            .created("duration_minutes", entry.duration_minutes)
+            .created("description", &entry.description)
+            .created("hourly_rate", entry.hourly_rate),
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Payload(TimeEntryCreateResponse {
+        time_entry_id: entry.id,
+    }))
+}
@@ -0,0 +1,55 @@
+use actix_multiresponse::Payload;
+use actix_web::web;
+use serde::Deserialize;
+use dal::entities::{OrgScope, TimeEntry, TimeEntryFilter, TimeEntrySort};
+use dal::pagination::SortDirection;
+use proto::TimeEntryListResponse;
+use crate::error::WebResult;
+use crate::routes::v1::{OrgAccess, PageQuery};
+use crate::routes::v1::time::dal_time_entry_to_proto;
+use crate::session::Session;
+use crate::WebData;
+
+#[derive(Debug, Deserialize)]
+pub struct Query {
+    org_id: String,
+    user_id: Option<String>,
+    project: Option<String>,
+    from: Option<i64>,
+    until: Option<i64>,
+    billed: Option<bool>,
+}
+
+/// List time entries. Without the [OrgScope::ManageTimeEntries] scope, only the user's own entries are listed
+pub async fn list(data: WebData, session: Session, query: web::Query<Query>, page: web::Query<PageQuery>) -> WebResult<Payload<TimeEntryListResponse>> {
+    let user = session.user(&data.driver).await?;
+    let access = OrgAccess::require(&data.driver, &user, &query.org_id, OrgScope::TrackTime).await?;
+
+    let user_id = match &query.user_id {
+        Some(user_id) if user_id.eq(&user.id) => Some(user_id.clone()),
+        user_id => {
+            access.check(&OrgScope::ManageTimeEntries)?;
+            user_id.clone()
+        }
+    };
+
+    let filter = TimeEntryFilter {
+        user_id,
+        project: query.project.clone(),
+        from: query.from,
+        until: query.until,
+        billed: query.billed,
+    };
+
+    let page = TimeEntry::list_for_org(&data.driver, &access.org, &filter, &page.page_request::<TimeEntrySort>(SortDirection::Desc)?).await?;
+    let time_entries = page.items.into_iter()
+        .map(dal_time_entry_to_proto)
+        .collect::<Vec<_>>();
+
+    Ok(Payload(TimeEntryListResponse {
+        time_entries,
+        page: Some(proto::PageInfo {
+            next_cursor: page.next_cursor,
+        }),
+    }))
+}
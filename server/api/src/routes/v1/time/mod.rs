@@ -0,0 +1,104 @@
+use actix_web::web;
+use actix_web::web::ServiceConfig;
+use dal::Driver;
+use dal::entities::{Entity, OrgScope, TimeEntry, User};
+use crate::error::{Error, WebResult};
+use crate::routable::Routable;
+use crate::routes::v1::OrgAccess;
+
+mod bill;
+mod create;
+mod list;
+mod remove;
+mod update;
+
+/// The maximum length of the name of a project
+const MAX_PROJECT_LENGTH: usize = 255;
+/// The maximum duration of a single time entry
+const MAX_DURATION_MINUTES: u32 = 24 * 60;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+pub struct Router;
+
+impl Routable for Router {
+    fn configure(config: &mut ServiceConfig) {
+        config.service(web::scope("/time")
+            .route("/bill", web::post().to(bill::bill))
+            .route("/create", web::post().to(create::create))
+            .route("/list", web::get().to(list::list))
+            .route("/remove", web::post().to(remove::remove))
+            .route("/update", web::post().to(update::update))
+        );
+    }
+}
+
+fn dal_time_entry_to_proto(entry: TimeEntry) -> proto::TimeEntry {
+    proto::TimeEntry {
+        id: entry.id,
+        user_id: entry.user_id,
+        project: entry.project,
+        work_date: entry.work_date,
+        duration_minutes: entry.duration_minutes,
+        description: entry.description,
+        hourly_rate: entry.hourly_rate,
+        billed_at: entry.billed_at,
+        billing_id: entry.billing_id,
+        created_at: entry.created_at,
+    }
+}
+
+/// Get a time entry the user may modify: their own entries with [OrgScope::TrackTime],
+/// or those of any user with [OrgScope::ManageTimeEntries]
+async fn get_own_time_entry(driver: &Driver, user: &User, time_entry_id: &str) -> WebResult<TimeEntry> {
+    let entry = TimeEntry::get(driver, time_entry_id.to_string()).await?
+        .ok_or(Error::NotFound("Time entry not found".to_string()))?;
+
+    let access = OrgAccess::require(driver, user, &entry.org_id, OrgScope::TrackTime).await?;
+    if entry.user_id.ne(&user.id) {
+        access.check(&OrgScope::ManageTimeEntries)?;
+    }
+
+    Ok(entry)
+}
+
+/// Map errors of modifying billed time entries to the matching HTTP status
+fn billing_error(error: dal::Error) -> Error {
+    match error {
+        dal::Error::AlreadyBilled => Error::Conflict("Time entry has already been billed".to_string()),
+        dal::Error::UnknownTimeEntry => Error::NotFound("Time entry not found".to_string()),
+        e => e.into(),
+    }
+}
+
+fn require_valid_project(project: &str) -> WebResult<()> {
+    if project.trim().is_empty() {
+        return Err(Error::BadRequest("Project may not be empty".to_string()));
+    }
+
+    if project.len() > MAX_PROJECT_LENGTH {
+        return Err(Error::BadRequest(format!("Project may be at most {MAX_PROJECT_LENGTH} characters")));
+    }
+
+    Ok(())
+}
+
+fn require_valid_duration(duration_minutes: u32) -> WebResult<()> {
+    if duration_minutes == 0 || duration_minutes > MAX_DURATION_MINUTES {
+        return Err(Error::BadRequest(format!("Duration must be between 1 and {MAX_DURATION_MINUTES} minutes")));
+    }
+
+    Ok(())
+}
+
+fn require_valid_rate(hourly_rate: f32) -> WebResult<()> {
+    if !hourly_rate.is_finite() || hourly_rate < 0.0 {
+        return Err(Error::BadRequest("Hourly rate must be a positive number".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Truncate a UNIX timestamp to the start of its day, in UTC
+fn start_of_day(timestamp: i64) -> i64 {
+    timestamp.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY
+}
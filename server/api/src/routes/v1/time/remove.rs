@@ -0,0 +1,39 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder};
+use proto::TimeEntryRemoveRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::time::{billing_error, get_own_time_entry};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn remove(data: WebData, session: Session, payload: Payload<TimeEntryRemoveRequest>) -> WebResult<Empty> {
+    let entry = get_own_time_entry(&data.driver, &session.user(&data.driver).await?, &payload.time_entry_id).await?;
+    if entry.billed_at.is_some() {
+        return Err(Error::Conflict("Time entry has already been billed".to_string()));
+    }
+
+    let org_id = entry.org_id.clone();
+    let entry_id = entry.id.clone();
+    let diff = AuditDiff::new()
+        .removed("user_id", &entry.user_id)
+        .removed("project", &entry.project)
+        .removed("work_date", entry.work_date)
+        .removed("duration_minutes", entry.duration_minutes)
+        .removed("description", &entry.description)
+        .removed("hourly_rate", entry.hourly_rate);
+    let mut tx = data.driver.start_transaction().await?;
+    entry.remove_with_tx(&mut tx).await.map_err(billing_error)?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id,
+        actor: session.actor(),
+        entity_type: AuditEntityType::TimeEntry,
+        entity_id: entry_id,
+        action: AuditAction::Remove,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -0,0 +1,66 @@
+use actix_multiresponse::Payload;
+use dal::entities::{AuditAction, AuditDiff, AuditEntityType, AuditLogEntry, AuditLogEntryBuilder};
+use proto::TimeEntryUpdateRequest;
+use crate::empty::Empty;
+use crate::error::{Error, WebResult};
+use crate::routes::v1::time::{billing_error, get_own_time_entry, require_valid_duration, require_valid_project, require_valid_rate, start_of_day};
+use crate::session::Session;
+use crate::WebData;
+
+pub async fn update(data: WebData, session: Session, payload: Payload<TimeEntryUpdateRequest>) -> WebResult<Empty> {
+    let mut entry = get_own_time_entry(&data.driver, &session.user(&data.driver).await?, &payload.time_entry_id).await?;
+    if entry.billed_at.is_some() {
+        return Err(Error::Conflict("Time entry has already been billed".to_string()));
+    }
+
+    let original = entry.clone();
+
+    if let Some(project) = &payload.project {
+        require_valid_project(project)?;
+        entry.project = project.trim().to_string();
+    }
+
+    if let Some(work_date) = payload.work_date {
+        entry.work_date = start_of_day(work_date);
+    }
+
+    if let Some(duration_minutes) = payload.duration_minutes {
+        require_valid_duration(duration_minutes)?;
+        entry.duration_minutes = duration_minutes;
+    }
+
+    if let Some(description) = &payload.description {
+        entry.description = description.clone();
+    }
+
+    if let Some(hourly_rate) = payload.hourly_rate {
+        require_valid_rate(hourly_rate)?;
+        entry.hourly_rate = hourly_rate;
+    }
+
+    let diff = AuditDiff::new()
+        .field("project", &original.project, &entry.project)
+        .field("work_date", original.work_date, entry.work_date)
+        .field("duration_minutes", original.duration_minutes, entry.duration_minutes)
+        .field("description", &original.description, &entry.description)
+        .field("hourly_rate", original.hourly_rate, entry.hourly_rate);
+
+    if diff.is_empty() {
+        return Ok(Empty);
+    }
+
+    let mut tx = data.driver.start_transaction().await?;
+    entry.update_with_tx(&mut tx).await.map_err(billing_error)?;
+
+    AuditLogEntry::create(&mut tx, AuditLogEntryBuilder {
+        org_id: entry.org_id.clone(),
+        actor: session.actor(),
+        entity_type: AuditEntityType::TimeEntry,
+        entity_id: entry.id.clone(),
+        action: AuditAction::Update,
+        diff,
+    }).await?;
+    tx.commit().await?;
+
+    Ok(Empty)
+}
@@ -3,7 +3,7 @@ use std::pin::Pin;
 use actix_web::{FromRequest, HttpRequest};
 use actix_web::dev::Payload;
 use dal::Driver;
-use dal::entities::{Entity, User};
+use dal::entities::{Actor, Entity, User};
 use crate::error::{Error, WebResult};
 use crate::WebData;
 
@@ -13,8 +13,13 @@ pub struct Session {
 }
 
 impl Session {
-    pub fn user<'a>(&self, driver: &'a Driver) -> WebResult<User<'a>> {
-        Ok(User::get(driver, self.id.clone())?.unwrap())
+    pub async fn user(&self, driver: &Driver) -> WebResult<User> {
+        Ok(User::get(driver, self.user_id.clone()).await?.unwrap())
+    }
+
+    /// The actor to record in the audit log for actions performed with this session
+    pub fn actor(&self) -> Actor {
+        Actor::User(self.user_id.clone())
     }
 }
 
@@ -31,7 +36,7 @@ impl FromRequest for Session {
                 .to_str().map_err(|e| Error::Unauthorized(format!("Invalid Authorization header: {e}")))?;
 
             if authorization.starts_with("US_") {
-                let user = User::get_by_session(&data.driver, &authorization.to_string())?.ok_or(Error::Unauthorized("Session does not exist or has expired".to_string()))?;
+                let user = User::get_by_session(&data.driver, authorization, data.config.session_expiry).await?.ok_or(Error::Unauthorized("Session does not exist or has expired".to_string()))?;
 
                 Ok(Self {
                     id: authorization.to_string(),
@@ -0,0 +1,333 @@
+//! Reading and writing tabular data as CSV or XLSX, used by the import and export endpoints.
+
+use std::io::{Cursor, Write};
+use std::str::FromStr;
+use calamine::Reader;
+use thiserror::Error;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::error::{Error, WebResult};
+
+#[derive(Debug, Error)]
+pub enum SpreadsheetError {
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+    #[error("{0}")]
+    Xlsx(#[from] calamine::XlsxError),
+    #[error("{0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("The spreadsheet contains no worksheet")]
+    NoWorksheet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFormat {
+    Csv,
+    Xlsx,
+}
+
+impl SheetFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+        }
+    }
+
+    /// Determine the format from the `Content-Type` of an upload
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        [Self::Csv, Self::Xlsx].into_iter()
+            .find(|x| content_type.split(';').next().map(str::trim).eq(&Some(x.content_type())))
+    }
+
+    /// Determine the format from the `format` query parameter, falling back to the `Content-Type` of the request
+    pub fn from_request(format: Option<&str>, content_type: Option<&str>) -> WebResult<Self> {
+        match (format, content_type) {
+            (Some(format), _) => Self::from_str(format).map_err(|_| Error::BadRequest(format!("Unknown format '{format}', expected 'csv' or 'xlsx'"))),
+            (None, Some(content_type)) => Self::from_content_type(content_type)
+                .ok_or_else(|| Error::BadRequest(format!("Unsupported content type '{content_type}'"))),
+            (None, None) => Err(Error::BadRequest("No format provided".to_string())),
+        }
+    }
+}
+
+impl FromStr for SheetFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "xlsx" => Ok(Self::Xlsx),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A cell of a sheet that is written
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<String> for Cell {
+    fn from(x: String) -> Self {
+        Self::Text(x)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(x: Option<T>) -> Self {
+        x.map(Into::into).unwrap_or(Self::Empty)
+    }
+}
+
+impl From<f32> for Cell {
+    fn from(x: f32) -> Self {
+        // Through the textual representation, so 0.1f32 is written as 0.1 rather than 0.10000000149011612
+        Self::Number(x.to_string().parse().unwrap_or_default())
+    }
+}
+
+/// A sheet with a header row.
+/// When reading, cells are returned in their textual representation and empty cells as empty strings
+#[derive(Debug, Clone, Default)]
+pub struct Sheet<C> {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<C>>,
+}
+
+/// Read the first worksheet of a spreadsheet. The first row is taken as the header row.
+/// Rows which are completely empty are skipped
+pub fn read(format: SheetFormat, data: &[u8]) -> Result<Sheet<String>, SpreadsheetError> {
+    let mut rows: Vec<Vec<String>> = match format {
+        SheetFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(data);
+
+            reader.records()
+                .map(|record| Ok(record?.iter().map(|x| unescape_formula(x.trim())).collect()))
+                .collect::<Result<_, csv::Error>>()?
+        },
+        SheetFormat::Xlsx => {
+            let mut workbook = calamine::Xlsx::new(Cursor::new(data))?;
+            let range = workbook.worksheet_range_at(0).ok_or(SpreadsheetError::NoWorksheet)??;
+
+            range.rows()
+                .map(|row| row.iter().map(|x| x.to_string().trim().to_string()).collect())
+                .collect()
+        }
+    };
+
+    rows.retain(|row| row.iter().any(|x| !x.is_empty()));
+    if rows.is_empty() {
+        return Ok(Sheet::default());
+    }
+
+    let headers = rows.remove(0);
+    Ok(Sheet {
+        headers,
+        rows,
+    })
+}
+
+/// Write a sheet, the header row first
+pub fn write(format: SheetFormat, sheet: &Sheet<Cell>) -> Result<Vec<u8>, SpreadsheetError> {
+    match format {
+        SheetFormat::Csv => write_csv(sheet),
+        SheetFormat::Xlsx => write_xlsx(sheet),
+    }
+}
+
+fn write_csv(sheet: &Sheet<Cell>) -> Result<Vec<u8>, SpreadsheetError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&sheet.headers)?;
+
+    for row in &sheet.rows {
+        writer.write_record(row.iter().map(|x| match x {
+            Cell::Text(x) => escape_formula(x),
+            Cell::Number(x) => x.to_string(),
+            Cell::Empty => String::new(),
+        }))?;
+    }
+
+    writer.into_inner().map_err(|e| SpreadsheetError::Io(e.into_error()))
+}
+
+/// Characters which make spreadsheet applications evaluate a CSV cell as a formula
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Prefix text which would be evaluated as a formula with a `'`, so that it is shown as text instead
+fn escape_formula(text: &str) -> String {
+    if text.starts_with(FORMULA_PREFIXES) {
+        format!("'{text}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Reverse [escape_formula], so that exported sheets can be imported again unchanged
+fn unescape_formula(text: &str) -> String {
+    match text.strip_prefix('\'') {
+        Some(x) if x.starts_with(FORMULA_PREFIXES) => x.to_string(),
+        _ => text.to_string(),
+    }
+}
+
+const XLSX_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const XLSX_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const XLSX_WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+
+const XLSX_WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+/// Write a minimal workbook with a single worksheet. Text is written as inline strings,
+/// so no shared string table is needed
+fn write_xlsx(sheet: &Sheet<Cell>) -> Result<Vec<u8>, SpreadsheetError> {
+    let mut worksheet = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#);
+
+    let header = sheet.headers.iter().cloned().map(Cell::Text).collect::<Vec<_>>();
+    for (row_idx, row) in std::iter::once(&header).chain(&sheet.rows).enumerate() {
+        let row_number = row_idx + 1;
+        worksheet.push_str(&format!(r#"<row r="{row_number}">"#));
+
+        for (column_idx, cell) in row.iter().enumerate() {
+            let reference = format!("{}{row_number}", column_name(column_idx));
+            match cell {
+                Cell::Text(x) => worksheet.push_str(&format!(r#"<c r="{reference}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#, escape_xml(x))),
+                Cell::Number(x) if x.is_finite() => worksheet.push_str(&format!(r#"<c r="{reference}"><v>{x}</v></c>"#)),
+                Cell::Number(_) | Cell::Empty => {},
+            }
+        }
+
+        worksheet.push_str("</row>");
+    }
+
+    worksheet.push_str("</sheetData></worksheet>");
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in [
+        ("[Content_Types].xml", XLSX_CONTENT_TYPES),
+        ("_rels/.rels", XLSX_RELS),
+        ("xl/workbook.xml", XLSX_WORKBOOK),
+        ("xl/_rels/workbook.xml.rels", XLSX_WORKBOOK_RELS),
+        ("xl/worksheets/sheet1.xml", &worksheet),
+    ] {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The name of a column in A1 notation, e.g. `A` for 0 and `AA` for 26
+fn column_name(mut idx: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (idx % 26) as u8);
+        if idx < 26 {
+            break;
+        }
+
+        idx = idx / 26 - 1;
+    }
+
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// Escape text for use in XML. Control characters are not allowed in XML 1.0 and are dropped
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {},
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sheet() -> Sheet<Cell> {
+        Sheet {
+            headers: vec!["name".to_string(), "price".to_string()],
+            rows: vec![
+                vec![Cell::Text("Widget & co".to_string()), Cell::from(1.5f32)],
+                vec![Cell::Text("Gadget".to_string()), Cell::Empty],
+            ],
+        }
+    }
+
+    #[test]
+    fn column_names() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(702), "AAA");
+    }
+
+    #[test]
+    fn round_trip() {
+        for format in [SheetFormat::Csv, SheetFormat::Xlsx] {
+            let written = write(format, &sheet()).unwrap();
+            let read = read(format, &written).unwrap();
+
+            assert_eq!(read.headers, vec!["name", "price"]);
+            assert_eq!(read.rows[0], vec!["Widget & co", "1.5"]);
+            assert_eq!(read.rows[1][0], "Gadget");
+        }
+    }
+
+    #[test]
+    fn csv_formulas_are_escaped() {
+        let sheet = Sheet {
+            headers: vec!["name".to_string()],
+            rows: vec![
+                vec![Cell::Text("=HYPERLINK(\"http://example.com\")".to_string())],
+                vec![Cell::Text("-1".to_string())],
+                vec![Cell::Text("a=b".to_string())],
+            ],
+        };
+
+        let written = write(SheetFormat::Csv, &sheet).unwrap();
+        let written_text = String::from_utf8(written.clone()).unwrap();
+        assert!(written_text.contains("'=HYPERLINK"));
+        assert!(written_text.contains("'-1"));
+        assert!(written_text.contains("\na=b"));
+
+        let read = read(SheetFormat::Csv, &written).unwrap();
+        assert_eq!(read.rows[0][0], "=HYPERLINK(\"http://example.com\")");
+        assert_eq!(read.rows[1][0], "-1");
+        assert_eq!(read.rows[2][0], "a=b");
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::storage::{Storage, StorageError, validate_key};
+
+/// Stores files in a directory on the local filesystem, with one file per key
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl Storage for LocalStorage {
+    /// Write the file next to its destination first and move it in place after,
+    /// so that a file is never read while it is partially written
+    async fn put(&self, key: &str, content: &[u8], _content_type: &str) -> Result<(), StorageError> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let temporary = path.with_extension(format!("{}-{}.tmp", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+        tokio::fs::write(&temporary, content).await?;
+        if let Err(e) = tokio::fs::rename(&temporary, &path).await {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(tokio::fs::try_exists(self.path(key)?).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[actix_web::test]
+    async fn round_trip() {
+        let root = std::env::temp_dir().join(format!("invoicex-storage-{}", std::process::id()));
+        let storage = LocalStorage::new(&root);
+
+        assert_eq!(storage.get("org/file").await.unwrap(), None);
+        assert!(!storage.exists("org/file").await.unwrap());
+
+        storage.put("org/file", b"first", "text/plain").await.unwrap();
+        storage.put("org/file", b"second", "text/plain").await.unwrap();
+        assert!(storage.exists("org/file").await.unwrap());
+        assert_eq!(storage.get("org/file").await.unwrap(), Some(b"second".to_vec()));
+
+        storage.delete("org/file").await.unwrap();
+        storage.delete("org/file").await.unwrap();
+        assert_eq!(storage.get("org/file").await.unwrap(), None);
+
+        assert!(matches!(storage.get("../file").await, Err(StorageError::InvalidKey(_))));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+//! Storage of uploaded files, such as the attachments of purchase invoices.
+//!
+//! Files are stored under a key, which consists of path segments separated by `/`.
+//! Keys may only contain ASCII alphanumerics, `-` and `_` within their segments,
+//! so that they map onto every backend without escaping.
+
+use std::future::Future;
+use thiserror::Error;
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("Request to storage backend failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Storage backend responded with status {status}: {body}")]
+    Backend {
+        status: u16,
+        body: String,
+    },
+    #[error("Invalid storage key '{0}'")]
+    InvalidKey(String),
+}
+
+/// A place files can be stored in and read back from
+pub trait Storage: Send + Sync {
+    /// Store a file, replacing any file with the same key
+    fn put(&self, key: &str, content: &[u8], content_type: &str) -> impl Future<Output = Result<(), StorageError>> + Send;
+
+    /// Read a file. Returns `None` if no file with the key exists
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>, StorageError>> + Send;
+
+    /// Whether a file with the key exists
+    fn exists(&self, key: &str) -> impl Future<Output = Result<bool, StorageError>> + Send;
+
+    /// Remove a file. Removing a file which does not exist is not an error
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), StorageError>> + Send;
+}
+
+/// The storage backend selected in the configuration
+#[derive(Debug, Clone)]
+pub enum StorageBackend {
+    Local(LocalStorage),
+    S3(S3Storage),
+}
+
+impl Storage for StorageBackend {
+    async fn put(&self, key: &str, content: &[u8], content_type: &str) -> Result<(), StorageError> {
+        match self {
+            Self::Local(x) => x.put(key, content, content_type).await,
+            Self::S3(x) => x.put(key, content, content_type).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match self {
+            Self::Local(x) => x.get(key).await,
+            Self::S3(x) => x.get(key).await,
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        match self {
+            Self::Local(x) => x.exists(key).await,
+            Self::S3(x) => x.exists(key).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match self {
+            Self::Local(x) => x.delete(key).await,
+            Self::S3(x) => x.delete(key).await,
+        }
+    }
+}
+
+/// Require that the key consists of non-empty segments of ASCII alphanumerics, `-` and `_`
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = key.split('/')
+        .all(|segment| !segment.is_empty() && segment.chars().all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        assert!(validate_key("org_1/abc-def").is_ok());
+        assert!(validate_key("../etc/passwd").is_err());
+        assert!(validate_key("org_1//abc").is_err());
+        assert!(validate_key("/abc").is_err());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("abc.txt").is_err());
+    }
+}
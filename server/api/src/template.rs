@@ -0,0 +1,340 @@
+//! A small Handlebars-like template language, used for invoice templates uploaded by organizations.
+//!
+//! Supported are `{{ path.to.value }}`, `{{#if path}} .. {{else}} .. {{/if}}`,
+//! `{{#each path}} .. {{/each}}` and `{{! comments }}`. Within `each`, `this` refers to the current
+//! item and `@index` to its zero-based index. Names are looked up in the innermost scope first.
+//!
+//! Templates are sandboxed: there are no partials, includes or helpers, so a template can only
+//! read the context it is rendered with. All output is HTML-escaped, and both the rendered size
+//! and the work done while rendering are limited.
+
+use std::borrow::Cow;
+use serde_json::Value;
+use thiserror::Error;
+
+/// The maximum nesting depth of blocks
+const MAX_DEPTH: usize = 32;
+/// The maximum size of rendered output, guarding against nested loops blowing up
+const MAX_OUTPUT_SIZE: usize = 8 * 1024 * 1024;
+/// The maximum number of nodes visited and loop iterations while rendering, guarding against nested loops which produce little output
+const MAX_RENDER_STEPS: usize = 1_000_000;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("Line {line}: {message}")]
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    #[error("Rendered output exceeds {MAX_OUTPUT_SIZE} bytes")]
+    OutputTooLarge,
+    #[error("Rendering takes more than {MAX_RENDER_STEPS} steps")]
+    TooManySteps,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(Vec<String>),
+    If {
+        path: Vec<String>,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        path: Vec<String>,
+        body: Vec<Node>,
+    },
+}
+
+/// A parsed template
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// A block which has been opened but not yet closed while parsing
+struct OpenBlock {
+    kind: &'static str,
+    path: Vec<String>,
+    line: usize,
+    nodes: Vec<Node>,
+    /// The nodes before `{{else}}`, once it has been encountered
+    then: Option<Vec<Node>>,
+}
+
+impl Template {
+    /// Parse a template, validating its syntax
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut nodes = Vec::new();
+        let mut open: Vec<OpenBlock> = Vec::new();
+        let mut rest = source;
+        let mut line = 1;
+
+        while !rest.is_empty() {
+            let Some(start) = rest.find("{{") else {
+                push(&mut open, &mut nodes, Node::Text(rest.to_string()));
+                break;
+            };
+
+            if start > 0 {
+                push(&mut open, &mut nodes, Node::Text(rest[..start].to_string()));
+                line += rest[..start].matches('\n').count();
+            }
+
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(syntax(line, "Unclosed '{{'"));
+            };
+
+            let tag = &rest[start + 2..start + end];
+            let tag_line = line;
+            line += tag.matches('\n').count();
+            rest = &rest[start + end + 2..];
+
+            let tag = tag.trim();
+            if tag.starts_with('!') {
+                continue;
+            }
+
+            if let Some(block) = tag.strip_prefix('#') {
+                let (kind, path) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+                let kind = match kind {
+                    "if" => "if",
+                    "each" => "each",
+                    _ => return Err(syntax(tag_line, &format!("Unknown block '{kind}'"))),
+                };
+
+                if open.len() >= MAX_DEPTH {
+                    return Err(syntax(tag_line, &format!("Blocks may be nested at most {MAX_DEPTH} levels deep")));
+                }
+
+                open.push(OpenBlock {
+                    kind,
+                    path: parse_path(tag_line, path)?,
+                    line: tag_line,
+                    nodes: Vec::new(),
+                    then: None,
+                });
+            } else if tag == "else" {
+                match open.last_mut() {
+                    Some(block) if block.kind == "if" && block.then.is_none() => {
+                        block.then = Some(std::mem::take(&mut block.nodes));
+                    },
+                    _ => return Err(syntax(tag_line, "'{{else}}' outside of an 'if' block")),
+                }
+            } else if let Some(kind) = tag.strip_prefix('/') {
+                let block = match open.pop() {
+                    Some(block) if block.kind == kind.trim() => block,
+                    Some(block) => return Err(syntax(tag_line, &format!("Expected '{{{{/{}}}}}', found '{{{{/{}}}}}'", block.kind, kind.trim()))),
+                    None => return Err(syntax(tag_line, &format!("'{{{{/{}}}}}' without an opening block", kind.trim()))),
+                };
+
+                let node = match block.then {
+                    Some(then) => Node::If { path: block.path, then, otherwise: block.nodes },
+                    None if block.kind == "if" => Node::If { path: block.path, then: block.nodes, otherwise: Vec::new() },
+                    None => Node::Each { path: block.path, body: block.nodes },
+                };
+
+                push(&mut open, &mut nodes, node);
+            } else {
+                let node = Node::Variable(parse_path(tag_line, tag)?);
+                push(&mut open, &mut nodes, node);
+            }
+        }
+
+        if let Some(block) = open.last() {
+            return Err(syntax(block.line, &format!("Unclosed '{}' block", block.kind)));
+        }
+
+        Ok(Self {
+            nodes,
+        })
+    }
+
+    /// Render the template with the provided context.
+    /// Rendering a large template may take a while, so async code should render on a blocking thread
+    pub fn render(&self, context: &Value) -> Result<String, TemplateError> {
+        let mut output = String::new();
+        let mut steps = 0;
+        render_nodes(&self.nodes, &mut vec![Scope { value: context, index: None }], &mut output, &mut steps)?;
+        Ok(output)
+    }
+}
+
+fn syntax(line: usize, message: &str) -> TemplateError {
+    TemplateError::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn push(open: &mut [OpenBlock], nodes: &mut Vec<Node>, node: Node) {
+    match open.last_mut() {
+        Some(block) => block.nodes.push(node),
+        None => nodes.push(node),
+    }
+}
+
+fn parse_path(line: usize, path: &str) -> Result<Vec<String>, TemplateError> {
+    let path = path.trim();
+    let valid = !path.is_empty() && path.split('.').all(|segment| {
+        let name = segment.strip_prefix('@').unwrap_or(segment);
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+
+    if !valid {
+        return Err(syntax(line, &format!("Invalid name '{path}'")));
+    }
+
+    Ok(path.split('.').map(str::to_string).collect())
+}
+
+/// A level of the context in which names are looked up
+struct Scope<'v> {
+    value: &'v Value,
+    /// The index of the item within the enclosing `each` block
+    index: Option<usize>,
+}
+
+fn lookup<'v>(scopes: &[Scope<'v>], path: &[String]) -> Option<Cow<'v, Value>> {
+    let (first, rest) = path.split_first()?;
+    let value = match first.as_str() {
+        "this" => scopes.last()?.value,
+        "@index" => return scopes.last()?.index.map(|x| Cow::Owned(Value::from(x))),
+        name => scopes.iter().rev().find_map(|scope| scope.value.get(name))?,
+    };
+
+    rest.iter()
+        .try_fold(value, |value, name| value.get(name))
+        .map(Cow::Borrowed)
+}
+
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(x)) => *x,
+        Some(Value::Number(x)) => x.as_f64().map(|x| x != 0.0).unwrap_or(true),
+        Some(Value::String(x)) => !x.is_empty(),
+        Some(Value::Array(x)) => !x.is_empty(),
+        Some(Value::Object(_)) => true,
+    }
+}
+
+/// Count a step of rendering, failing once the budget is used up
+fn step(steps: &mut usize) -> Result<(), TemplateError> {
+    *steps += 1;
+    if *steps > MAX_RENDER_STEPS {
+        return Err(TemplateError::TooManySteps);
+    }
+
+    Ok(())
+}
+
+fn render_nodes<'v>(nodes: &[Node], scopes: &mut Vec<Scope<'v>>, output: &mut String, steps: &mut usize) -> Result<(), TemplateError> {
+    for node in nodes {
+        step(steps)?;
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(path) => match lookup(scopes, path).as_deref() {
+                Some(Value::String(x)) => escape_html(x, output),
+                Some(Value::Number(x)) => output.push_str(&x.to_string()),
+                Some(Value::Bool(x)) => output.push_str(&x.to_string()),
+                // Objects and arrays have no sensible textual representation
+                _ => {},
+            },
+            Node::If { path, then, otherwise } => {
+                let branch = if truthy(lookup(scopes, path).as_deref()) { then } else { otherwise };
+                render_nodes(branch, scopes, output, steps)?;
+            },
+            Node::Each { path, body } => {
+                let Some(Cow::Borrowed(Value::Array(items))) = lookup(scopes, path) else {
+                    continue;
+                };
+
+                for (index, item) in items.iter().enumerate() {
+                    step(steps)?;
+                    scopes.push(Scope { value: item, index: Some(index) });
+                    let result = render_nodes(body, scopes, output, steps);
+                    scopes.pop();
+                    result?;
+                }
+            },
+        }
+
+        if output.len() > MAX_OUTPUT_SIZE {
+            return Err(TemplateError::OutputTooLarge);
+        }
+    }
+
+    Ok(())
+}
+
+fn escape_html(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn render() {
+        let template = Template::parse("\
+            <h1>{{ org.name }}</h1>{{! not rendered }}\n\
+            {{#each lines}}<p>{{@index}}: {{ description }} ({{ org.name }}) {{#if this.discount}}-{{ discount }}{{else}}full{{/if}}</p>{{/each}}\
+            {{#if notes}}{{ notes }}{{/if}}").unwrap();
+
+        let output = template.render(&json!({
+            "org": { "name": "Foo & Bar" },
+            "lines": [
+                { "description": "<b>Consultancy</b>", "discount": 10 },
+                { "description": "Support" },
+            ],
+            "notes": "",
+        })).unwrap();
+
+        assert_eq!(output, "<h1>Foo &amp; Bar</h1>\n\
+            <p>0: &lt;b&gt;Consultancy&lt;/b&gt; (Foo &amp; Bar) -10</p>\
+            <p>1: Support (Foo &amp; Bar) full</p>");
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |source: &str| match Template::parse(source) {
+            Err(TemplateError::Syntax { line, .. }) => line,
+            x => panic!("Expected a syntax error for {source:?}, got {x:?}"),
+        };
+
+        assert_eq!(error("{{ foo"), 1);
+        assert_eq!(error("\n\n{{#if foo}}"), 3);
+        assert_eq!(error("{{#each foo}}\n{{/if}}"), 2);
+        assert_eq!(error("{{#include \"/etc/passwd\"}}{{/include}}"), 1);
+        assert_eq!(error("{{ ../secret }}"), 1);
+        assert_eq!(error("{{else}}"), 1);
+    }
+
+    #[test]
+    fn output_limit() {
+        let items = Value::Array(vec![Value::Null; 100]);
+        let template = Template::parse(&format!("{}{}{}", "{{#each items}}".repeat(3), "x".repeat(1000), "{{/each}}".repeat(3))).unwrap();
+        assert_eq!(template.render(&json!({ "items": items })), Err(TemplateError::OutputTooLarge));
+    }
+
+    #[test]
+    fn step_limit() {
+        // Renders nothing, but would iterate 100^4 times
+        let items = Value::Array(vec![Value::Null; 100]);
+        let template = Template::parse(&format!("{}{}", "{{#each items}}".repeat(4), "{{/each}}".repeat(4))).unwrap();
+        assert_eq!(template.render(&json!({ "items": items })), Err(TemplateError::TooManySteps));
+    }
+}
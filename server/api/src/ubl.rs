@@ -0,0 +1,293 @@
+//! Reading supplier e-invoices in the UBL 2.1 format, e.g. Peppol BIS Billing 3.0.
+//! Only the fields needed to record a purchase invoice are read, the rest of the document is ignored.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum UblError {
+    #[error("Invalid XML: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("The document is not a UBL invoice")]
+    NotAnInvoice,
+    #[error("The invoice has no {0}")]
+    Missing(&'static str),
+    #[error("Invalid {field} '{value}'")]
+    Invalid {
+        field: &'static str,
+        value: String,
+    },
+}
+
+/// The fields of a UBL invoice needed to record it as a purchase invoice
+#[derive(Debug, Clone, PartialEq)]
+pub struct UblInvoice {
+    pub invoice_number: String,
+    /// UNIX timestamp of the start of the issue date, in UTC
+    pub issue_date: i64,
+    pub due_date: Option<i64>,
+    /// ISO 4217 currency code
+    pub currency: String,
+    /// The total amount without VAT
+    pub net_amount: f64,
+    /// The total VAT amount in the document currency
+    pub vat_amount: f64,
+    pub note: Option<String>,
+    pub supplier: UblParty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UblParty {
+    pub name: String,
+    pub vat_number: Option<String>,
+    pub email: Option<String>,
+    /// The postal address, one line per part
+    pub address: Option<String>,
+}
+
+/// The path of the supplier's party, relative to the root element
+const SUPPLIER_PARTY: [&str; 2] = ["AccountingSupplierParty", "Party"];
+
+/// Values read from the document, before they are validated
+#[derive(Debug, Default)]
+struct Fields {
+    id: Option<String>,
+    issue_date: Option<String>,
+    due_date: Option<String>,
+    currency: Option<String>,
+    note: Option<String>,
+    net_amount: Option<String>,
+    /// The amounts of all `TaxTotal`s with their currency. A second `TaxTotal` may be present in the tax currency
+    tax_amounts: Vec<(Option<String>, String)>,
+    party_name: Option<String>,
+    registration_name: Option<String>,
+    vat_number: Option<String>,
+    email: Option<String>,
+    address: Vec<String>,
+}
+
+impl Fields {
+    /// Store the text of the element at `path`, relative to the root element
+    fn set(&mut self, path: &[String], text: String, currency: Option<String>) {
+        let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+        let first = |field: &mut Option<String>| if field.is_none() {
+            *field = Some(text.clone());
+        };
+
+        match path.as_slice() {
+            ["ID"] => first(&mut self.id),
+            ["IssueDate"] => first(&mut self.issue_date),
+            ["DueDate"] | ["PaymentMeans", "PaymentDueDate"] => first(&mut self.due_date),
+            ["DocumentCurrencyCode"] => first(&mut self.currency),
+            ["Note"] => first(&mut self.note),
+            ["LegalMonetaryTotal", "TaxExclusiveAmount"] => first(&mut self.net_amount),
+            ["TaxTotal", "TaxAmount"] => self.tax_amounts.push((currency, text)),
+            [a, b, party @ ..] if [*a, *b] == SUPPLIER_PARTY => match party {
+                ["PartyName", "Name"] => first(&mut self.party_name),
+                ["PartyLegalEntity", "RegistrationName"] => first(&mut self.registration_name),
+                ["PartyTaxScheme", "CompanyID"] => first(&mut self.vat_number),
+                ["Contact", "ElectronicMail"] => first(&mut self.email),
+                ["PostalAddress", "StreetName" | "AdditionalStreetName" | "CityName" | "PostalZone"]
+                | ["PostalAddress", "Country", "IdentificationCode"] => self.address.push(text),
+                _ => {},
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Read a UBL 2.1 invoice. Credit notes are not supported
+pub fn parse(xml: &[u8]) -> Result<UblInvoice, UblError> {
+    let mut reader = Reader::from_reader(xml);
+    reader.trim_text(true);
+
+    let mut fields = Fields::default();
+    // The local names of the open elements below the root element
+    let mut path: Vec<String> = Vec::new();
+    let mut root_seen = false;
+    let mut text = String::new();
+    let mut currency = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) => {
+                if !root_seen {
+                    root_seen = true;
+                    if local_name(&element) != "Invoice" {
+                        return Err(UblError::NotAnInvoice);
+                    }
+                } else {
+                    path.push(local_name(&element));
+                }
+
+                text.clear();
+                currency = currency_id(&reader, &element)?;
+            },
+            Event::Empty(_) if !root_seen => return Err(UblError::NotAnInvoice),
+            Event::Text(value) => text.push_str(&value.unescape()?),
+            Event::CData(value) => text.push_str(&String::from_utf8_lossy(&value)),
+            Event::End(_) => {
+                if path.is_empty() {
+                    break;
+                }
+
+                if !text.is_empty() {
+                    fields.set(&path, std::mem::take(&mut text), currency.take());
+                }
+
+                path.pop();
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+
+        buf.clear();
+    }
+
+    if !root_seen {
+        return Err(UblError::NotAnInvoice);
+    }
+
+    let currency = fields.currency.ok_or(UblError::Missing("currency"))?.to_uppercase();
+
+    // Prefer the VAT total in the document currency
+    let vat_amount = fields.tax_amounts.iter()
+        .find(|(x, _)| x.as_deref().map(|x| x.eq_ignore_ascii_case(&currency)).unwrap_or(true))
+        .or(fields.tax_amounts.first())
+        .map(|(_, amount)| parse_amount("VAT amount", amount))
+        .transpose()?
+        .unwrap_or(0.0);
+
+    Ok(UblInvoice {
+        invoice_number: fields.id.ok_or(UblError::Missing("invoice number"))?,
+        issue_date: parse_date("issue date", &fields.issue_date.ok_or(UblError::Missing("issue date"))?)?,
+        due_date: fields.due_date.map(|x| parse_date("due date", &x)).transpose()?,
+        net_amount: parse_amount("net amount", &fields.net_amount.ok_or(UblError::Missing("net amount"))?)?,
+        vat_amount,
+        currency,
+        note: fields.note,
+        supplier: UblParty {
+            name: fields.party_name.or(fields.registration_name).ok_or(UblError::Missing("supplier name"))?,
+            vat_number: fields.vat_number,
+            email: fields.email,
+            address: (!fields.address.is_empty()).then(|| fields.address.join("\n")),
+        },
+    })
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).to_string()
+}
+
+fn currency_id(reader: &Reader<&[u8]>, element: &BytesStart) -> Result<Option<String>, UblError> {
+    Ok(match element.try_get_attribute("currencyID")? {
+        Some(attribute) => Some(attribute.decode_and_unescape_value(reader)?.to_string()),
+        None => None,
+    })
+}
+
+fn parse_amount(field: &'static str, value: &str) -> Result<f64, UblError> {
+    value.trim().parse::<f64>().ok()
+        .filter(|x| x.is_finite())
+        .ok_or_else(|| UblError::Invalid { field, value: value.to_string() })
+}
+
+/// Parse a `YYYY-MM-DD` date to the UNIX timestamp of its start, in UTC
+fn parse_date(field: &'static str, value: &str) -> Result<i64, UblError> {
+    let invalid = || UblError::Invalid { field, value: value.to_string() };
+
+    let mut parts = value.trim().splitn(3, '-');
+    let mut next = || parts.next().and_then(|x| x.parse::<i32>().ok()).ok_or_else(invalid);
+    let (year, month, day) = (next()?, next()?, next()?);
+
+    let month = u8::try_from(month).ok().and_then(|x| time::Month::try_from(x).ok()).ok_or_else(invalid)?;
+    let day = u8::try_from(day).map_err(|_| invalid())?;
+    let date = time::Date::from_calendar_date(year, month, day).map_err(|_| invalid())?;
+
+    Ok(date.midnight().assume_utc().unix_timestamp())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INVOICE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Invoice xmlns="urn:oasis:names:specification:ubl:schema:xsd:Invoice-2"
+    xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2"
+    xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2">
+  <cbc:CustomizationID>urn:cen.eu:en16931:2017#compliant#urn:fdc:peppol.eu:2017:poacc:billing:3.0</cbc:CustomizationID>
+  <cbc:ID>INV-2024-001</cbc:ID>
+  <cbc:IssueDate>2024-01-31</cbc:IssueDate>
+  <cbc:DueDate>2024-03-01</cbc:DueDate>
+  <cbc:Note>Office supplies &amp; paper</cbc:Note>
+  <cbc:DocumentCurrencyCode>EUR</cbc:DocumentCurrencyCode>
+  <cac:AccountingSupplierParty>
+    <cac:Party>
+      <cac:PartyName><cbc:Name>Paper Co</cbc:Name></cac:PartyName>
+      <cac:PostalAddress>
+        <cbc:StreetName>Main Street 1</cbc:StreetName>
+        <cbc:CityName>Amsterdam</cbc:CityName>
+        <cbc:PostalZone>1000 AA</cbc:PostalZone>
+        <cac:Country><cbc:IdentificationCode>NL</cbc:IdentificationCode></cac:Country>
+      </cac:PostalAddress>
+      <cac:PartyTaxScheme>
+        <cbc:CompanyID>NL123456789B01</cbc:CompanyID>
+        <cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme>
+      </cac:PartyTaxScheme>
+      <cac:PartyLegalEntity><cbc:RegistrationName>Paper Company B.V.</cbc:RegistrationName></cac:PartyLegalEntity>
+      <cac:Contact><cbc:ElectronicMail>billing@paper.example</cbc:ElectronicMail></cac:Contact>
+    </cac:Party>
+  </cac:AccountingSupplierParty>
+  <cac:AccountingCustomerParty>
+    <cac:Party><cac:PartyName><cbc:Name>Customer</cbc:Name></cac:PartyName></cac:Party>
+  </cac:AccountingCustomerParty>
+  <cac:TaxTotal>
+    <cbc:TaxAmount currencyID="USD">27.50</cbc:TaxAmount>
+  </cac:TaxTotal>
+  <cac:TaxTotal>
+    <cbc:TaxAmount currencyID="EUR">21.00</cbc:TaxAmount>
+    <cac:TaxSubtotal>
+      <cbc:TaxableAmount currencyID="EUR">100.00</cbc:TaxableAmount>
+      <cbc:TaxAmount currencyID="EUR">21.00</cbc:TaxAmount>
+    </cac:TaxSubtotal>
+  </cac:TaxTotal>
+  <cac:LegalMonetaryTotal>
+    <cbc:LineExtensionAmount currencyID="EUR">100.00</cbc:LineExtensionAmount>
+    <cbc:TaxExclusiveAmount currencyID="EUR">100.00</cbc:TaxExclusiveAmount>
+    <cbc:PayableAmount currencyID="EUR">121.00</cbc:PayableAmount>
+  </cac:LegalMonetaryTotal>
+</Invoice>"#;
+
+    #[test]
+    fn parse_invoice() {
+        let invoice = parse(INVOICE.as_bytes()).unwrap();
+        assert_eq!(invoice, UblInvoice {
+            invoice_number: "INV-2024-001".to_string(),
+            issue_date: 1706659200,
+            due_date: Some(1709251200),
+            currency: "EUR".to_string(),
+            net_amount: 100.0,
+            vat_amount: 21.0,
+            note: Some("Office supplies & paper".to_string()),
+            supplier: UblParty {
+                name: "Paper Co".to_string(),
+                vat_number: Some("NL123456789B01".to_string()),
+                email: Some("billing@paper.example".to_string()),
+                address: Some("Main Street 1\nAmsterdam\n1000 AA\nNL".to_string()),
+            },
+        });
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(matches!(parse(b"<CreditNote><ID>1</ID></CreditNote>"), Err(UblError::NotAnInvoice)));
+        assert!(matches!(parse(b""), Err(UblError::NotAnInvoice)));
+        assert!(matches!(parse(b"<Invoice><ID>1</ID></Invoice>"), Err(UblError::Missing(_))));
+        assert!(parse(b"<Invoice><ID>1</Invoice>").is_err());
+
+        let invalid_date = INVOICE.replace("2024-01-31", "2024-02-30");
+        assert!(matches!(parse(invalid_date.as_bytes()), Err(UblError::Invalid { field: "issue date", .. })));
+    }
+}
@@ -0,0 +1,33 @@
+CREATE TABLE suppliers (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    vat_number VARCHAR(32) DEFAULT NULL,
+    email VARCHAR(255) DEFAULT NULL,
+    address TEXT DEFAULT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX suppliers_org_id ON suppliers (org_id);
+
+-- Invoices received from suppliers
+CREATE TABLE purchase_invoices (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    supplier_id VARCHAR(32) NOT NULL,
+    -- The number the supplier gave the invoice
+    invoice_number VARCHAR(64) NOT NULL,
+    issue_date BIGINT NOT NULL,
+    due_date BIGINT DEFAULT NULL,
+    -- ISO 4217 currency code
+    currency VARCHAR(3) NOT NULL,
+    net_amount FLOAT NOT NULL,
+    vat_amount FLOAT NOT NULL,
+    -- The part of the VAT amount which can be deducted in the VAT return
+    deductible_vat_amount FLOAT NOT NULL,
+    description TEXT DEFAULT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX purchase_invoices_supplier_invoice_number ON purchase_invoices (org_id, supplier_id, invoice_number);
+CREATE INDEX purchase_invoices_org_id_issue_date ON purchase_invoices (org_id, issue_date);
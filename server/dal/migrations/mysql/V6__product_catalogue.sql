@@ -0,0 +1,16 @@
+-- UN/ECE Recommendation 20 unit code. C62 means 'one', a unit without dimension
+ALTER TABLE products ADD COLUMN unit VARCHAR(3) NOT NULL DEFAULT 'C62';
+ALTER TABLE products ADD COLUMN category_id VARCHAR(32) DEFAULT NULL;
+-- Archived products are hidden from listings, but kept so existing references stay valid
+ALTER TABLE products ADD COLUMN archived_at BIGINT DEFAULT NULL;
+
+ALTER TABLE products ADD CONSTRAINT products_org_id_product_code UNIQUE (org_id, product_code);
+CREATE INDEX products_org_id_name ON products (org_id, name);
+CREATE FULLTEXT INDEX products_search ON products (name, description, product_code);
+
+CREATE TABLE product_categories (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    UNIQUE (org_id, name)
+);
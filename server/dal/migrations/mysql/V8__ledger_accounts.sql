@@ -0,0 +1,14 @@
+-- Ledger accounts of an organization's bookkeeping, used when exporting journal entries
+CREATE TABLE ledger_accounts (
+    org_id VARCHAR(32) NOT NULL PRIMARY KEY,
+    debtor_account VARCHAR(32) DEFAULT NULL,
+    -- Used for revenue with a tax rate that has no account of its own
+    default_revenue_account VARCHAR(32) DEFAULT NULL
+);
+
+CREATE TABLE ledger_revenue_accounts (
+    org_id VARCHAR(32) NOT NULL,
+    tax_percentage FLOAT NOT NULL,
+    account VARCHAR(32) NOT NULL,
+    PRIMARY KEY (org_id, tax_percentage)
+);
@@ -0,0 +1,13 @@
+-- Reminder levels for overdue invoices. A level applies once an invoice
+-- is overdue by at least days_after_due days
+CREATE TABLE dunning_levels (
+    org_id VARCHAR(32) NOT NULL,
+    days_after_due INT NOT NULL,
+    email_subject VARCHAR(255) NOT NULL,
+    email_body TEXT NOT NULL,
+    -- Fixed fee charged when the level is reached
+    late_fee REAL DEFAULT NULL,
+    -- Yearly interest percentage charged over the overdue amount
+    interest_percentage REAL DEFAULT NULL,
+    PRIMARY KEY (org_id, days_after_due)
+);
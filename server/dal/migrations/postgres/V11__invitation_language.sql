@@ -0,0 +1,2 @@
+-- ISO 639-1 code of the language the invitation email is written in
+ALTER TABLE org_invitations ADD COLUMN language VARCHAR(8) NOT NULL DEFAULT 'en';
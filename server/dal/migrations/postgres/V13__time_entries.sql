@@ -0,0 +1,18 @@
+CREATE TABLE time_entries (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    user_id VARCHAR(32) NOT NULL,
+    project VARCHAR(255) NOT NULL,
+    -- UNIX timestamp of the start of the day the work was done, in UTC
+    work_date BIGINT NOT NULL,
+    duration_minutes INT NOT NULL,
+    description TEXT NOT NULL,
+    hourly_rate REAL NOT NULL,
+    -- UNIX timestamp at which the entry was turned into invoice lines. NULL while unbilled
+    billed_at BIGINT DEFAULT NULL,
+    -- Shared by all entries billed together
+    billing_id VARCHAR(32) DEFAULT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX time_entries_org_id_work_date ON time_entries (org_id, work_date);
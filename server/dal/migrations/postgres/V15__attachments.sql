@@ -0,0 +1,18 @@
+-- Files attached to entities of an organization. The content itself is kept in file storage,
+-- under a key derived from the organization and the SHA-256 hash of the content
+CREATE TABLE attachments (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    -- The type of entity the file is attached to, e.g. PurchaseInvoice
+    owner_type VARCHAR(32) NOT NULL,
+    owner_id VARCHAR(32) NOT NULL,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size BIGINT NOT NULL,
+    -- Hex encoded SHA-256 hash of the content. Attachments with the same content share the stored file
+    content_hash VARCHAR(64) NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX attachments_owner ON attachments (owner_type, owner_id);
+CREATE INDEX attachments_org_id_content_hash ON attachments (org_id, content_hash);
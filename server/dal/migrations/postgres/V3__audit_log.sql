@@ -0,0 +1,14 @@
+CREATE TABLE audit_log (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    actor_type VARCHAR(32) NOT NULL,
+    actor_id VARCHAR(64) NOT NULL,
+    entity_type VARCHAR(32) NOT NULL,
+    entity_id VARCHAR(64) NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    diff TEXT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX audit_log_org_id_created_at ON audit_log (org_id, created_at);
+CREATE INDEX audit_log_entity ON audit_log (entity_type, entity_id);
\ No newline at end of file
@@ -0,0 +1,22 @@
+-- A user can be granted more than one scope
+ALTER TABLE org_user_link_scopes DROP CONSTRAINT org_user_link_scopes_pkey, ADD PRIMARY KEY (org_id, user_id, scope_name);
+
+CREATE TABLE org_roles (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    UNIQUE (org_id, name)
+);
+
+CREATE TABLE org_role_scopes (
+    role_id VARCHAR(32) NOT NULL,
+    scope_name VARCHAR(64) NOT NULL,
+    PRIMARY KEY (role_id, scope_name)
+);
+
+CREATE TABLE org_user_roles (
+    org_id VARCHAR(32) NOT NULL,
+    user_id VARCHAR(32) NOT NULL,
+    role_id VARCHAR(32) NOT NULL,
+    PRIMARY KEY (org_id, user_id, role_id)
+);
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE price_lists (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    -- UNIX timestamps bounding the period in which the price list applies. NULL means unbounded
+    valid_from BIGINT DEFAULT NULL,
+    valid_until BIGINT DEFAULT NULL,
+    UNIQUE (org_id, name)
+);
+
+-- A product may have several prices in a price list, the price with the
+-- highest min_quantity not exceeding the ordered quantity applies
+CREATE TABLE price_list_prices (
+    price_list_id VARCHAR(32) NOT NULL,
+    product_id VARCHAR(32) NOT NULL,
+    min_quantity REAL NOT NULL DEFAULT 0.0,
+    price_per_unit REAL NOT NULL,
+    PRIMARY KEY (price_list_id, product_id, min_quantity)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE invoice_templates (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    -- At most one template per organization is the default
+    is_default BOOL NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE INDEX invoice_templates_org_id ON invoice_templates (org_id);
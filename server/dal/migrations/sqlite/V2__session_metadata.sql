@@ -0,0 +1,8 @@
+ALTER TABLE user_sessions ADD COLUMN created_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE user_sessions ADD COLUMN user_agent TEXT DEFAULT NULL;
+ALTER TABLE user_sessions ADD COLUMN ip_address VARCHAR(45) DEFAULT NULL;
+
+UPDATE user_sessions SET created_at = last_used;
+
+CREATE INDEX user_sessions_user_id ON user_sessions (user_id);
+CREATE INDEX user_sessions_expires_at ON user_sessions (expires_at);
\ No newline at end of file
@@ -0,0 +1,18 @@
+CREATE TABLE org_invitations (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    token VARCHAR(32) NOT NULL UNIQUE,
+    org_id VARCHAR(32) NOT NULL,
+    email VARCHAR(64) NOT NULL,
+    org_admin BOOL NOT NULL DEFAULT FALSE,
+    invited_by VARCHAR(32) NOT NULL,
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+CREATE INDEX org_invitations_org_id ON org_invitations (org_id);
+
+CREATE TABLE org_invitation_scopes (
+    invitation_id VARCHAR(32) NOT NULL,
+    scope_name VARCHAR(64) NOT NULL,
+    PRIMARY KEY (invitation_id, scope_name)
+);
\ No newline at end of file
@@ -0,0 +1,32 @@
+-- UN/ECE Recommendation 20 unit code. C62 means 'one', a unit without dimension
+ALTER TABLE products ADD COLUMN unit VARCHAR(3) NOT NULL DEFAULT 'C62';
+ALTER TABLE products ADD COLUMN category_id VARCHAR(32) DEFAULT NULL;
+-- Archived products are hidden from listings, but kept so existing references stay valid
+ALTER TABLE products ADD COLUMN archived_at BIGINT DEFAULT NULL;
+
+CREATE UNIQUE INDEX products_org_id_product_code ON products (org_id, product_code);
+CREATE INDEX products_org_id_name ON products (org_id, name);
+
+-- Full-text index over the products table, kept up to date by the triggers below
+CREATE VIRTUAL TABLE products_search USING fts5(name, description, product_code, content='products', content_rowid='rowid');
+INSERT INTO products_search (products_search) VALUES ('rebuild');
+
+CREATE TRIGGER products_search_insert AFTER INSERT ON products BEGIN
+    INSERT INTO products_search (rowid, name, description, product_code) VALUES (new.rowid, new.name, new.description, new.product_code);
+END;
+
+CREATE TRIGGER products_search_delete AFTER DELETE ON products BEGIN
+    INSERT INTO products_search (products_search, rowid, name, description, product_code) VALUES ('delete', old.rowid, old.name, old.description, old.product_code);
+END;
+
+CREATE TRIGGER products_search_update AFTER UPDATE ON products BEGIN
+    INSERT INTO products_search (products_search, rowid, name, description, product_code) VALUES ('delete', old.rowid, old.name, old.description, old.product_code);
+    INSERT INTO products_search (rowid, name, description, product_code) VALUES (new.rowid, new.name, new.description, new.product_code);
+END;
+
+CREATE TABLE product_categories (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    UNIQUE (org_id, name)
+);
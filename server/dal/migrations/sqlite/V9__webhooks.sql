@@ -0,0 +1,35 @@
+CREATE TABLE webhooks (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    org_id VARCHAR(32) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    -- Key of the HMAC-SHA256 signature sent with every delivery
+    secret VARCHAR(64) NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE TABLE webhook_event_types (
+    webhook_id VARCHAR(32) NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    PRIMARY KEY (webhook_id, event_type)
+);
+
+-- Outbox of events to deliver. A row is written for every subscribed webhook
+-- in the same transaction as the event, and is kept as the delivery log
+CREATE TABLE webhook_deliveries (
+    id VARCHAR(32) NOT NULL PRIMARY KEY,
+    webhook_id VARCHAR(32) NOT NULL,
+    event_id VARCHAR(32) NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    -- UNIX timestamp of the next delivery attempt. NULL once delivered or given up on
+    next_attempt_at BIGINT DEFAULT NULL,
+    delivered_at BIGINT DEFAULT NULL,
+    -- HTTP status of the last attempt, NULL if no response was received
+    last_status INT DEFAULT NULL,
+    last_error TEXT DEFAULT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX webhook_deliveries_next_attempt_at ON webhook_deliveries (next_attempt_at);
+CREATE INDEX webhook_deliveries_webhook_id_created_at ON webhook_deliveries (webhook_id, created_at);
@@ -0,0 +1,363 @@
+//! Abstraction over the supported database backends.
+//!
+//! Queries are written using named parameters (`:name`). Before a query is executed,
+//! the named parameters are rewritten to the placeholder syntax of the backend in use.
+
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::{DriverConfig, Error, Result};
+
+mod value;
+#[cfg(feature = "mysql")]
+mod mysql;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use value::{FromValue, Row, Value};
+
+/// The name of the table in which applied migrations are tracked
+const MIGRATION_TABLE: &str = "__invoicex_migrations";
+
+/// The database backends supported by the DAL.
+/// Which backends are available depends on the enabled crate features
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Mysql,
+    Postgres,
+    Sqlite,
+}
+
+/// A pool of connections to a database.
+/// Backends are synchronous, the [Driver] runs all work on the blocking thread pool of the runtime
+pub(crate) trait Pool: Send + Sync {
+    fn get_conn(&self) -> Result<Box<dyn Connection>>;
+
+    /// Apply all pending migrations for this backend
+    fn migrate(&self) -> Result<()>;
+}
+
+/// A single connection to a database
+pub(crate) trait Connection: Send {
+    /// Execute a query with positional parameters, returning the resulting rows
+    fn execute_positional(&mut self, query: &str, params: Vec<Value>) -> Result<Vec<Row>>;
+
+    /// Execute one or more statements without parameters
+    fn execute_batch(&mut self, query: &str) -> Result<()>;
+}
+
+type SharedConnection = Arc<Mutex<Box<dyn Connection>>>;
+
+/// Run blocking database work on the blocking thread pool, so it does not stall the async runtime
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await?
+}
+
+fn lock(conn: &SharedConnection) -> Result<MutexGuard<'_, Box<dyn Connection>>> {
+    conn.lock().map_err(|_| Error::InvalidState("Connection was poisoned by a panicking query".to_string()))
+}
+
+/// Handle to the database. Cloning the driver is cheap, clones share the same connection pool
+#[derive(Clone)]
+pub struct Driver {
+    backend: Backend,
+    pool: Arc<dyn Pool>,
+}
+
+impl Debug for Driver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Driver")
+            .field("backend", &self.backend)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Driver {
+    pub(crate) fn new(config: DriverConfig) -> Result<Self> {
+        let pool: Arc<dyn Pool> = match config.backend {
+            #[cfg(feature = "mysql")]
+            Backend::Mysql => Arc::new(mysql::MysqlPool::new(&config)?),
+            #[cfg(feature = "postgres")]
+            Backend::Postgres => Arc::new(postgres::PostgresPool::new(&config)?),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Arc::new(sqlite::SqlitePool::new(&config)?),
+            #[allow(unreachable_patterns)]
+            backend => return Err(Error::BackendDisabled(backend)),
+        };
+
+        Ok(Self {
+            backend: config.backend,
+            pool,
+        })
+    }
+
+    /// The backend this driver is connected to
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub async fn get_conn(&self) -> Result<Conn> {
+        let pool = self.pool.clone();
+        let inner = blocking(move || pool.get_conn()).await?;
+
+        Ok(Conn {
+            backend: self.backend,
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// Start a transaction. If the transaction is dropped without being committed, it is rolled back
+    pub async fn start_transaction(&self) -> Result<Transaction> {
+        let conn = self.get_conn().await?;
+        conn.batch(match self.backend {
+            // Take the write lock up front. A deferred transaction which later
+            // tries to write fails with SQLITE_BUSY if another connection wrote in the meantime
+            Backend::Sqlite => "BEGIN IMMEDIATE",
+            _ => "BEGIN",
+        }).await?;
+
+        Ok(Transaction {
+            conn,
+            finished: false,
+        })
+    }
+
+    pub(crate) async fn migrate(&self) -> Result<()> {
+        let pool = self.pool.clone();
+        blocking(move || pool.migrate()).await
+    }
+}
+
+/// Parameters for a query
+#[derive(Debug, Clone, Default)]
+pub enum Params {
+    #[default]
+    Empty,
+    Named(Vec<(String, Value)>),
+}
+
+impl From<Vec<(String, Value)>> for Params {
+    fn from(x: Vec<(String, Value)>) -> Self {
+        Self::Named(x)
+    }
+}
+
+/// Create [Params] from `name => value` pairs
+#[macro_export]
+macro_rules! params {
+    () => {
+        $crate::driver::Params::Empty
+    };
+    ($($name:expr => $value:expr),+ $(,)?) => {
+        $crate::driver::Params::from(vec![
+            $((::std::string::String::from($name), $crate::driver::Value::from($value))),+
+        ])
+    };
+}
+
+/// Methods to execute queries, implemented for connections and transactions
+pub trait Queryable {
+    /// Execute a query, returning all resulting rows
+    fn exec<Q: AsRef<str>, P: Into<Params>>(&mut self, query: Q, params: P) -> impl Future<Output = Result<Vec<Row>>> + Send;
+
+    /// Execute a query, returning the first resulting row
+    fn exec_first<Q: AsRef<str>, P: Into<Params>>(&mut self, query: Q, params: P) -> impl Future<Output = Result<Option<Row>>> + Send {
+        let rows = self.exec(query, params);
+        async move {
+            Ok(rows.await?.into_iter().next())
+        }
+    }
+
+    /// Execute a query, discarding the result
+    fn exec_drop<Q: AsRef<str>, P: Into<Params>>(&mut self, query: Q, params: P) -> impl Future<Output = Result<()>> + Send {
+        let rows = self.exec(query, params);
+        async move {
+            rows.await?;
+            Ok(())
+        }
+    }
+}
+
+/// A connection taken from the pool of a [Driver]
+pub struct Conn {
+    backend: Backend,
+    inner: SharedConnection,
+}
+
+impl Conn {
+    async fn batch(&self, query: &'static str) -> Result<()> {
+        let inner = self.inner.clone();
+        blocking(move || lock(&inner)?.execute_batch(query)).await
+    }
+}
+
+impl Queryable for Conn {
+    fn exec<Q: AsRef<str>, P: Into<Params>>(&mut self, query: Q, params: P) -> impl Future<Output = Result<Vec<Row>>> + Send {
+        let rewritten = rewrite_named_params(self.backend, query.as_ref(), params.into());
+        let inner = self.inner.clone();
+
+        async move {
+            let (query, params) = rewritten?;
+            blocking(move || lock(&inner)?.execute_positional(&query, params)).await
+        }
+    }
+}
+
+/// A database transaction, see [Driver::start_transaction]
+pub struct Transaction {
+    conn: Conn,
+    finished: bool,
+}
+
+impl Transaction {
+    pub async fn commit(mut self) -> Result<()> {
+        self.finished = true;
+        self.conn.batch("COMMIT").await
+    }
+
+    pub async fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.conn.batch("ROLLBACK").await
+    }
+}
+
+impl Queryable for Transaction {
+    fn exec<Q: AsRef<str>, P: Into<Params>>(&mut self, query: Q, params: P) -> impl Future<Output = Result<Vec<Row>>> + Send {
+        self.conn.exec(query, params)
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // The rollback holds on to the connection, so it is only returned to the pool once the rollback is done.
+        // Nothing sensible can be done if the rollback fails
+        let inner = self.conn.inner.clone();
+        let rollback = move || {
+            if let Ok(mut conn) = inner.lock() {
+                let _ = conn.execute_batch("ROLLBACK");
+            }
+        };
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(rollback);
+            },
+            Err(_) => rollback(),
+        }
+    }
+}
+
+/// Rewrite the named parameters (`:name`) in `query` to the placeholder syntax of `backend`.
+/// Returns the rewritten query and the parameter values in positional order.
+fn rewrite_named_params(backend: Backend, query: &str, params: Params) -> Result<(String, Vec<Value>)> {
+    let named = match params {
+        Params::Empty => Vec::new(),
+        Params::Named(x) => x,
+    };
+
+    let mut rewritten = String::with_capacity(query.len());
+    let mut positional = Vec::new();
+    // Postgres and SQLite placeholders are numbered, a parameter used more than once only needs to be bound once
+    let mut bound: Vec<&str> = Vec::new();
+
+    let mut in_literal = false;
+    let mut chars = query.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c == '\'' {
+            in_literal = !in_literal;
+        }
+
+        let starts_param = c == ':'
+            && !in_literal
+            && !query[..idx].ends_with(':')
+            && matches!(chars.peek(), Some((_, next)) if next.is_ascii_alphabetic() || *next == '_');
+
+        if !starts_param {
+            rewritten.push(c);
+            continue;
+        }
+
+        let start = idx + 1;
+        let mut end = start;
+        while let Some((next_idx, next)) = chars.peek() {
+            if !next.is_ascii_alphanumeric() && *next != '_' {
+                break;
+            }
+
+            end = next_idx + next.len_utf8();
+            chars.next();
+        }
+
+        let name = &query[start..end];
+        let value = named.iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| Error::MissingParameter(name.to_string()))?;
+
+        match backend {
+            Backend::Mysql => {
+                rewritten.push('?');
+                positional.push(value);
+            },
+            Backend::Postgres | Backend::Sqlite => {
+                let position = match bound.iter().position(|x| *x == name) {
+                    Some(x) => x + 1,
+                    None => {
+                        bound.push(name);
+                        positional.push(value);
+                        bound.len()
+                    }
+                };
+
+                let prefix = if backend == Backend::Postgres { '$' } else { '?' };
+                rewritten.push_str(&format!("{prefix}{position}"));
+            }
+        }
+    }
+
+    Ok((rewritten, positional))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rewrite_mysql() {
+        let (query, params) = rewrite_named_params(Backend::Mysql, "SELECT a FROM b WHERE c = :c AND d = :d OR c = :c", crate::params! {
+            "c" => 1i64,
+            "d" => "d",
+        }).unwrap();
+
+        assert_eq!(query, "SELECT a FROM b WHERE c = ? AND d = ? OR c = ?");
+        assert_eq!(params, vec![Value::Int(1), Value::Text("d".to_string()), Value::Int(1)]);
+    }
+
+    #[test]
+    fn rewrite_postgres() {
+        let (query, params) = rewrite_named_params(Backend::Postgres, "SELECT a::TEXT, ':e' FROM b WHERE c = :c AND d = :d OR c = :c", crate::params! {
+            "c" => 1i64,
+            "d" => "d",
+        }).unwrap();
+
+        assert_eq!(query, "SELECT a::TEXT, ':e' FROM b WHERE c = $1 AND d = $2 OR c = $1");
+        assert_eq!(params, vec![Value::Int(1), Value::Text("d".to_string())]);
+    }
+
+    #[test]
+    fn rewrite_missing_param() {
+        let result = rewrite_named_params(Backend::Postgres, "SELECT a FROM b WHERE c = :c", Params::Empty);
+        assert!(matches!(result, Err(Error::MissingParameter(x)) if x == "c"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,95 @@
+use mysql::{OptsBuilder, PooledConn};
+use mysql::prelude::Queryable;
+use crate::driver::{Connection, MIGRATION_TABLE, Pool, Row, Value};
+use crate::{DriverConfig, Result};
+
+mod migrations {
+    use refinery::embed_migrations;
+    embed_migrations!("./migrations/mysql/");
+}
+
+pub(crate) struct MysqlPool(mysql::Pool);
+
+impl MysqlPool {
+    pub(crate) fn new(config: &DriverConfig) -> Result<Self> {
+        let opts = OptsBuilder::new()
+            .ip_or_hostname(Some(&config.host))
+            .db_name(Some(&config.database))
+            .user(Some(&config.username))
+            .pass(Some(&config.password));
+        let pool = mysql::Pool::new(opts)?;
+        Ok(Self(pool))
+    }
+}
+
+impl Pool for MysqlPool {
+    fn get_conn(&self) -> Result<Box<dyn Connection>> {
+        Ok(Box::new(MysqlConnection(self.0.get_conn()?)))
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let mut conn = self.0.get_conn()?;
+        migrations::migrations::runner()
+            .set_migration_table_name(MIGRATION_TABLE)
+            .run(&mut conn)?;
+
+        Ok(())
+    }
+}
+
+struct MysqlConnection(PooledConn);
+
+impl Connection for MysqlConnection {
+    fn execute_positional(&mut self, query: &str, params: Vec<Value>) -> Result<Vec<Row>> {
+        let params = if params.is_empty() {
+            mysql::Params::Empty
+        } else {
+            mysql::Params::Positional(params.into_iter().map(to_mysql_value).collect())
+        };
+
+        let rows: Vec<mysql::Row> = self.0.exec(query, params)?;
+        Ok(rows.into_iter().map(from_mysql_row).collect())
+    }
+
+    fn execute_batch(&mut self, query: &str) -> Result<()> {
+        self.0.query_drop(query)?;
+        Ok(())
+    }
+}
+
+fn to_mysql_value(value: Value) -> mysql::Value {
+    match value {
+        Value::Null => mysql::Value::NULL,
+        Value::Bool(x) => mysql::Value::Int(x.into()),
+        Value::Int(x) => mysql::Value::Int(x),
+        Value::Float(x) => mysql::Value::Double(x),
+        Value::Text(x) => mysql::Value::Bytes(x.into_bytes()),
+        Value::Bytes(x) => mysql::Value::Bytes(x),
+    }
+}
+
+fn from_mysql_row(row: mysql::Row) -> Row {
+    let columns = row.columns_ref()
+        .iter()
+        .map(|x| x.name_str().to_string())
+        .collect();
+    let values = row.unwrap()
+        .into_iter()
+        .map(from_mysql_value)
+        .collect();
+
+    Row::new(columns, values)
+}
+
+fn from_mysql_value(value: mysql::Value) -> Value {
+    match value {
+        mysql::Value::NULL => Value::Null,
+        mysql::Value::Bytes(x) => Value::Bytes(x),
+        mysql::Value::Int(x) => Value::Int(x),
+        mysql::Value::UInt(x) => i64::try_from(x).map(Value::Int).unwrap_or_else(|_| Value::Text(x.to_string())),
+        mysql::Value::Float(x) => Value::Float(x.into()),
+        mysql::Value::Double(x) => Value::Float(x),
+        // Dates and times are not used by the DAL, but are passed on in their textual representation
+        x => Value::Text(x.as_sql(true).trim_matches('\'').to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,123 @@
+use std::error::Error;
+use bytes::BytesMut;
+use postgres::NoTls;
+use postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+use r2d2_postgres::PostgresConnectionManager;
+use crate::driver::{Connection, MIGRATION_TABLE, Pool, Row, Value};
+use crate::{DriverConfig, Result};
+
+mod migrations {
+    use refinery::embed_migrations;
+    embed_migrations!("./migrations/postgres/");
+}
+
+type Manager = PostgresConnectionManager<NoTls>;
+
+pub(crate) struct PostgresPool(r2d2::Pool<Manager>);
+
+impl PostgresPool {
+    pub(crate) fn new(config: &DriverConfig) -> Result<Self> {
+        let mut pg_config = postgres::Config::new();
+        pg_config
+            .host(&config.host)
+            .dbname(&config.database)
+            .user(&config.username)
+            .password(&config.password);
+
+        let pool = r2d2::Pool::new(Manager::new(pg_config, NoTls))?;
+        Ok(Self(pool))
+    }
+}
+
+impl Pool for PostgresPool {
+    fn get_conn(&self) -> Result<Box<dyn Connection>> {
+        Ok(Box::new(PostgresConnection(self.0.get()?)))
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let mut conn = self.0.get()?;
+        migrations::migrations::runner()
+            .set_migration_table_name(MIGRATION_TABLE)
+            .run(&mut *conn)?;
+
+        Ok(())
+    }
+}
+
+struct PostgresConnection(r2d2::PooledConnection<Manager>);
+
+impl Connection for PostgresConnection {
+    fn execute_positional(&mut self, query: &str, params: Vec<Value>) -> Result<Vec<Row>> {
+        let params = params.iter()
+            .map(|x| x as &(dyn ToSql + Sync))
+            .collect::<Vec<_>>();
+
+        let rows = self.0.query(query, &params)?;
+        rows.iter()
+            .map(from_postgres_row)
+            .collect()
+    }
+
+    fn execute_batch(&mut self, query: &str) -> Result<()> {
+        self.0.batch_execute(query)?;
+        Ok(())
+    }
+}
+
+fn from_postgres_row(row: &postgres::Row) -> Result<Row> {
+    let columns = row.columns()
+        .iter()
+        .map(|x| x.name().to_string())
+        .collect();
+
+    let values = row.columns()
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let value = match *column.type_() {
+                Type::BOOL => row.try_get::<_, Option<bool>>(idx)?.map(Value::Bool),
+                Type::INT2 => row.try_get::<_, Option<i16>>(idx)?.map(|x| Value::Int(x.into())),
+                Type::INT4 => row.try_get::<_, Option<i32>>(idx)?.map(|x| Value::Int(x.into())),
+                Type::INT8 => row.try_get::<_, Option<i64>>(idx)?.map(Value::Int),
+                Type::FLOAT4 => row.try_get::<_, Option<f32>>(idx)?.map(|x| Value::Float(x.into())),
+                Type::FLOAT8 => row.try_get::<_, Option<f64>>(idx)?.map(Value::Float),
+                Type::BYTEA => row.try_get::<_, Option<Vec<u8>>>(idx)?.map(Value::Bytes),
+                _ => row.try_get::<_, Option<String>>(idx)?.map(Value::Text),
+            };
+
+            Ok(value.unwrap_or(Value::Null))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Row::new(columns, values))
+}
+
+impl ToSql for Value {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // Integers are always passed as i64, convert them to the width of the column
+        match self {
+            Value::Null => Ok(IsNull::Yes),
+            Value::Bool(x) => x.to_sql(ty, out),
+            Value::Int(x) => match *ty {
+                Type::BOOL => (*x != 0).to_sql(ty, out),
+                Type::INT2 => i16::try_from(*x)?.to_sql(ty, out),
+                Type::INT4 => i32::try_from(*x)?.to_sql(ty, out),
+                Type::FLOAT4 => (*x as f32).to_sql(ty, out),
+                Type::FLOAT8 => (*x as f64).to_sql(ty, out),
+                _ => x.to_sql(ty, out),
+            },
+            Value::Float(x) => match *ty {
+                Type::FLOAT4 => (*x as f32).to_sql(ty, out),
+                _ => x.to_sql(ty, out),
+            },
+            Value::Text(x) => x.as_str().to_sql(ty, out),
+            Value::Bytes(x) => x.as_slice().to_sql(ty, out),
+        }
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
\ No newline at end of file
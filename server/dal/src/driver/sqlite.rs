@@ -0,0 +1,163 @@
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::types::{ToSqlOutput, ValueRef};
+use rusqlite::ToSql;
+use crate::driver::{Connection, MIGRATION_TABLE, Pool, Row, Value};
+use crate::{DriverConfig, Result};
+
+mod migrations {
+    use refinery::embed_migrations;
+    embed_migrations!("./migrations/sqlite/");
+}
+
+/// Path which opens an in-memory database rather than a file
+const IN_MEMORY: &str = ":memory:";
+
+pub(crate) struct SqlitePool(r2d2::Pool<SqliteConnectionManager>);
+
+impl SqlitePool {
+    /// Open the database file at `config.database`, creating it if it does not exist.
+    /// The host and credentials in the config are ignored.
+    pub(crate) fn new(config: &DriverConfig) -> Result<Self> {
+        let pool = if config.database.eq(IN_MEMORY) {
+            // Every in-memory connection is a separate database, so only one may exist
+            r2d2::Pool::builder()
+                .max_size(1)
+                .build(SqliteConnectionManager::memory().with_init(init_connection))?
+        } else {
+            r2d2::Pool::new(SqliteConnectionManager::file(&config.database).with_init(init_connection))?
+        };
+
+        Ok(Self(pool))
+    }
+}
+
+fn init_connection(conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+    // WAL allows readers to continue while a write is in progress.
+    // Writers still take turns, wait for the lock rather than failing immediately
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;")
+}
+
+impl Pool for SqlitePool {
+    fn get_conn(&self) -> Result<Box<dyn Connection>> {
+        Ok(Box::new(SqliteConnection(self.0.get()?)))
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let mut conn = self.0.get()?;
+        migrations::migrations::runner()
+            .set_migration_table_name(MIGRATION_TABLE)
+            .run(&mut *conn)?;
+
+        Ok(())
+    }
+}
+
+struct SqliteConnection(r2d2::PooledConnection<SqliteConnectionManager>);
+
+impl Connection for SqliteConnection {
+    fn execute_positional(&mut self, query: &str, params: Vec<Value>) -> Result<Vec<Row>> {
+        let mut stmt = self.0.prepare(query)?;
+        let columns = stmt.column_names()
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        let mut result = Vec::new();
+        let mut rows = stmt.query(params.iter())?;
+        while let Some(row) = rows.next()? {
+            let values = (0..columns.len())
+                .map(|idx| Ok(from_sqlite_value(row.get_raw_checked(idx)?)))
+                .collect::<Result<Vec<_>>>()?;
+            result.push(Row::new(columns.clone(), values));
+        }
+
+        Ok(result)
+    }
+
+    fn execute_batch(&mut self, query: &str) -> Result<()> {
+        self.0.execute_batch(query)?;
+        Ok(())
+    }
+}
+
+fn from_sqlite_value(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(x) => Value::Int(x),
+        ValueRef::Real(x) => Value::Float(x),
+        ValueRef::Text(x) => Value::Text(String::from_utf8_lossy(x).into_owned()),
+        ValueRef::Blob(x) => Value::Bytes(x.to_vec()),
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            Value::Null => ToSqlOutput::Borrowed(ValueRef::Null),
+            Value::Bool(x) => ToSqlOutput::Borrowed(ValueRef::Integer((*x).into())),
+            Value::Int(x) => ToSqlOutput::Borrowed(ValueRef::Integer(*x)),
+            Value::Float(x) => ToSqlOutput::Borrowed(ValueRef::Real(*x)),
+            Value::Text(x) => ToSqlOutput::Borrowed(ValueRef::Text(x.as_bytes())),
+            Value::Bytes(x) => ToSqlOutput::Borrowed(ValueRef::Blob(x)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::driver::{Backend, Queryable};
+    use crate::{Driver, DriverConfig};
+    use crate::params;
+
+    fn memory_driver() -> Driver {
+        Driver::new(DriverConfig {
+            backend: Backend::Sqlite,
+            host: String::default(),
+            database: super::IN_MEMORY.to_string(),
+            username: String::default(),
+            password: String::default(),
+        }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let driver = memory_driver();
+        let mut conn = driver.get_conn().await.unwrap();
+        conn.exec_drop("CREATE TABLE t (id VARCHAR(32) NOT NULL PRIMARY KEY, flag BOOL NOT NULL, note TEXT DEFAULT NULL, amount REAL NOT NULL)", params!()).await.unwrap();
+        conn.exec_drop("INSERT INTO t (id, flag, note, amount) VALUES (:id, :flag, :note, :amount)", params! {
+            "id" => "a",
+            "flag" => true,
+            "note" => Option::<String>::None,
+            "amount" => 1.5f32,
+        }).await.unwrap();
+
+        let row = conn.exec_first("SELECT id,flag,note,amount FROM t WHERE id = :id AND flag = :flag", params! {
+            "id" => "a",
+            "flag" => true,
+        }).await.unwrap().unwrap();
+
+        assert_eq!(row.get::<String, _>("id").unwrap(), "a");
+        assert!(row.get::<bool, _>("flag").unwrap());
+        assert_eq!(row.get::<Option<String>, _>("note").unwrap(), None);
+        assert_eq!(row.get::<f32, _>("amount").unwrap(), 1.5);
+    }
+
+    #[tokio::test]
+    async fn transaction_rolls_back_on_drop() {
+        let driver = memory_driver();
+        driver.get_conn().await.unwrap().exec_drop("CREATE TABLE t (id VARCHAR(32) NOT NULL PRIMARY KEY)", params!()).await.unwrap();
+
+        {
+            let mut tx = driver.start_transaction().await.unwrap();
+            tx.exec_drop("INSERT INTO t (id) VALUES (:id)", params! { "id" => "a" }).await.unwrap();
+        }
+
+        let mut tx = driver.start_transaction().await.unwrap();
+        tx.exec_drop("INSERT INTO t (id) VALUES (:id)", params! { "id" => "b" }).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let rows = driver.get_conn().await.unwrap().exec("SELECT id FROM t", params!()).await.unwrap();
+        let ids = rows.iter().map(|x| x.get::<String, _>("id").unwrap()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["b".to_string()]);
+    }
+}
\ No newline at end of file
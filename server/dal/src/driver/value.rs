@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+/// A value sent to or received from the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Self {
+        Self::Bool(x)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(x: i64) -> Self {
+        Self::Int(x)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(x: i32) -> Self {
+        Self::Int(x.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(x: u32) -> Self {
+        Self::Int(x.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Self::Float(x)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(x: f32) -> Self {
+        Self::Float(x.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Self::Text(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Self::Text(x.to_string())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(x: Vec<u8>) -> Self {
+        Self::Bytes(x)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(x: Option<T>) -> Self {
+        match x {
+            Some(x) => x.into(),
+            None => Self::Null,
+        }
+    }
+}
+
+impl<T: Into<Value> + Clone> From<&T> for Value {
+    fn from(x: &T) -> Self {
+        x.clone().into()
+    }
+}
+
+/// Conversion from a database [Value]
+pub trait FromValue: Sized {
+    /// Convert the value, returns `None` if the value cannot be represented as `Self`
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Text(x) => Some(x),
+            Value::Bytes(x) => String::from_utf8(x).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bytes(x) => Some(x),
+            Value::Text(x) => Some(x.into_bytes()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Int(x) => Some(x),
+            Value::Bool(x) => Some(x.into()),
+            // Some backends return numbers in their textual representation
+            Value::Text(_) | Value::Bytes(_) => String::from_value(value)?.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for i32 {
+    fn from_value(value: Value) -> Option<Self> {
+        i64::from_value(value)?.try_into().ok()
+    }
+}
+
+impl FromValue for u32 {
+    fn from_value(value: Value) -> Option<Self> {
+        i64::from_value(value)?.try_into().ok()
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(x) => Some(x),
+            // MySQL represents booleans as TINYINT
+            x => i64::from_value(x).map(|x| x != 0),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Float(x) => Some(x),
+            Value::Int(x) => Some(x as f64),
+            Value::Text(_) | Value::Bytes(_) => String::from_value(value)?.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Option<Self> {
+        f64::from_value(value).map(|x| x as f32)
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            x => T::from_value(x).map(Some),
+        }
+    }
+}
+
+/// A row returned by a query
+#[derive(Debug, Clone)]
+pub struct Row {
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl Row {
+    pub(crate) fn new(columns: Vec<String>, values: Vec<Value>) -> Self {
+        Self {
+            columns,
+            values,
+        }
+    }
+
+    /// Get the value of a column.
+    /// Returns `None` if the column does not exist, or if its value cannot be converted to `T`
+    pub fn get<T: FromValue, I: AsRef<str>>(&self, column: I) -> Option<T> {
+        let idx = self.columns.iter().position(|x| x.eq(column.as_ref()))?;
+        T::from_value(self.values[idx].clone())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,244 @@
+use std::str::FromStr;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, Error, gen_id};
+use crate::entities::{Entity, Org};
+use proc::{Stringify, Variants};
+
+const COLUMNS: &str = "id,org_id,owner_type,owner_id,file_name,content_type,size,content_hash,created_at";
+
+/// A file attached to an entity of an organization.
+/// The DAL only stores the metadata, the content is kept in file storage by the caller
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub owner_type: AttachmentOwnerType,
+    pub owner_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    /// The size of the content in bytes
+    pub size: i64,
+    /// Hex encoded SHA-256 hash of the content. Attachments of an organization with the same content share the stored file
+    pub content_hash: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachmentBuilder<'a> {
+    pub org: &'a Org,
+    pub owner_type: AttachmentOwnerType,
+    pub owner_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub content_hash: String,
+}
+
+/// Exclusive access to the stored content of the attachments of an organization, see [Attachment::lock_content].
+/// Released when committed or dropped
+pub struct ContentLock {
+    driver: Driver,
+    tx: Transaction,
+    org_id: String,
+}
+
+/// The types of entities files can be attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Stringify, Variants)]
+pub enum AttachmentOwnerType {
+    PurchaseInvoice,
+    Supplier,
+}
+
+impl Entity for Attachment {
+    type Information<'a> = AttachmentBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut conn = driver.get_conn().await?;
+        Self::insert(&mut conn, driver, builder).await
+    }
+
+    /// Remove the attachment. Whether its content is still used by other attachments can be checked
+    /// with [ContentLock::is_content_referenced]
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the attachment. Only the file name can be changed
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut conn = self.driver.get_conn().await?;
+        conn.exec_drop("UPDATE attachments SET file_name = :file_name WHERE id = :id", params! {
+            "file_name" => &self.file_name,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM attachments WHERE id = :id"), params! {
+            "id" => &id
+        }).await?;
+
+        row.map(|row| Self::from_row(driver, row)).transpose()
+    }
+}
+
+impl Attachment {
+    /// Remove the attachment using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM attachments WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    fn from_row(driver: &Driver, row: Row) -> crate::Result<Self> {
+        Ok(Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            owner_type: AttachmentOwnerType::from_str(&row.get::<String, _>("owner_type").unwrap())
+                .map_err(|_| Error::UnknownEnumVariant)?,
+            owner_id: row.get("owner_id").unwrap(),
+            file_name: row.get("file_name").unwrap(),
+            content_type: row.get("content_type").unwrap(),
+            size: row.get("size").unwrap(),
+            content_hash: row.get("content_hash").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+        })
+    }
+
+    async fn insert(conn: &mut impl Queryable, driver: &Driver, builder: AttachmentBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        conn.exec_drop("INSERT INTO attachments (id, org_id, owner_type, owner_id, file_name, content_type, size, content_hash, created_at) \
+            VALUES (:id, :org_id, :owner_type, :owner_id, :file_name, :content_type, :size, :content_hash, :created_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "owner_type" => builder.owner_type.to_string(),
+            "owner_id" => &builder.owner_id,
+            "file_name" => &builder.file_name,
+            "content_type" => &builder.content_type,
+            "size" => builder.size,
+            "content_hash" => &builder.content_hash,
+            "created_at" => created_at,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            owner_type: builder.owner_type,
+            owner_id: builder.owner_id,
+            file_name: builder.file_name,
+            content_type: builder.content_type,
+            size: builder.size,
+            content_hash: builder.content_hash,
+            created_at,
+        })
+    }
+
+    async fn list(conn: &mut impl Queryable, driver: &Driver, owner_type: AttachmentOwnerType, owner_id: &str) -> crate::Result<Vec<Self>> {
+        let rows: Vec<Row> = conn.exec(format!("SELECT {COLUMNS} FROM attachments WHERE owner_type = :owner_type AND owner_id = :owner_id ORDER BY created_at, id"), params! {
+            "owner_type" => owner_type.to_string(),
+            "owner_id" => owner_id,
+        }).await?;
+
+        rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect()
+    }
+
+    /// List the attachments of an entity, oldest first
+    pub async fn list_for_owner(driver: &Driver, owner_type: AttachmentOwnerType, owner_id: &str) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        Self::list(&mut conn, driver, owner_type, owner_id).await
+    }
+
+    /// Remove all attachments of an entity, returning them.
+    /// Used when the entity itself is removed
+    pub async fn remove_for_owner(driver: &Driver, owner_type: AttachmentOwnerType, owner_id: &str) -> crate::Result<Vec<Self>> {
+        let mut tx = driver.start_transaction().await?;
+        let attachments = Self::remove_for_owner_with_tx(&mut tx, driver, owner_type, owner_id).await?;
+        tx.commit().await?;
+        Ok(attachments)
+    }
+
+    /// Remove all attachments of an entity using the provided transaction, returning them
+    pub async fn remove_for_owner_with_tx(tx: &mut Transaction, driver: &Driver, owner_type: AttachmentOwnerType, owner_id: &str) -> crate::Result<Vec<Self>> {
+        let attachments = Self::list(tx, driver, owner_type, owner_id).await?;
+        tx.exec_drop("DELETE FROM attachments WHERE owner_type = :owner_type AND owner_id = :owner_id", params! {
+            "owner_type" => owner_type.to_string(),
+            "owner_id" => owner_id,
+        }).await?;
+
+        Ok(attachments)
+    }
+
+    async fn query_content_referenced(conn: &mut impl Queryable, org_id: &str, content_hash: &str) -> crate::Result<bool> {
+        let row: Option<Row> = conn.exec_first("SELECT id FROM attachments WHERE org_id = :org_id AND content_hash = :content_hash LIMIT 1", params! {
+            "org_id" => org_id,
+            "content_hash" => content_hash,
+        }).await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Lock the stored content of the attachments of the organization. Attachments should only be recorded, and content
+    /// only removed, while holding the lock, so that content is never removed after an attachment using it was recorded.
+    /// The lock is a write transaction, so it should not be held during slow operations such as storing content
+    pub async fn lock_content(driver: &Driver, org_id: &str) -> crate::Result<ContentLock> {
+        let mut tx = driver.start_transaction().await?;
+        // Locks the row of the organization until the transaction ends
+        tx.exec_drop("UPDATE orgs SET id = id WHERE id = :org_id", params! {
+            "org_id" => org_id
+        }).await?;
+
+        Ok(ContentLock {
+            driver: driver.clone(),
+            tx,
+            org_id: org_id.to_string(),
+        })
+    }
+
+    /// The distinct content hashes of all attachments of the organization
+    pub async fn list_content_hashes(driver: &Driver, org: &Org) -> crate::Result<Vec<String>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT DISTINCT content_hash FROM attachments WHERE org_id = :org_id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| row.get("content_hash").unwrap())
+            .collect())
+    }
+}
+
+impl ContentLock {
+    /// Record an attachment of the organization. Other connections only see it once the lock is committed
+    pub async fn create(&mut self, builder: AttachmentBuilder<'_>) -> crate::Result<Attachment> {
+        Attachment::insert(&mut self.tx, &self.driver, builder).await
+    }
+
+    /// Whether any attachment of the organization has content with the provided hash
+    pub async fn is_content_referenced(&mut self, content_hash: &str) -> crate::Result<bool> {
+        Attachment::query_content_referenced(&mut self.tx, &self.org_id, content_hash).await
+    }
+
+    /// The transaction holding the lock, for changes which should be committed together with the recorded attachments
+    pub fn transaction(&mut self) -> &mut Transaction {
+        &mut self.tx
+    }
+
+    pub async fn commit(self) -> crate::Result<()> {
+        self.tx.commit().await
+    }
+}
@@ -0,0 +1,309 @@
+use std::str::FromStr;
+use crate::driver::{Params, Queryable, Row, Transaction, Value};
+use crate::params;
+use serde::Serialize;
+use crate::{Driver, Error, gen_id};
+use crate::entities::Webhook;
+use crate::pagination::{Page, PageRequest, SortKey};
+use proc::{Stringify, Variants};
+
+/// An entry in the audit log of an organization.
+/// Entries can only be created and listed, never changed or removed.
+#[derive(Debug, Clone)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub org_id: String,
+    pub actor: Actor,
+    pub entity_type: AuditEntityType,
+    pub entity_id: String,
+    pub action: AuditAction,
+    /// JSON object describing the changed fields, see [AuditDiff]
+    pub diff: serde_json::Value,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLogEntryBuilder {
+    pub org_id: String,
+    pub actor: Actor,
+    pub entity_type: AuditEntityType,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub diff: AuditDiff,
+}
+
+/// Who performed an audited action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Actor {
+    User(String),
+    ServiceToken(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Stringify, Variants)]
+pub enum ActorType {
+    User,
+    ServiceToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Stringify, Variants)]
+pub enum AuditEntityType {
+    Org,
+    OrgUser,
+    OrgUserScope,
+    OrgRole,
+    OrgUserRole,
+    OrgInvitation,
+    Product,
+    ProductCategory,
+    PriceList,
+    LedgerAccounts,
+    Webhook,
+    DunningLevels,
+    InvoiceTemplate,
+    TimeEntry,
+    Supplier,
+    PurchaseInvoice,
+    Attachment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Stringify, Variants)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Remove,
+}
+
+/// The changes made to an entity.
+/// Serialized as a JSON object of the form `{ "field": { "old": .., "new": .. } }`
+#[derive(Debug, Clone, Default)]
+pub struct AuditDiff(serde_json::Map<String, serde_json::Value>);
+
+/// Filters applied when listing the audit log. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub actor_id: Option<String>,
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_id: Option<String>,
+    pub action: Option<AuditAction>,
+    /// Only include entries created at or after this UNIX timestamp
+    pub since: Option<i64>,
+    /// Only include entries created before this UNIX timestamp
+    pub until: Option<i64>,
+}
+
+/// Fields the audit log can be sorted on when listing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum AuditLogSort {
+    #[default]
+    CreatedAt,
+}
+
+impl SortKey for AuditLogSort {
+    type Item = AuditLogEntry;
+    const ID_COLUMN: &'static str = "id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+        }
+    }
+
+    fn key(&self, item: &AuditLogEntry) -> Value {
+        match self {
+            Self::CreatedAt => item.created_at.into(),
+        }
+    }
+
+    fn id(item: &AuditLogEntry) -> String {
+        item.id.clone()
+    }
+}
+
+impl Actor {
+    pub fn actor_type(&self) -> ActorType {
+        match self {
+            Self::User(_) => ActorType::User,
+            Self::ServiceToken(_) => ActorType::ServiceToken,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Self::User(x) | Self::ServiceToken(x) => x,
+        }
+    }
+}
+
+impl AuditDiff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a changed field. If `old` and `new` are equal, the field is not recorded
+    pub fn field<T: Serialize + PartialEq>(mut self, name: &str, old: T, new: T) -> Self {
+        if old.ne(&new) {
+            let mut change = serde_json::Map::new();
+            change.insert("old".to_string(), serde_json::to_value(old).unwrap_or_default());
+            change.insert("new".to_string(), serde_json::to_value(new).unwrap_or_default());
+            self.0.insert(name.to_string(), serde_json::Value::Object(change));
+        }
+
+        self
+    }
+
+    /// Record a field of a newly created entity
+    pub fn created<T: Serialize>(self, name: &str, value: T) -> Self {
+        self.field(name, None, Some(serde_json::to_value(value).unwrap_or_default()))
+    }
+
+    /// Record a field of a removed entity
+    pub fn removed<T: Serialize>(self, name: &str, value: T) -> Self {
+        self.field(name, Some(serde_json::to_value(value).unwrap_or_default()), None)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_value(self) -> serde_json::Value {
+        serde_json::Value::Object(self.0)
+    }
+}
+
+impl AuditLogEntry {
+    /// Append an entry to the audit log, and queue it for delivery to subscribed webhooks.
+    /// Create the entry in the transaction the change is made in, so that the entry and its deliveries are only
+    /// written if the change is
+    pub async fn create(tx: &mut Transaction, builder: AuditLogEntryBuilder) -> crate::Result<Self> {
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+        let diff = builder.diff.into_value();
+
+        tx.exec_drop("INSERT INTO audit_log (id, org_id, actor_type, actor_id, entity_type, entity_id, action, diff, created_at) VALUES (:id, :org_id, :actor_type, :actor_id, :entity_type, :entity_id, :action, :diff, :created_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org_id,
+            "actor_type" => builder.actor.actor_type().to_string(),
+            "actor_id" => builder.actor.id(),
+            "entity_type" => builder.entity_type.to_string(),
+            "entity_id" => &builder.entity_id,
+            "action" => builder.action.to_string(),
+            "diff" => diff.to_string(),
+            "created_at" => created_at,
+        }).await?;
+
+        let entry = Self {
+            id,
+            org_id: builder.org_id,
+            actor: builder.actor,
+            entity_type: builder.entity_type,
+            entity_id: builder.entity_id,
+            action: builder.action,
+            diff,
+            created_at,
+        };
+
+        Webhook::enqueue(tx, &entry).await?;
+        Ok(entry)
+    }
+
+    /// List a page of the audit log of an organization
+    pub async fn list_for_org(driver: &Driver, org_id: &str, filter: &AuditLogFilter, page: &PageRequest<AuditLogSort>) -> crate::Result<Page<Self>> {
+        let mut query = String::from("SELECT id,org_id,actor_type,actor_id,entity_type,entity_id,action,diff,created_at FROM audit_log WHERE org_id = :org_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), org_id.into()),
+        ];
+
+        if let Some(actor_id) = &filter.actor_id {
+            query.push_str(" AND actor_id = :actor_id");
+            params.push(("actor_id".to_string(), actor_id.into()));
+        }
+
+        if let Some(entity_type) = &filter.entity_type {
+            query.push_str(" AND entity_type = :entity_type");
+            params.push(("entity_type".to_string(), entity_type.to_string().into()));
+        }
+
+        if let Some(entity_id) = &filter.entity_id {
+            query.push_str(" AND entity_id = :entity_id");
+            params.push(("entity_id".to_string(), entity_id.into()));
+        }
+
+        if let Some(action) = &filter.action {
+            query.push_str(" AND action = :action");
+            params.push(("action".to_string(), action.to_string().into()));
+        }
+
+        if let Some(since) = filter.since {
+            query.push_str(" AND created_at >= :since");
+            params.push(("since".to_string(), since.into()));
+        }
+
+        if let Some(until) = filter.until {
+            query.push_str(" AND created_at < :until");
+            params.push(("until".to_string(), until.into()));
+        }
+
+        page.apply(&mut query, &mut params);
+
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
+        let entries = rows.into_iter()
+            .map(|row| {
+                let actor_type: String = row.get("actor_type").unwrap();
+                let actor_id: String = row.get("actor_id").unwrap();
+                let actor = match ActorType::from_str(&actor_type).map_err(|_| Error::UnknownEnumVariant)? {
+                    ActorType::User => Actor::User(actor_id),
+                    ActorType::ServiceToken => Actor::ServiceToken(actor_id),
+                };
+
+                let entity_type: String = row.get("entity_type").unwrap();
+                let action: String = row.get("action").unwrap();
+                let diff: String = row.get("diff").unwrap();
+
+                Ok(Self {
+                    id: row.get("id").unwrap(),
+                    org_id: row.get("org_id").unwrap(),
+                    actor,
+                    entity_type: AuditEntityType::from_str(&entity_type).map_err(|_| Error::UnknownEnumVariant)?,
+                    entity_id: row.get("entity_id").unwrap(),
+                    action: AuditAction::from_str(&action).map_err(|_| Error::UnknownEnumVariant)?,
+                    diff: serde_json::from_str(&diff)?,
+                    created_at: row.get("created_at").unwrap(),
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        page.finish(entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AuditDiff;
+
+    #[test]
+    fn unchanged_field_is_omitted() {
+        let diff = AuditDiff::new()
+            .field("name", "foo", "foo");
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn changed_field() {
+        let diff = AuditDiff::new()
+            .field("name", "foo", "bar")
+            .into_value();
+        assert_eq!(diff, serde_json::json!({ "name": { "old": "foo", "new": "bar" } }));
+    }
+
+    #[test]
+    fn created_and_removed() {
+        let diff = AuditDiff::new()
+            .created("name", "foo")
+            .removed("price", 1.5)
+            .into_value();
+        assert_eq!(diff, serde_json::json!({
+            "name": { "old": null, "new": "foo" },
+            "price": { "old": 1.5, "new": null },
+        }));
+    }
+}
\ No newline at end of file
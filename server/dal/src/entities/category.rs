@@ -0,0 +1,142 @@
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, gen_id};
+use crate::entities::{Entity, Org};
+
+/// A category products of an organization can be grouped in
+#[derive(Debug, Clone)]
+pub struct ProductCategory {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductCategoryBuilder<'a> {
+    pub org: &'a Org,
+    pub name: String,
+}
+
+impl Entity for ProductCategory {
+    type Information<'a> = ProductCategoryBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let category = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(category)
+    }
+
+    /// Remove the category. Products in the category are kept, but no longer have a category
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT org_id,name FROM product_categories WHERE id = :id", params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id,
+            org_id: row.get("org_id").unwrap(),
+            name: row.get("name").unwrap(),
+        }))
+    }
+}
+
+impl ProductCategory {
+    /// Create a category using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: ProductCategoryBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+
+        tx.exec_drop("INSERT INTO product_categories (id, org_id, name) VALUES (:id, :org_id, :name)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "name" => &builder.name,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            name: builder.name,
+        })
+    }
+
+    /// Remove the category using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE products SET category_id = NULL WHERE category_id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM product_categories WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the category using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE product_categories SET name = :name WHERE id = :id", params! {
+            "name" => &self.name,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Get a category of the organization by its name
+    pub async fn get_by_name(driver: &Driver, org: &Org, name: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT id FROM product_categories WHERE org_id = :org_id AND name = :name", params! {
+            "org_id" => &org.id,
+            "name" => name,
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: org.id.clone(),
+            name: name.to_string(),
+        }))
+    }
+
+    /// List all categories of the organization, ordered by name
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,name FROM product_categories WHERE org_id = :org_id ORDER BY name", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let categories = rows.into_iter()
+            .map(|row| Self {
+                driver: driver.clone(),
+                id: row.get("id").unwrap(),
+                org_id: org.id.clone(),
+                name: row.get("name").unwrap(),
+            })
+            .collect();
+        Ok(categories)
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::Driver;
+use crate::entities::Org;
+
+/// The reminder levels of an organization for overdue invoices, ordered by [DunningLevel::days_after_due].
+/// Organizations without levels send no reminders
+#[derive(Debug, Clone)]
+pub struct DunningLevels {
+    driver: Driver,
+    pub org_id: String,
+    pub levels: Vec<DunningLevel>,
+}
+
+/// A reminder sent once an invoice is overdue by at least `days_after_due` days
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DunningLevel {
+    pub days_after_due: u32,
+    pub email_subject: String,
+    pub email_body: String,
+    /// Fixed fee charged when the level is reached
+    pub late_fee: Option<f32>,
+    /// Yearly interest percentage charged over the overdue amount
+    pub interest_percentage: Option<f32>,
+}
+
+impl DunningLevels {
+    /// Get the dunning levels of the organization
+    pub async fn get(driver: &Driver, org: &Org) -> crate::Result<Self> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT days_after_due,email_subject,email_body,late_fee,interest_percentage FROM dunning_levels WHERE org_id = :org_id ORDER BY days_after_due", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let levels = rows.into_iter()
+            .map(|row| DunningLevel {
+                days_after_due: row.get("days_after_due").unwrap(),
+                email_subject: row.get("email_subject").unwrap(),
+                email_body: row.get("email_body").unwrap(),
+                late_fee: row.get("late_fee").unwrap(),
+                interest_percentage: row.get("interest_percentage").unwrap(),
+            })
+            .collect();
+
+        Ok(Self {
+            driver: driver.clone(),
+            org_id: org.id.clone(),
+            levels,
+        })
+    }
+
+    /// Store the dunning levels, replacing those stored previously
+    pub async fn save(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.save_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store the dunning levels using the provided transaction
+    pub async fn save_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        self.levels.sort_by_key(|x| x.days_after_due);
+
+        tx.exec_drop("DELETE FROM dunning_levels WHERE org_id = :org_id", params! {
+            "org_id" => &self.org_id
+        }).await?;
+
+        for level in &self.levels {
+            tx.exec_drop("INSERT INTO dunning_levels (org_id, days_after_due, email_subject, email_body, late_fee, interest_percentage) VALUES (:org_id, :days_after_due, :email_subject, :email_body, :late_fee, :interest_percentage)", params! {
+                "org_id" => &self.org_id,
+                "days_after_due" => level.days_after_due,
+                "email_subject" => &level.email_subject,
+                "email_body" => &level.email_body,
+                "late_fee" => level.late_fee,
+                "interest_percentage" => level.interest_percentage,
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The level which applies to an invoice overdue by `days_overdue` days.
+    /// This is the level with the most days after the due date not exceeding `days_overdue`
+    pub fn level_for(&self, days_overdue: u32) -> Option<&DunningLevel> {
+        applicable_level(&self.levels, days_overdue)
+    }
+}
+
+fn applicable_level(levels: &[DunningLevel], days_overdue: u32) -> Option<&DunningLevel> {
+    levels.iter()
+        .filter(|x| x.days_after_due <= days_overdue)
+        .max_by_key(|x| x.days_after_due)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn highest_reached_level() {
+        let level = |days_after_due| DunningLevel {
+            days_after_due,
+            email_subject: String::new(),
+            email_body: String::new(),
+            late_fee: None,
+            interest_percentage: None,
+        };
+
+        let levels = vec![level(7), level(30), level(14)];
+        let level_for = |days| applicable_level(&levels, days).map(|x| x.days_after_due);
+
+        assert_eq!(level_for(3), None);
+        assert_eq!(level_for(7), Some(7));
+        assert_eq!(level_for(20), Some(14));
+        assert_eq!(level_for(365), Some(30));
+    }
+}
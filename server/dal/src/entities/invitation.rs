@@ -0,0 +1,266 @@
+use std::str::FromStr;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, Error, gen_id};
+use crate::entities::{Entity, Language, Org, OrgScope, User};
+
+/// An invitation for an email address to join an organization.
+/// The invitee does not need to have an account yet, the invitation is accepted
+/// using its token once the invitee has registered or logged in.
+#[derive(Debug, Clone)]
+pub struct OrgInvitation {
+    driver: Driver,
+    pub id: String,
+    /// The secret token sent to the invitee
+    pub token: String,
+    pub org_id: String,
+    pub email: String,
+    /// Whether the invitee will become an organization admin
+    pub is_org_admin: bool,
+    /// The scopes the invitee will be granted
+    pub scopes: Vec<OrgScope>,
+    /// The ID of the user who created the invitation
+    pub invited_by: String,
+    /// The language the invitation email is written in
+    pub language: Language,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrgInvitationBuilder<'a> {
+    pub org: &'a Org,
+    pub email: String,
+    pub is_org_admin: bool,
+    pub scopes: Vec<OrgScope>,
+    pub invited_by: &'a User,
+    pub language: Language,
+    /// How long the invitation remains valid
+    pub expiry: std::time::Duration,
+}
+
+impl Entity for OrgInvitation {
+    type Information<'a> = OrgInvitationBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let invitation = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(invitation)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        Self::get_by("id", driver, &id).await
+    }
+}
+
+impl OrgInvitation {
+    /// Create an invitation using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: OrgInvitationBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let token = gen_id();
+
+        let now = time::OffsetDateTime::now_utc();
+        let created_at = now.unix_timestamp();
+        let expires_at = (now + builder.expiry).unix_timestamp();
+
+        tx.exec_drop("INSERT INTO org_invitations (id, token, org_id, email, org_admin, invited_by, language, created_at, expires_at) VALUES (:id, :token, :org_id, :email, :org_admin, :invited_by, :language, :created_at, :expires_at)", params! {
+            "id" => &id,
+            "token" => &token,
+            "org_id" => &builder.org.id,
+            "email" => &builder.email,
+            "org_admin" => builder.is_org_admin,
+            "invited_by" => &builder.invited_by.id,
+            "language" => builder.language.code(),
+            "created_at" => created_at,
+            "expires_at" => expires_at,
+        }).await?;
+
+        for scope in &builder.scopes {
+            tx.exec_drop("INSERT INTO org_invitation_scopes (invitation_id, scope_name) VALUES (:invitation_id, :scope_name)", params! {
+                "invitation_id" => &id,
+                "scope_name" => scope.to_string(),
+            }).await?;
+        }
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            token,
+            org_id: builder.org.id.clone(),
+            email: builder.email,
+            is_org_admin: builder.is_org_admin,
+            scopes: builder.scopes,
+            invited_by: builder.invited_by.id.clone(),
+            language: builder.language,
+            created_at,
+            expires_at,
+        })
+    }
+
+    /// Remove the invitation using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM org_invitation_scopes WHERE invitation_id = :invitation_id", params! {
+            "invitation_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM org_invitations WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the invitation using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE org_invitations SET token = :token, expires_at = :expires_at WHERE id = :id", params! {
+            "token" => &self.token,
+            "expires_at" => self.expires_at,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Get an invitation by its token
+    pub async fn get_by_token(driver: &Driver, token: &str) -> crate::Result<Option<Self>> {
+        Self::get_by("token", driver, token).await
+    }
+
+    async fn get_by(column: &str, driver: &Driver, value: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first(format!("SELECT id,token,org_id,email,org_admin,invited_by,language,created_at,expires_at FROM org_invitations WHERE {column} = :value"), params! {
+            "value" => value
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let invitation = Self::from_row(&mut conn, driver, row).await?;
+        Ok(Some(invitation))
+    }
+
+    async fn from_row(conn: &mut impl Queryable, driver: &Driver, row: Row) -> crate::Result<Self> {
+        let id: String = row.get("id").unwrap();
+        let rows: Vec<Row> = conn.exec("SELECT scope_name FROM org_invitation_scopes WHERE invitation_id = :invitation_id", params! {
+            "invitation_id" => &id
+        }).await?;
+
+        let scopes = rows.into_iter()
+            .map(|x| {
+                let scope_name: String = x.get("scope_name").unwrap();
+                OrgScope::from_str(&scope_name).map_err(|_| Error::UnknownEnumVariant)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let language: String = row.get("language").unwrap();
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            token: row.get("token").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            email: row.get("email").unwrap(),
+            is_org_admin: row.get("org_admin").unwrap(),
+            scopes,
+            invited_by: row.get("invited_by").unwrap(),
+            language: Language::from_code(&language).ok_or(Error::UnknownEnumVariant)?,
+            created_at: row.get("created_at").unwrap(),
+            expires_at: row.get("expires_at").unwrap(),
+        })
+    }
+
+    /// List all pending invitations of the organization, including those which have expired
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,token,org_id,email,org_admin,invited_by,language,created_at,expires_at FROM org_invitations WHERE org_id = :org_id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let mut invitations = Vec::with_capacity(rows.len());
+        for row in rows {
+            invitations.push(Self::from_row(&mut conn, driver, row).await?);
+        }
+
+        Ok(invitations)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        time::OffsetDateTime::now_utc().unix_timestamp() > self.expires_at
+    }
+
+    /// Generate a new token and move the expiry forward, invalidating the previous token
+    pub async fn renew(&mut self, expiry: std::time::Duration) -> crate::Result<()> {
+        self.token = gen_id();
+        self.expires_at = (time::OffsetDateTime::now_utc() + expiry).unix_timestamp();
+        self.update().await
+    }
+
+    /// Renew the invitation using the provided transaction, see [Self::renew]
+    pub async fn renew_with_tx(&mut self, tx: &mut Transaction, expiry: std::time::Duration) -> crate::Result<()> {
+        self.token = gen_id();
+        self.expires_at = (time::OffsetDateTime::now_utc() + expiry).unix_timestamp();
+        self.update_with_tx(tx).await
+    }
+
+    /// Accept the invitation on behalf of `user`, adding the user to the organization.
+    /// The user's email address must match the address the invitation was sent to.
+    /// The invitation is removed once accepted.
+    ///
+    /// # Errors
+    ///
+    /// - [Error::ExpiredToken] if the invitation has expired
+    /// - [Error::UnknownToken] if the user's email address does not match
+    pub async fn accept(self, user: &User) -> crate::Result<()> {
+        let mut org = self.prepare_accept(&user.email).await?;
+
+        let mut tx = self.driver.start_transaction().await?;
+        self.accept_with_tx(&mut tx, &mut org, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Check that the invitation can be accepted by the user with the provided email address, returning its organization.
+    /// See [Self::accept] for the errors returned
+    pub async fn prepare_accept(&self, email: &str) -> crate::Result<Org> {
+        if self.is_expired() {
+            return Err(Error::ExpiredToken);
+        }
+
+        if !self.email.eq_ignore_ascii_case(email) {
+            return Err(Error::UnknownToken);
+        }
+
+        Org::get(&self.driver, self.org_id.clone()).await?
+            .ok_or_else(|| Error::InvalidState(format!("Invitation {} belongs to organization {}, but it does not exist", self.id, self.org_id)))
+    }
+
+    /// Accept the invitation using the provided transaction. The invitation must have been checked with [Self::prepare_accept]
+    pub async fn accept_with_tx(&self, tx: &mut Transaction, org: &mut Org, user: &User) -> crate::Result<()> {
+        let existing: Option<Row> = tx.exec_first("SELECT user_id FROM org_user_links WHERE org_id = :org_id AND user_id = :user_id", params! {
+            "org_id" => &self.org_id,
+            "user_id" => &user.id,
+        }).await?;
+
+        // Users who are already part of the organization keep their current scopes
+        if existing.is_none() {
+            org.add_user_with_tx(tx, user, self.is_org_admin, &self.scopes).await?;
+        }
+
+        self.remove_with_tx(tx).await
+    }
+}
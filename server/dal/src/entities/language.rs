@@ -0,0 +1,42 @@
+use proc::Variants;
+
+/// A language documents and emails can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Variants)]
+pub enum Language {
+    #[default]
+    En,
+    Nl,
+    De,
+}
+
+impl Language {
+    /// The ISO 639-1 code of the language
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Nl => "nl",
+            Self::De => "de",
+        }
+    }
+
+    /// Parse an ISO 639-1 code, ignoring case and a region suffix, e.g. `nl-BE`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let language = code.split(['-', '_']).next()?;
+        Self::variants().iter()
+            .find(|x| x.code().eq_ignore_ascii_case(language))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Language;
+
+    #[test]
+    fn parse_code() {
+        assert_eq!(Language::from_code("nl"), Some(Language::Nl));
+        assert_eq!(Language::from_code("DE-at"), Some(Language::De));
+        assert_eq!(Language::from_code("en_GB"), Some(Language::En));
+        assert_eq!(Language::from_code("fr"), None);
+    }
+}
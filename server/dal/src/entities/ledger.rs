@@ -0,0 +1,123 @@
+use serde::Serialize;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::Driver;
+use crate::entities::Org;
+
+/// Tax percentages within this distance of each other are considered equal
+const TAX_PERCENTAGE_EPSILON: f32 = 0.001;
+
+/// The ledger accounts of an organization's bookkeeping, to which journal entries are booked when exporting.
+/// Every organization has exactly one set of ledger accounts, which is empty until configured
+#[derive(Debug, Clone)]
+pub struct LedgerAccounts {
+    driver: Driver,
+    pub org_id: String,
+    /// The account receivables are booked to
+    pub debtor_account: Option<String>,
+    /// The account revenue is booked to if its tax percentage has no account in [Self::revenue_accounts]
+    pub default_revenue_account: Option<String>,
+    pub revenue_accounts: Vec<RevenueAccount>,
+}
+
+/// The account revenue with a specific tax percentage is booked to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RevenueAccount {
+    pub tax_percentage: f32,
+    pub account: String,
+}
+
+impl LedgerAccounts {
+    /// Get the ledger accounts of the organization
+    pub async fn get(driver: &Driver, org: &Org) -> crate::Result<Self> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT debtor_account,default_revenue_account FROM ledger_accounts WHERE org_id = :org_id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let rows: Vec<Row> = conn.exec("SELECT tax_percentage,account FROM ledger_revenue_accounts WHERE org_id = :org_id ORDER BY tax_percentage", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let revenue_accounts = rows.into_iter()
+            .map(|row| RevenueAccount {
+                tax_percentage: row.get("tax_percentage").unwrap(),
+                account: row.get("account").unwrap(),
+            })
+            .collect();
+
+        Ok(Self {
+            driver: driver.clone(),
+            org_id: org.id.clone(),
+            debtor_account: row.as_ref().and_then(|x| x.get("debtor_account").unwrap()),
+            default_revenue_account: row.as_ref().and_then(|x| x.get("default_revenue_account").unwrap()),
+            revenue_accounts,
+        })
+    }
+
+    /// Store the ledger accounts, replacing those stored previously
+    pub async fn save(&self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.save_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store the ledger accounts using the provided transaction
+    pub async fn save_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM ledger_revenue_accounts WHERE org_id = :org_id", params! {
+            "org_id" => &self.org_id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM ledger_accounts WHERE org_id = :org_id", params! {
+            "org_id" => &self.org_id
+        }).await?;
+
+        tx.exec_drop("INSERT INTO ledger_accounts (org_id, debtor_account, default_revenue_account) VALUES (:org_id, :debtor_account, :default_revenue_account)", params! {
+            "org_id" => &self.org_id,
+            "debtor_account" => &self.debtor_account,
+            "default_revenue_account" => &self.default_revenue_account,
+        }).await?;
+
+        for revenue_account in &self.revenue_accounts {
+            tx.exec_drop("INSERT INTO ledger_revenue_accounts (org_id, tax_percentage, account) VALUES (:org_id, :tax_percentage, :account)", params! {
+                "org_id" => &self.org_id,
+                "tax_percentage" => revenue_account.tax_percentage,
+                "account" => &revenue_account.account,
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The account revenue with the provided tax percentage is booked to.
+    /// Revenue without a tax percentage, or with a percentage without an account of its own, uses the default revenue account
+    pub fn revenue_account(&self, tax_percentage: Option<f32>) -> Option<&str> {
+        revenue_account(&self.revenue_accounts, self.default_revenue_account.as_deref(), tax_percentage)
+    }
+}
+
+fn revenue_account<'a>(accounts: &'a [RevenueAccount], default: Option<&'a str>, tax_percentage: Option<f32>) -> Option<&'a str> {
+    tax_percentage
+        .and_then(|tax_percentage| accounts.iter().find(|x| (x.tax_percentage - tax_percentage).abs() < TAX_PERCENTAGE_EPSILON))
+        .map(|x| x.account.as_str())
+        .or(default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_default_account() {
+        let accounts = vec![
+            RevenueAccount { tax_percentage: 21.0, account: "8000".to_string() },
+            RevenueAccount { tax_percentage: 9.0, account: "8010".to_string() },
+        ];
+
+        assert_eq!(revenue_account(&accounts, Some("8090"), Some(9.0)), Some("8010"));
+        assert_eq!(revenue_account(&accounts, Some("8090"), Some(0.0)), Some("8090"));
+        assert_eq!(revenue_account(&accounts, Some("8090"), None), Some("8090"));
+        assert_eq!(revenue_account(&accounts, None, Some(6.0)), None);
+    }
+}
@@ -1,21 +1,63 @@
+use std::future::Future;
 use crate::{Result, Driver};
 
 mod user;
 mod org;
 mod product;
+mod category;
+mod unit;
+mod language;
+mod price_list;
+mod ledger;
+mod webhook;
+mod dunning;
+mod template;
+mod time_entry;
+mod supplier;
+mod purchase_invoice;
+mod attachment;
+mod audit;
+mod role;
+mod invitation;
 
 pub use user::*;
 pub use org::*;
 pub use product::*;
+pub use category::*;
+pub use unit::*;
+pub use language::*;
+pub use price_list::*;
+pub use ledger::*;
+pub use webhook::*;
+pub use dunning::*;
+pub use template::*;
+pub use time_entry::*;
+pub use supplier::*;
+pub use purchase_invoice::*;
+pub use attachment::*;
+pub use audit::*;
+pub use role::*;
+pub use invitation::*;
 
-pub trait Entity<'a>: Sized {
-    type Information;
+/// An entity stored in the database.
+/// Entities hold on to a clone of the [Driver] they were loaded with, so they can be moved between tasks freely
+pub trait Entity: Sized {
+    type Information<'a>;
 
-    fn create(driver: &'a Driver, builder: Self::Information) -> Result<Self>;
+    fn create(driver: &Driver, builder: Self::Information<'_>) -> impl Future<Output = Result<Self>> + Send;
 
-    fn remove(self) -> Result<()>;
+    fn remove(self) -> impl Future<Output = Result<()>> + Send;
 
-    fn update(&mut self) -> Result<()>;
+    fn update(&mut self) -> impl Future<Output = Result<()>> + Send;
 
-    fn get(driver: &'a Driver, id: String) -> Result<Option<Self>>;
-}
\ No newline at end of file
+    fn get(driver: &Driver, id: String) -> impl Future<Output = Result<Option<Self>>> + Send;
+}
+
+/// Create a `LIKE` pattern matching any value containing `needle`.
+/// Wildcards in `needle` are escaped with `!`, so queries must use `LIKE :pattern ESCAPE '!'`
+pub(crate) fn contains_pattern(needle: &str) -> String {
+    let escaped = needle.replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_");
+    format!("%{escaped}%")
+}
@@ -1,13 +1,14 @@
 use std::str::FromStr;
-use mysql::prelude::Queryable;
-use mysql::{params, Params, Row, Transaction, TxOpts};
+use crate::driver::{Conn, Params, Queryable, Row, Transaction, Value};
+use crate::params;
 use crate::{Driver, Error, gen_id};
-use crate::entities::{Entity, User};
+use crate::entities::{contains_pattern, Entity, User};
+use crate::pagination::{Page, PageRequest, SortKey};
 use proc::{Stringify, Variants, ScopeList};
 
 #[derive(Debug, Clone)]
-pub struct Org<'a> {
-    driver: &'a Driver,
+pub struct Org {
+    driver: Driver,
     pub id: String,
     pub name: String,
 }
@@ -15,7 +16,7 @@ pub struct Org<'a> {
 #[derive(Debug, Clone)]
 pub struct OrgBuilder<'a> {
     pub name: String,
-    pub creator: &'a User<'a>
+    pub creator: &'a User
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Stringify, Variants, ScopeList)]
@@ -33,40 +34,187 @@ pub enum OrgScope {
     UpdateOrg,
     /// Allows the user to get and list products
     GetProduct,
-    /// Allows the user to create products
+    /// Allows the user to create products and product categories
     #[admin]
     CreateProduct,
-    /// Allows the user to remove products
+    /// Allows the user to archive products and remove product categories
     #[admin]
     RemoveProduct,
-    /// Allows the user to update existing products
+    /// Allows the user to update existing products and product categories
     #[admin]
     UpdateProduct,
+    /// Allows the user to view the audit log of the organization
+    #[admin]
+    GetAuditLog,
+    /// Allows the user to manage webhooks and view their deliveries
+    #[admin]
+    ManageWebhooks,
+    /// Allows the user to create, update and remove invoice templates
+    #[admin]
+    ManageInvoiceTemplates,
+    /// Allows the user to record time, and to view, update and remove their own time entries
+    TrackTime,
+    /// Allows the user to view, update and remove the time entries of all users, and to bill them
+    #[admin]
+    ManageTimeEntries,
+    /// Allows the user to view suppliers and purchase invoices, and the VAT totals of purchase invoices
+    GetPurchaseInvoice,
+    /// Allows the user to record, import, update and remove suppliers and purchase invoices
+    #[admin]
+    ManagePurchaseInvoice,
 }
 
 #[derive(Debug, Clone)]
-pub struct OrgUserLink<'a> {
-    pub org: Org<'a>,
-    pub user: User<'a>
+pub struct OrgUserLink {
+    pub org: Org,
+    pub user: User
+}
+
+impl Entity for Org {
+    type Information<'a> = OrgBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let org = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(org)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut conn = self.driver.get_conn().await?;
+        conn.exec_drop("UPDATE orgs SET name = :name WHERE id = :id", params! {
+            "id" => &self.id,
+            "name" => &self.name
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT name FROM orgs WHERE id = :id", params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id,
+            name: row.get("name").unwrap(),
+        }))
+    }
+}
+
+/// A user who is part of an organization
+pub struct OrgUser {
+    /// The user itself
+    pub user: User,
+    /// Whether the user is an organization admin.
+    /// When this is `true`, it does not matter which scopes
+    /// the user has, they can do anything within the organization.
+    pub is_org_admin: bool,
+    /// The scopes that have been granted to the user
+    pub scopes: Vec<OrgScope>,
+}
+
+/// Fields organizations can be sorted on when listing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum OrgSort {
+    #[default]
+    Name,
+}
+
+impl SortKey for OrgSort {
+    type Item = Org;
+    const ID_COLUMN: &'static str = "o.id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Name => "o.name",
+        }
+    }
+
+    fn key(&self, item: &Org) -> Value {
+        match self {
+            Self::Name => item.name.clone().into(),
+        }
+    }
+
+    fn id(item: &Org) -> String {
+        item.id.clone()
+    }
+}
+
+/// Filters applied when listing organizations. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct OrgFilter {
+    /// Only include organizations whose name contains this value, case insensitive
+    pub name: Option<String>,
+}
+
+/// Fields users can be sorted on when listing the users of an organization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum OrgUserSort {
+    #[default]
+    Name,
+    Email,
+}
+
+impl SortKey for OrgUserSort {
+    type Item = OrgUser;
+    const ID_COLUMN: &'static str = "u.id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Name => "u.name",
+            Self::Email => "u.email",
+        }
+    }
+
+    fn key(&self, item: &OrgUser) -> Value {
+        match self {
+            Self::Name => item.user.name.clone().into(),
+            Self::Email => item.user.email.clone().into(),
+        }
+    }
+
+    fn id(item: &OrgUser) -> String {
+        item.user.id.clone()
+    }
 }
 
-impl<'a> Entity<'a> for Org<'a> {
-    type Information = OrgBuilder<'a>;
+/// Filters applied when listing the users of an organization. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct OrgUserFilter {
+    /// Only include users whose name or email address contains this value, case insensitive
+    pub search: Option<String>,
+    pub is_org_admin: Option<bool>,
+}
 
-    fn create(driver: &'a Driver, builder: Self::Information) -> crate::Result<Self> {
-        let mut tx = driver.start_transaction(TxOpts::default())?;
+impl Org {
+    /// Create an organization using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: OrgBuilder<'_>) -> crate::Result<Self> {
         let id = gen_id();
 
         tx.exec_drop("INSERT INTO orgs (id, name, created_at) VALUES (:id, :name, :created_at)", params! {
             "id" => &id,
             "name" => &builder.name,
             "created_at" => time::OffsetDateTime::now_utc().unix_timestamp()
-        })?;
+        }).await?;
 
         tx.exec_drop("INSERT INTO org_user_links (org_id, user_id, org_admin) VALUES (:org_id, :user_id, true)", params! {
             "org_id" => &id,
             "user_id" => &builder.creator.id
-        })?;
+        }).await?;
 
         for scope in OrgScope::variants() {
             let scope: &OrgScope = scope;
@@ -74,188 +222,371 @@ impl<'a> Entity<'a> for Org<'a> {
                 "org_id" => &id,
                 "user_id" => &builder.creator.id,
                 "scope_name" => &scope.to_string()
-            })?;
+            }).await?;
         }
 
-        tx.commit()?;
-
         Ok(Self {
-            driver,
+            driver: driver.clone(),
             id,
             name: builder.name
         })
     }
 
-    fn remove(self) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    /// Remove the organization using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
         tx.exec_drop("DELETE FROM org_user_links WHERE org_id = :org_id", params! {
             "org_id" => &self.id
-        })?;
+        }).await?;
 
         tx.exec_drop("DELETE FROM org_user_link_scopes WHERE org_id = :org_id", params! {
             "org_id" => &self.id
-        })?;
+        }).await?;
 
-        tx.exec_drop("DELETE FROM orgs WHERE id = :id", params! {
-            "id" => &self.id
-        })?;
+        tx.exec_drop("DELETE FROM org_user_roles WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
 
-        tx.commit()?;
+        tx.exec_drop("DELETE FROM org_role_scopes WHERE role_id IN (SELECT id FROM org_roles WHERE org_id = :org_id)", params! {
+            "org_id" => &self.id
+        }).await?;
 
-        Ok(())
-    }
+        tx.exec_drop("DELETE FROM org_roles WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
 
-    fn update(&mut self) -> crate::Result<()> {
-        let mut conn = self.driver.get_conn()?;
-        conn.exec_drop("UPDATE orgs SET name = :name WHERE id = :id", params! {
-            "id" => &self.id,
-            "name" => &self.name
-        })?;
+        tx.exec_drop("DELETE FROM org_invitation_scopes WHERE invitation_id IN (SELECT id FROM org_invitations WHERE org_id = :org_id)", params! {
+            "org_id" => &self.id
+        }).await?;
 
-        Ok(())
-    }
+        tx.exec_drop("DELETE FROM org_invitations WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
 
-    fn get(driver: &'a Driver, id: String) -> crate::Result<Option<Self>> {
-        let mut conn = driver.get_conn()?;
-        let row: Row = match conn.exec_first("SELECT name FROM orgs WHERE id = :id", params! {
-            "id" => &id
-        })? {
-            Some(x) => x,
-            None => return Ok(None)
-        };
+        tx.exec_drop("DELETE FROM product_categories WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
 
-        Ok(Some(Self {
-            driver,
-            id,
-            name: row.get("name").unwrap(),
-        }))
+        tx.exec_drop("DELETE FROM price_list_prices WHERE price_list_id IN (SELECT id FROM price_lists WHERE org_id = :org_id)", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM price_lists WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM ledger_revenue_accounts WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM ledger_accounts WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        for table in ["webhook_deliveries", "webhook_event_types"] {
+            tx.exec_drop(format!("DELETE FROM {table} WHERE webhook_id IN (SELECT id FROM webhooks WHERE org_id = :org_id)"), params! {
+                "org_id" => &self.id
+            }).await?;
+        }
+
+        tx.exec_drop("DELETE FROM webhooks WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM time_entries WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM purchase_invoices WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM suppliers WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM attachments WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM invoice_templates WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM dunning_levels WHERE org_id = :org_id", params! {
+            "org_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM orgs WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
     }
-}
 
-/// A user who is part of an organization
-pub struct OrgUser<'a> {
-    /// The user itself
-    pub user: User<'a>,
-    /// Whether the user is an organization admin.
-    /// When this is `true`, it does not matter which scopes
-    /// the user has, they can do anything within the organization.
-    pub is_org_admin: bool,
-    /// The scopes that have been granted to the user
-    pub scopes: Vec<OrgScope>,
-}
+    /// List the organizations in which the user has the provided scope.
+    /// Organization admins have every scope, other users need to be granted the scope directly or through a role
+    pub async fn list_for_user(driver: &Driver, user: &User, scope: &OrgScope, filter: &OrgFilter, page: &PageRequest<OrgSort>) -> crate::Result<Page<Self>> {
+        let mut query = String::from("SELECT o.id,o.name FROM orgs o INNER JOIN org_user_links l ON l.org_id = o.id \
+            WHERE l.user_id = :user_id AND (l.org_admin = true \
+            OR EXISTS (SELECT 1 FROM org_user_link_scopes s WHERE s.org_id = o.id AND s.user_id = l.user_id AND s.scope_name = :scope_name) \
+            OR EXISTS (SELECT 1 FROM org_user_roles ur INNER JOIN org_role_scopes rs ON rs.role_id = ur.role_id WHERE ur.org_id = o.id AND ur.user_id = l.user_id AND rs.scope_name = :scope_name))");
+        let mut params: Vec<(String, Value)> = vec![
+            ("user_id".to_string(), user.id.clone().into()),
+            ("scope_name".to_string(), scope.to_string().into()),
+        ];
+
+        if let Some(name) = &filter.name {
+            query.push_str(" AND LOWER(o.name) LIKE LOWER(:name) ESCAPE '!'");
+            params.push(("name".to_string(), contains_pattern(name).into()));
+        }
 
-impl<'a> Org<'a> {
-    /// List all known orgs
-    pub fn list_available(driver: &'a Driver) -> crate::Result<Vec<Self>> {
-        let mut conn = driver.get_conn()?;
-        let rows: Vec<Row> = conn.exec("SELECT id FROM orgs", Params::Empty)?;
+        page.apply(&mut query, &mut params);
+
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
         let orgs = rows.into_iter()
-            .map(|x| Ok(Org::get(driver, x.get("id").unwrap())?.unwrap()))
-            .collect::<crate::Result<Vec<_>>>()?;
-        Ok(orgs)
+            .map(|row| Self {
+                driver: driver.clone(),
+                id: row.get("id").unwrap(),
+                name: row.get("name").unwrap(),
+            })
+            .collect::<Vec<_>>();
+
+        page.finish(orgs)
     }
 
     /// Add a user to the organization. If `admin` is set to `true`, all scopes will be granted.
     /// If `admin` is set to false, only non-admin scopes will be granted.
-    pub fn add_user(&mut self, user: &User<'_>, admin: bool) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
-        tx.exec_drop("INSERT INTO org_user_links (:org_id, :user_id, :org_admin) VALUES (:org_id, :user_id, :org_admin)", params! {
+    pub async fn add_user(&mut self, user: &User, admin: bool) -> crate::Result<()> {
+        self.add_user_with_scopes(user, admin, OrgScope::default_scopes()).await
+    }
+
+    /// Add a user to the organization, granting the provided scopes.
+    /// If `admin` is set to `true`, all admin scopes will be granted as well.
+    pub async fn add_user_with_scopes(&mut self, user: &User, admin: bool, scopes: &[OrgScope]) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.add_user_with_tx(&mut tx, user, admin, scopes).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Add a user to the organization using the provided transaction
+    pub async fn add_user_with_tx(&mut self, tx: &mut Transaction, user: &User, admin: bool, scopes: &[OrgScope]) -> crate::Result<()> {
+        tx.exec_drop("INSERT INTO org_user_links (org_id, user_id, org_admin) VALUES (:org_id, :user_id, :org_admin)", params! {
             "org_id" => &self.id,
             "user_id" => &user.id,
             "org_admin" => admin,
-        })?;
+        }).await?;
 
-        for scope in OrgScope::default_scopes() {
-            let scope: &OrgScope = scope;
-            self.set_scope_with_tx(&mut tx, user, scope, true)?;
-        }
-
-        if admin {
-            for scope in OrgScope::admin_scopes() {
-                let scope: &OrgScope = scope;
-                self.set_scope_with_tx(&mut tx, user, scope, true)?;
+        let mut granted: Vec<&OrgScope> = Vec::new();
+        let admin_scopes: &[OrgScope] = if admin { OrgScope::admin_scopes() } else { &[] };
+        for scope in scopes.iter().chain(admin_scopes) {
+            if granted.contains(&scope) {
+                continue;
             }
+
+            self.set_scope_with_tx(tx, user, scope, true).await?;
+            granted.push(scope);
         }
 
-        tx.commit()?;
         Ok(())
     }
 
+    /// Check whether the user is part of the organization
+    pub async fn has_user(&self, user: &User) -> crate::Result<bool> {
+        let mut conn = self.driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT user_id FROM org_user_links WHERE org_id = :org_id AND user_id = :user_id", params! {
+            "org_id" => &self.id,
+            "user_id" => &user.id,
+        }).await?;
+
+        Ok(row.is_some())
+    }
+
     /// Remove a user from the organization.
+    /// The last admin of the organization cannot be removed, [Error::LastOrgAdmin] is returned instead.
     /// If the user is the last user, the organization is **not** automatically deleted, this is up to the callee
-    pub fn remove_user(&mut self, user: &User<'_>) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    pub async fn remove_user(&mut self, user: &User) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_user_with_tx(&mut tx, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove a user from the organization using the provided transaction, see [Self::remove_user]
+    pub async fn remove_user_with_tx(&mut self, tx: &mut Transaction, user: &User) -> crate::Result<()> {
+        // Lock the organization, so that concurrent removals cannot both see another admin remaining.
+        // On SQLite the transaction already holds the write lock
+        tx.exec_drop("UPDATE orgs SET id = id WHERE id = :org_id", params! {
+            "org_id" => &self.id,
+        }).await?;
+
+        let admins: Vec<Row> = tx.exec("SELECT user_id FROM org_user_links WHERE org_id = :org_id AND org_admin = true", params! {
+            "org_id" => &self.id,
+        }).await?;
+
+        let is_admin = admins.iter().any(|x| x.get::<String, _>("user_id").unwrap().eq(&user.id));
+        if is_admin && admins.len() <= 1 {
+            return Err(Error::LastOrgAdmin);
+        }
+
         tx.exec_drop("DELETE FROM org_user_link_scopes WHERE org_id = :org_id AND user_id = :user_id", params! {
             "org_id" => &self.id,
             "user_id" => &user.id
-        })?;
+        }).await?;
+
+        tx.exec_drop("DELETE FROM org_user_roles WHERE org_id = :org_id AND user_id = :user_id", params! {
+            "org_id" => &self.id,
+            "user_id" => &user.id
+        }).await?;
 
         tx.exec_drop("DELETE FROM org_user_links WHERE org_id = :org_id AND user_id = :user_id", params! {
             "org_id" => &self.id,
             "user_id" => &user.id
-        })?;
+        }).await?;
 
-        tx.commit()?;
         Ok(())
     }
 
     /// Set a scope using the provided transaction
-    fn set_scope_with_tx(&mut self, tx: &mut Transaction, user: &User<'_>, scope: &OrgScope, enabled: bool) -> crate::Result<()> {
+    pub async fn set_scope_with_tx(&mut self, tx: &mut Transaction, user: &User, scope: &OrgScope, enabled: bool) -> crate::Result<()> {
         if enabled {
             tx.exec_drop("INSERT INTO org_user_link_scopes (org_id, user_id, scope_name) VALUES (:org_id, :user_id, :scope_name)", params! {
                 "org_id" => &self.id,
                 "user_id" => &user.id,
                 "scope_name" => &scope.to_string()
-            })?;
+            }).await?;
         } else {
-            tx.exec_drop("DELETE FROM org_user_link_scopes WHERE user_id = :user_id AND org_id = :org_id", params! {
+            tx.exec_drop("DELETE FROM org_user_link_scopes WHERE user_id = :user_id AND org_id = :org_id AND scope_name = :scope_name", params! {
                 "user_id" => &user.id,
                 "org_id" => &self.id,
-            })?;
+                "scope_name" => &scope.to_string()
+            }).await?;
         }
 
         Ok(())
     }
 
     /// Enable or disable a scope on a user for this organization
-    pub fn set_scope(&mut self, user: &User<'_>, scope: &OrgScope, enabled: bool) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
-        self.set_scope_with_tx(&mut tx, user, scope, enabled)?;
-        tx.commit()?;
+    pub async fn set_scope(&mut self, user: &User, scope: &OrgScope, enabled: bool) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.set_scope_with_tx(&mut tx, user, scope, enabled).await?;
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Get a user's link to the organization, including the scopes the user has.
+    /// Returns `None` if the user is not part of the organization
+    pub async fn get_user(&self, user: &User) -> crate::Result<Option<OrgUser>> {
+        let mut conn = self.driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT org_admin FROM org_user_links WHERE org_id = :org_id AND user_id = :user_id", params! {
+            "org_id" => &self.id,
+            "user_id" => &user.id,
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let scopes = self.query_scopes(&mut conn, user).await?;
+
+        Ok(Some(OrgUser {
+            user: user.clone(),
+            is_org_admin: row.get("org_admin").unwrap(),
+            scopes,
+        }))
+    }
+
     /// List all users in the organization
-    pub fn list_users(&self) -> crate::Result<Vec<OrgUser<'a>>> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
-        let rows: Vec<Row> = tx.exec("SELECT user_id,org_admin FROM org_user_links WHERE org_id = :org_id", params! {
+    pub async fn list_users(&self) -> crate::Result<Vec<OrgUser>> {
+        let mut conn = self.driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT u.id,u.name,u.email,l.org_admin FROM org_user_links l INNER JOIN users u ON u.id = l.user_id WHERE l.org_id = :org_id", params! {
             "org_id" => &self.id
-        })?;
+        }).await?;
 
-        let users = rows.into_iter()
-            .map(|x| {
-                let user_id: String = x.get("id").unwrap();
-                let org_admin: bool = x.get("org_admin").unwrap();
-
-                let user = User::get(&self.driver, user_id.clone())?.ok_or_else(|| Error::InvalidState(format!("User {user_id} is linked to organization {}, but does not exist", &self.id)))?;
-                let scopes = self.list_scopes_with_tx(&mut tx, &user)?;
-                Ok(OrgUser {
-                    user,
-                    is_org_admin: org_admin,
-                    scopes
-                })
+        self.org_users_from_rows(&mut conn, rows).await
+    }
+
+    /// List a page of the users in the organization
+    pub async fn list_users_page(&self, filter: &OrgUserFilter, page: &PageRequest<OrgUserSort>) -> crate::Result<Page<OrgUser>> {
+        let mut query = String::from("SELECT u.id,u.name,u.email,l.org_admin FROM org_user_links l INNER JOIN users u ON u.id = l.user_id WHERE l.org_id = :org_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), self.id.clone().into()),
+        ];
+
+        if let Some(search) = &filter.search {
+            query.push_str(" AND (LOWER(u.name) LIKE LOWER(:search) ESCAPE '!' OR LOWER(u.email) LIKE LOWER(:search) ESCAPE '!')");
+            params.push(("search".to_string(), contains_pattern(search).into()));
+        }
+
+        if let Some(is_org_admin) = filter.is_org_admin {
+            query.push_str(" AND l.org_admin = :org_admin");
+            params.push(("org_admin".to_string(), is_org_admin.into()));
+        }
+
+        page.apply(&mut query, &mut params);
+
+        let mut conn = self.driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
+        let users = self.org_users_from_rows(&mut conn, rows).await?;
+        page.finish(users)
+    }
+
+    /// Create the users from rows containing the columns of the users table and `org_admin`.
+    /// The scopes of all users are loaded with a single query
+    async fn org_users_from_rows(&self, conn: &mut Conn, rows: Vec<Row>) -> crate::Result<Vec<OrgUser>> {
+        let mut users = rows.iter()
+            .map(|row| OrgUser {
+                user: User::from_row(&self.driver, row),
+                is_org_admin: row.get("org_admin").unwrap(),
+                scopes: Vec::new(),
             })
-            .collect::<crate::Result<Vec<_>>>()?;
+            .collect::<Vec<_>>();
+
+        if users.is_empty() {
+            return Ok(users);
+        }
+
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), self.id.clone().into()),
+        ];
+        let placeholders = users.iter()
+            .enumerate()
+            .map(|(idx, x)| {
+                params.push((format!("user_{idx}"), x.user.id.clone().into()));
+                format!(":user_{idx}")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let rows: Vec<Row> = conn.exec(format!("SELECT user_id,scope_name FROM org_user_link_scopes WHERE org_id = :org_id AND user_id IN ({placeholders}) \
+            UNION SELECT ur.user_id,rs.scope_name FROM org_role_scopes rs INNER JOIN org_user_roles ur ON ur.role_id = rs.role_id WHERE ur.org_id = :org_id AND ur.user_id IN ({placeholders})"), Params::from(params)).await?;
+
+        for row in rows {
+            let user_id: String = row.get("user_id").unwrap();
+            let scope_name: String = row.get("scope_name").unwrap();
+            let scope = OrgScope::from_str(&scope_name).map_err(|_| Error::UnknownEnumVariant)?;
+
+            if let Some(user) = users.iter_mut().find(|x| x.user.id.eq(&user_id)) {
+                user.scopes.push(scope);
+            }
+        }
+
         Ok(users)
     }
 
-    /// List the scopes a user has in the organization using the provided transaction
-    fn list_scopes_with_tx(&self, tx: &mut Transaction, user: &User<'_>) -> crate::Result<Vec<OrgScope>> {
-        let rows: Vec<Row> = tx.exec("SELECT scope_name FROM org_user_link_scopes WHERE org_id = :org_id AND user_id = :user_id", params! {
+    /// List the scopes a user has in the organization using the provided connection.
+    /// This is the union of the scopes granted directly and the scopes granted by the user's roles
+    async fn query_scopes(&self, conn: &mut impl Queryable, user: &User) -> crate::Result<Vec<OrgScope>> {
+        let rows: Vec<Row> = conn.exec("SELECT scope_name FROM org_user_link_scopes WHERE org_id = :org_id AND user_id = :user_id \
+            UNION SELECT rs.scope_name FROM org_role_scopes rs INNER JOIN org_user_roles ur ON ur.role_id = rs.role_id WHERE ur.org_id = :org_id AND ur.user_id = :user_id", params! {
             "org_id" => &self.id,
             "user_id" => &user.id,
-        })?;
+        }).await?;
+
+        Self::scopes_from_rows(rows)
+    }
 
+    fn scopes_from_rows(rows: Vec<Row>) -> crate::Result<Vec<OrgScope>> {
         let scopes = rows.into_iter()
             .map(|x| {
                 let scope_name: String = x.get("scope_name").unwrap();
@@ -265,11 +596,20 @@ impl<'a> Org<'a> {
         Ok(scopes)
     }
 
-    /// List all scopes a user has in the organization
-    pub fn list_scopes(&self, user: &User<'_>) -> crate::Result<Vec<OrgScope>> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
-        let scopes = self.list_scopes_with_tx(&mut tx, user)?;
-        tx.commit()?;
-        Ok(scopes)
+    /// List all scopes a user has in the organization, either granted directly or through a role
+    pub async fn list_scopes(&self, user: &User) -> crate::Result<Vec<OrgScope>> {
+        let mut conn = self.driver.get_conn().await?;
+        self.query_scopes(&mut conn, user).await
+    }
+
+    /// List the scopes granted directly to a user in the organization, excluding those granted through a role
+    pub async fn list_direct_scopes(&self, user: &User) -> crate::Result<Vec<OrgScope>> {
+        let mut conn = self.driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT scope_name FROM org_user_link_scopes WHERE org_id = :org_id AND user_id = :user_id", params! {
+            "org_id" => &self.id,
+            "user_id" => &user.id,
+        }).await?;
+
+        Self::scopes_from_rows(rows)
     }
 }
\ No newline at end of file
@@ -0,0 +1,297 @@
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, gen_id};
+use crate::entities::{Entity, Org, Product};
+
+/// A set of prices which deviate from the regular price of products.
+/// A price list only applies within its validity period
+#[derive(Debug, Clone)]
+pub struct PriceList {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    /// UNIX timestamp from which the price list applies. `None` if it applies from the beginning of time
+    pub valid_from: Option<i64>,
+    /// UNIX timestamp until which the price list applies. `None` if it applies indefinitely
+    pub valid_until: Option<i64>,
+    pub prices: Vec<TierPrice>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PriceListBuilder<'a> {
+    pub org: &'a Org,
+    pub name: String,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+}
+
+/// The price of a product when ordering at least `min_quantity` units.
+/// Volume discounts are expressed as multiple tiers for the same product
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierPrice {
+    pub product_id: String,
+    pub min_quantity: f32,
+    pub price_per_unit: f32,
+}
+
+impl Entity for PriceList {
+    type Information<'a> = PriceListBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let price_list = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(price_list)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the name and validity period. Prices are changed with [PriceList::set_prices]
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT org_id,name,valid_from,valid_until FROM price_lists WHERE id = :id", params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let prices = Self::list_prices(&mut conn, &id).await?;
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id,
+            org_id: row.get("org_id").unwrap(),
+            name: row.get("name").unwrap(),
+            valid_from: row.get("valid_from").unwrap(),
+            valid_until: row.get("valid_until").unwrap(),
+            prices,
+        }))
+    }
+}
+
+impl PriceList {
+    /// Create a price list using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: PriceListBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+
+        tx.exec_drop("INSERT INTO price_lists (id, org_id, name, valid_from, valid_until) VALUES (:id, :org_id, :name, :valid_from, :valid_until)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "name" => &builder.name,
+            "valid_from" => builder.valid_from,
+            "valid_until" => builder.valid_until,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            name: builder.name,
+            valid_from: builder.valid_from,
+            valid_until: builder.valid_until,
+            prices: Vec::new(),
+        })
+    }
+
+    /// Remove the price list using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM price_list_prices WHERE price_list_id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM price_lists WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the price list using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE price_lists SET name = :name, valid_from = :valid_from, valid_until = :valid_until WHERE id = :id", params! {
+            "name" => &self.name,
+            "valid_from" => self.valid_from,
+            "valid_until" => self.valid_until,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    async fn list_prices(conn: &mut impl Queryable, price_list_id: &str) -> crate::Result<Vec<TierPrice>> {
+        let rows: Vec<Row> = conn.exec("SELECT product_id,min_quantity,price_per_unit FROM price_list_prices WHERE price_list_id = :price_list_id ORDER BY product_id, min_quantity", params! {
+            "price_list_id" => price_list_id
+        }).await?;
+
+        let prices = rows.into_iter()
+            .map(|row| TierPrice {
+                product_id: row.get("product_id").unwrap(),
+                min_quantity: row.get("min_quantity").unwrap(),
+                price_per_unit: row.get("price_per_unit").unwrap(),
+            })
+            .collect();
+        Ok(prices)
+    }
+
+    /// Get a price list of the organization by its name. The prices are not loaded
+    pub async fn get_by_name(driver: &Driver, org: &Org, name: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT id,valid_from,valid_until FROM price_lists WHERE org_id = :org_id AND name = :name", params! {
+            "org_id" => &org.id,
+            "name" => name,
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: org.id.clone(),
+            name: name.to_string(),
+            valid_from: row.get("valid_from").unwrap(),
+            valid_until: row.get("valid_until").unwrap(),
+            prices: Vec::new(),
+        }))
+    }
+
+    /// List all price lists of the organization, including their prices
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,name,valid_from,valid_until FROM price_lists WHERE org_id = :org_id ORDER BY name", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let mut price_lists = rows.into_iter()
+            .map(|row| Self {
+                driver: driver.clone(),
+                id: row.get("id").unwrap(),
+                org_id: org.id.clone(),
+                name: row.get("name").unwrap(),
+                valid_from: row.get("valid_from").unwrap(),
+                valid_until: row.get("valid_until").unwrap(),
+                prices: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let rows: Vec<Row> = conn.exec("SELECT p.price_list_id,p.product_id,p.min_quantity,p.price_per_unit FROM price_list_prices p \
+            INNER JOIN price_lists l ON l.id = p.price_list_id WHERE l.org_id = :org_id ORDER BY p.product_id, p.min_quantity", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        for row in rows {
+            let price_list_id: String = row.get("price_list_id").unwrap();
+            if let Some(price_list) = price_lists.iter_mut().find(|x| x.id.eq(&price_list_id)) {
+                price_list.prices.push(TierPrice {
+                    product_id: row.get("product_id").unwrap(),
+                    min_quantity: row.get("min_quantity").unwrap(),
+                    price_per_unit: row.get("price_per_unit").unwrap(),
+                });
+            }
+        }
+
+        Ok(price_lists)
+    }
+
+    /// Replace the prices of a product in this price list.
+    /// An empty list of tiers removes the product from the price list
+    pub async fn set_prices(&mut self, product: &Product, tiers: Vec<TierPrice>) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.set_prices_with_tx(&mut tx, product, tiers).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace the prices of a product in this price list using the provided transaction
+    pub async fn set_prices_with_tx(&mut self, tx: &mut Transaction, product: &Product, mut tiers: Vec<TierPrice>) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM price_list_prices WHERE price_list_id = :price_list_id AND product_id = :product_id", params! {
+            "price_list_id" => &self.id,
+            "product_id" => &product.id,
+        }).await?;
+
+        for tier in &mut tiers {
+            tier.product_id = product.id.clone();
+            tx.exec_drop("INSERT INTO price_list_prices (price_list_id, product_id, min_quantity, price_per_unit) VALUES (:price_list_id, :product_id, :min_quantity, :price_per_unit)", params! {
+                "price_list_id" => &self.id,
+                "product_id" => &product.id,
+                "min_quantity" => tier.min_quantity,
+                "price_per_unit" => tier.price_per_unit,
+            }).await?;
+        }
+
+        self.prices.retain(|x| x.product_id.ne(&product.id));
+        self.prices.append(&mut tiers);
+        Ok(())
+    }
+
+    /// Whether the price list applies at the provided UNIX timestamp
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        is_within(self.valid_from, self.valid_until, timestamp)
+    }
+
+    /// The price per unit of a product in this price list when ordering `quantity` units.
+    /// Returns `None` if the price list does not apply at `timestamp`, or has no price for the product and quantity
+    pub fn price_for(&self, product_id: &str, quantity: f32, timestamp: i64) -> Option<f32> {
+        if !self.is_valid_at(timestamp) {
+            return None;
+        }
+
+        tier_price(&self.prices, product_id, quantity)
+    }
+}
+
+/// Whether `timestamp` lies within the period. Bounds set to `None` are unbounded
+fn is_within(from: Option<i64>, until: Option<i64>, timestamp: i64) -> bool {
+    from.map(|x| x <= timestamp).unwrap_or(true)
+        && until.map(|x| timestamp < x).unwrap_or(true)
+}
+
+/// The price of the tier with the highest minimum quantity not exceeding `quantity`
+fn tier_price(prices: &[TierPrice], product_id: &str, quantity: f32) -> Option<f32> {
+    prices.iter()
+        .filter(|x| x.product_id.eq(product_id) && x.min_quantity <= quantity)
+        .max_by(|a, b| a.min_quantity.total_cmp(&b.min_quantity))
+        .map(|x| x.price_per_unit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn highest_applicable_tier() {
+        let tier = |min_quantity, price_per_unit| TierPrice {
+            product_id: "p".to_string(),
+            min_quantity,
+            price_per_unit,
+        };
+        let prices = vec![tier(0.0, 10.0), tier(100.0, 7.5), tier(10.0, 9.0)];
+
+        assert_eq!(tier_price(&prices, "p", 1.0), Some(10.0));
+        assert_eq!(tier_price(&prices, "p", 10.0), Some(9.0));
+        assert_eq!(tier_price(&prices, "p", 250.0), Some(7.5));
+        assert_eq!(tier_price(&prices, "q", 250.0), None);
+    }
+
+    #[test]
+    fn validity_period() {
+        assert!(!is_within(Some(100), Some(200), 99));
+        assert!(is_within(Some(100), Some(200), 100));
+        assert!(!is_within(Some(100), Some(200), 200));
+        assert!(is_within(None, None, 0));
+    }
+}
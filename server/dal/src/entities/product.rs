@@ -1,11 +1,16 @@
-use mysql::prelude::Queryable;
-use mysql::{params, Row, Transaction, TxOpts};
-use crate::{Driver, gen_id};
-use crate::entities::{Entity, Org};
+use std::str::FromStr;
+use crate::driver::{Params, Queryable, Row, Transaction, Value};
+use crate::params;
+use crate::{Backend, Driver, Error, gen_id};
+use crate::entities::{Entity, Org, PriceList, ProductCategory, UnitCode};
+use crate::pagination::{Page, PageRequest, SortKey};
+use proc::{Stringify, Variants};
+
+const COLUMNS: &str = "id,name,description,org_id,product_code,price_per_unit,tax_percentage,unit,category_id,archived_at";
 
 #[derive(Debug, Clone)]
-pub struct Product<'a> {
-    driver: &'a Driver,
+pub struct Product {
+    driver: Driver,
     pub id: String,
     pub name: String,
     pub description: Option<String>,
@@ -13,39 +18,151 @@ pub struct Product<'a> {
     pub product_code: Option<String>,
     pub price_per_unit: f32,
     pub tax_percentage: Option<f32>,
+    pub unit: UnitCode,
+    pub category_id: Option<String>,
+    /// UNIX timestamp at which the product was archived, `None` if it is not archived.
+    /// Archived products are not listed, but can still be retrieved by their ID
+    pub archived_at: Option<i64>,
+}
+
+/// Fields products can be sorted on when listing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum ProductSort {
+    #[default]
+    Name,
+    PricePerUnit,
+}
+
+impl SortKey for ProductSort {
+    type Item = Product;
+    const ID_COLUMN: &'static str = "id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::PricePerUnit => "price_per_unit",
+        }
+    }
+
+    fn key(&self, item: &Product) -> Value {
+        match self {
+            Self::Name => item.name.clone().into(),
+            Self::PricePerUnit => item.price_per_unit.into(),
+        }
+    }
+
+    fn id(item: &Product) -> String {
+        item.id.clone()
+    }
+}
+
+/// Filters applied when listing products. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct ProductFilter {
+    /// Full-text search on the name, description and product code.
+    /// Products match if they contain words starting with every word in the search
+    pub search: Option<String>,
+    pub category_id: Option<String>,
+    pub min_price: Option<f32>,
+    pub max_price: Option<f32>,
+    /// Include archived products as well
+    pub include_archived: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct ProductBuilder<'a> {
     pub name: String,
-    pub org: &'a Org<'a>,
+    pub org: &'a Org,
+    pub product_code: Option<String>,
+    pub description: Option<String>,
+    pub price_per_unit: f32,
+    pub tax_percentage: Option<f32>,
+    pub unit: UnitCode,
+    pub category: Option<&'a ProductCategory>,
+}
+
+/// A product in a bulk import, see [Product::import]
+#[derive(Debug, Clone)]
+pub struct ProductImport {
+    /// If a product with this code exists in the organization, that product is updated
     pub product_code: Option<String>,
+    pub name: String,
     pub description: Option<String>,
     pub price_per_unit: f32,
     pub tax_percentage: Option<f32>,
+    pub unit: UnitCode,
+    pub category_id: Option<String>,
+}
+
+/// The outcome of importing a single product
+#[derive(Debug, Clone)]
+pub enum ImportedProduct {
+    Created(Product),
+    Updated {
+        original: Product,
+        product: Product,
+    },
 }
 
-impl<'a> Entity<'a> for Product<'a> {
-    type Information = ProductBuilder<'a>;
+impl Entity for Product {
+    type Information<'a> = ProductBuilder<'a>;
 
-    fn create(driver: &'a Driver, builder: Self::Information) -> crate::Result<Self> {
-        let mut tx = driver.start_transaction(TxOpts::default())?;
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let product = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(product)
+    }
+
+    /// Remove the product permanently.
+    /// Products which may be referenced elsewhere should be archived instead, see [Product::archive]
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first(format!("SELECT {COLUMNS} FROM products WHERE id = :id"), params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        Ok(Some(Self::from_row(driver, row)?))
+    }
+}
+
+impl Product {
+    /// Create a product using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: ProductBuilder<'_>) -> crate::Result<Self> {
         let id = gen_id();
 
-        tx.exec_drop("INSERT INTO products (id, org_id, name, product_code, description, price_per_unit, tax_percentage) VALUES (:id, :org_id, :name, :product_code, :description, :price_per_unit, :tax_percentage)", params! {
+        let category_id = builder.category.map(|x| x.id.clone());
+        tx.exec_drop("INSERT INTO products (id, org_id, name, product_code, description, price_per_unit, tax_percentage, unit, category_id) VALUES (:id, :org_id, :name, :product_code, :description, :price_per_unit, :tax_percentage, :unit, :category_id)", params! {
             "id" => &id,
             "org_id" => &builder.org.id,
             "name" => &builder.name,
             "product_code" => &builder.product_code,
             "description" => &builder.description,
             "price_per_unit" => builder.price_per_unit,
-            "tax_percentage" => builder.tax_percentage
-        })?;
-
-        tx.commit()?;
+            "tax_percentage" => builder.tax_percentage,
+            "unit" => builder.unit.to_string(),
+            "category_id" => &category_id,
+        }).await?;
 
         Ok(Self {
-            driver,
+            driver: driver.clone(),
             id,
             name: builder.name,
             description: builder.description,
@@ -53,72 +170,270 @@ impl<'a> Entity<'a> for Product<'a> {
             price_per_unit: builder.price_per_unit,
             org_id: builder.org.id.clone(),
             tax_percentage: builder.tax_percentage,
+            unit: builder.unit,
+            category_id,
+            archived_at: None,
         })
     }
 
-    fn remove(self) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    /// Remove the product using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
         tx.exec_drop("DELETE FROM products WHERE id = :id", params! {
             "id" => &self.id
-        })?;
-        tx.commit()?;
+        }).await?;
         Ok(())
     }
 
-    fn update(&mut self) -> crate::Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
-        tx.exec_drop("UPDATE products SET name = :name, description = :description, product_code = :product_code, :price_per_unit = :price_per_unit, :tax_percentage WHERE id = :id", params! {
+    /// Update the product using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE products SET name = :name, description = :description, product_code = :product_code, price_per_unit = :price_per_unit, tax_percentage = :tax_percentage, unit = :unit, category_id = :category_id, archived_at = :archived_at WHERE id = :id", params! {
             "name" => &self.name,
             "description" => &self.description,
             "product_code" => &self.product_code,
             "price_per_unit" => &self.price_per_unit,
             "tax_percentage" => &self.tax_percentage,
+            "unit" => self.unit.to_string(),
+            "category_id" => &self.category_id,
+            "archived_at" => &self.archived_at,
             "id" => &self.id
-        })?;
-        tx.commit()?;
+        }).await?;
         Ok(())
     }
 
-    fn get(driver: &'a Driver, id: String) -> crate::Result<Option<Self>> {
-        let mut tx = driver.start_transaction(TxOpts::default())?;
-        let res = Self::get_with_tx(&mut tx, driver, id)?;
-        tx.commit()?;
-        Ok(res)
-    }
-}
+    /// Create a product from a row containing [COLUMNS]
+    fn from_row(driver: &Driver, row: Row) -> crate::Result<Self> {
+        let unit: String = row.get("unit").unwrap();
 
-impl<'a> Product<'a> {
-    fn get_with_tx(tx: &mut Transaction, driver: &'a Driver, id: String) -> crate::Result<Option<Self>> {
-        let row: Row = match tx.exec_first("SELECT name,description,org_id,product_code,price_per_unit,tax_percentage FROM products WHERE id = :id", params! {
-            "id" => &id
-        })? {
-            Some(x) => x,
-            None => return Ok(None)
-        };
-
-        Ok(Some(Self {
-            driver,
-            id,
+        Ok(Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
             name: row.get("name").unwrap(),
             description: row.get("description").unwrap(),
             product_code: row.get("product_code").unwrap(),
             org_id: row.get("org_id").unwrap(),
             price_per_unit: row.get("price_per_unit").unwrap(),
             tax_percentage: row.get("tax_percentage").unwrap(),
-        }))
+            unit: UnitCode::from_str(&unit).map_err(|_| Error::UnknownEnumVariant)?,
+            category_id: row.get("category_id").unwrap(),
+            archived_at: row.get("archived_at").unwrap(),
+        })
+    }
+
+    /// Get a product of the organization by its product code. Archived products are included
+    pub async fn get_by_code(driver: &Driver, org: &Org, product_code: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM products WHERE org_id = :org_id AND product_code = :product_code"), params! {
+            "org_id" => &org.id,
+            "product_code" => product_code,
+        }).await?;
+
+        row.map(|row| Self::from_row(driver, row)).transpose()
+    }
+
+    /// The price per unit when ordering `quantity` units at the provided UNIX timestamp.
+    /// If the price list applies and has a price for the product, that price is used. Otherwise the regular price is used
+    pub fn unit_price(&self, price_list: Option<&PriceList>, quantity: f32, timestamp: i64) -> f32 {
+        price_list.and_then(|x| x.price_for(&self.id, quantity, timestamp))
+            .unwrap_or(self.price_per_unit)
+    }
+
+    /// Archive the product, hiding it from listings
+    pub async fn archive(&mut self) -> crate::Result<()> {
+        self.archived_at = Some(time::OffsetDateTime::now_utc().unix_timestamp());
+        self.update().await
+    }
+
+    /// Archive the product using the provided transaction
+    pub async fn archive_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        self.archived_at = Some(time::OffsetDateTime::now_utc().unix_timestamp());
+        self.update_with_tx(tx).await
+    }
+
+    /// Restore an archived product
+    pub async fn unarchive(&mut self) -> crate::Result<()> {
+        self.archived_at = None;
+        self.update().await
+    }
+
+    /// Restore an archived product using the provided transaction
+    pub async fn unarchive_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        self.archived_at = None;
+        self.update_with_tx(tx).await
+    }
+
+    /// Import products into the organization in a single transaction.
+    /// Products with a product code already in use update that product, all others are created.
+    /// If any product fails, nothing is imported. With `dry_run` the transaction is always rolled back,
+    /// so the outcome can be inspected without changing anything
+    pub async fn import(driver: &Driver, org: &Org, products: Vec<ProductImport>, dry_run: bool) -> crate::Result<Vec<ImportedProduct>> {
+        let mut tx = driver.start_transaction().await?;
+        let imported = Self::import_with_tx(&mut tx, driver, org, products).await?;
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Import products using the provided transaction, see [Self::import]
+    pub async fn import_with_tx(tx: &mut Transaction, driver: &Driver, org: &Org, products: Vec<ProductImport>) -> crate::Result<Vec<ImportedProduct>> {
+        let mut imported = Vec::with_capacity(products.len());
+
+        for import in products {
+            let existing: Option<Row> = match &import.product_code {
+                Some(product_code) => tx.exec_first(format!("SELECT {COLUMNS} FROM products WHERE org_id = :org_id AND product_code = :product_code"), params! {
+                    "org_id" => &org.id,
+                    "product_code" => product_code,
+                }).await?,
+                None => None,
+            };
+
+            match existing {
+                Some(row) => {
+                    let original = Self::from_row(driver, row)?;
+                    let product = Self {
+                        name: import.name,
+                        description: import.description,
+                        price_per_unit: import.price_per_unit,
+                        tax_percentage: import.tax_percentage,
+                        unit: import.unit,
+                        category_id: import.category_id,
+                        ..original.clone()
+                    };
+
+                    tx.exec_drop("UPDATE products SET name = :name, description = :description, price_per_unit = :price_per_unit, tax_percentage = :tax_percentage, unit = :unit, category_id = :category_id WHERE id = :id", params! {
+                        "name" => &product.name,
+                        "description" => &product.description,
+                        "price_per_unit" => product.price_per_unit,
+                        "tax_percentage" => product.tax_percentage,
+                        "unit" => product.unit.to_string(),
+                        "category_id" => &product.category_id,
+                        "id" => &product.id,
+                    }).await?;
+
+                    imported.push(ImportedProduct::Updated {
+                        original,
+                        product,
+                    });
+                },
+                None => {
+                    let id = gen_id();
+                    tx.exec_drop("INSERT INTO products (id, org_id, name, product_code, description, price_per_unit, tax_percentage, unit, category_id) VALUES (:id, :org_id, :name, :product_code, :description, :price_per_unit, :tax_percentage, :unit, :category_id)", params! {
+                        "id" => &id,
+                        "org_id" => &org.id,
+                        "name" => &import.name,
+                        "product_code" => &import.product_code,
+                        "description" => &import.description,
+                        "price_per_unit" => import.price_per_unit,
+                        "tax_percentage" => import.tax_percentage,
+                        "unit" => import.unit.to_string(),
+                        "category_id" => &import.category_id,
+                    }).await?;
+
+                    imported.push(ImportedProduct::Created(Self {
+                        driver: driver.clone(),
+                        id,
+                        name: import.name,
+                        description: import.description,
+                        org_id: org.id.clone(),
+                        product_code: import.product_code,
+                        price_per_unit: import.price_per_unit,
+                        tax_percentage: import.tax_percentage,
+                        unit: import.unit,
+                        category_id: import.category_id,
+                        archived_at: None,
+                    }));
+                }
+            }
+        }
+
+        Ok(imported)
     }
 
-    pub fn list_for_org(driver: &'a Driver, org: &Org<'a>) -> crate::Result<Vec<Self>> {
-        let mut tx = driver.start_transaction(TxOpts::default())?;
-        let rows: Vec<Row> = tx.exec("SELECT id FROM orgs WHERE org_id = :org_id", params! {
-            "org_id" => &org.id
-        })?;
+    /// List a page of the products of an organization
+    pub async fn list_for_org(driver: &Driver, org: &Org, filter: &ProductFilter, page: &PageRequest<ProductSort>) -> crate::Result<Page<Self>> {
+        let mut query = format!("SELECT {COLUMNS} FROM products WHERE org_id = :org_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), org.id.clone().into()),
+        ];
+
+        if !filter.include_archived {
+            query.push_str(" AND archived_at IS NULL");
+        }
+
+        if let Some(search) = filter.search.as_deref().and_then(|x| search_query(driver.backend(), x)) {
+            query.push_str(match driver.backend() {
+                Backend::Mysql => " AND MATCH (name, description, product_code) AGAINST (:search IN BOOLEAN MODE)",
+                // Must match the expression of the products_search index
+                Backend::Postgres => " AND to_tsvector('simple', name || ' ' || COALESCE(description, '') || ' ' || COALESCE(product_code, '')) @@ to_tsquery('simple', :search)",
+                Backend::Sqlite => " AND rowid IN (SELECT rowid FROM products_search WHERE products_search MATCH :search)",
+            });
+            params.push(("search".to_string(), search.into()));
+        }
+
+        if let Some(category_id) = &filter.category_id {
+            query.push_str(" AND category_id = :category_id");
+            params.push(("category_id".to_string(), category_id.into()));
+        }
+
+        if let Some(min_price) = filter.min_price {
+            query.push_str(" AND price_per_unit >= :min_price");
+            params.push(("min_price".to_string(), min_price.into()));
+        }
+
+        if let Some(max_price) = filter.max_price {
+            query.push_str(" AND price_per_unit <= :max_price");
+            params.push(("max_price".to_string(), max_price.into()));
+        }
+
+        page.apply(&mut query, &mut params);
 
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
         let products = rows.into_iter()
-            .map(|row| Ok(Self::get_with_tx(&mut tx, driver, row.get("id").unwrap())?.unwrap()))
+            .map(|row| Self::from_row(driver, row))
             .collect::<crate::Result<Vec<_>>>()?;
 
-        tx.commit()?;
-        Ok(products)
+        page.finish(products)
     }
-}
\ No newline at end of file
+}
+
+/// Convert a search entered by a user into a full-text query for the backend, matching all words as prefix.
+/// Only letters and digits are kept, so the search cannot contain operators of the backend's query syntax.
+/// Returns `None` if the search contains no words
+fn search_query(backend: Backend, search: &str) -> Option<String> {
+    let words = search.split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| match backend {
+            Backend::Mysql => format!("+{x}*"),
+            Backend::Postgres => format!("{x}:*"),
+            Backend::Sqlite => format!("\"{x}\"*"),
+        })
+        .collect::<Vec<_>>();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    Some(words.join(match backend {
+        Backend::Postgres => " & ",
+        Backend::Mysql | Backend::Sqlite => " ",
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::search_query;
+    use crate::Backend;
+
+    #[test]
+    fn search_query_strips_operators() {
+        assert_eq!(search_query(Backend::Mysql, "red -widget"), Some("+red* +widget*".to_string()));
+        assert_eq!(search_query(Backend::Postgres, "red & widget"), Some("red:* & widget:*".to_string()));
+        assert_eq!(search_query(Backend::Sqlite, "\"red\" OR"), Some("\"red\"* \"OR\"*".to_string()));
+        assert_eq!(search_query(Backend::Sqlite, "*-"), None);
+    }
+}
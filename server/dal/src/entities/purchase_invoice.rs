@@ -0,0 +1,377 @@
+use crate::driver::{Params, Queryable, Row, Transaction, Value};
+use crate::params;
+use crate::{Driver, Error, gen_id};
+use crate::entities::{Entity, Org, Supplier, SupplierBuilder};
+use crate::pagination::{Page, PageRequest, SortKey};
+use proc::{Stringify, Variants};
+
+const COLUMNS: &str = "id,org_id,supplier_id,invoice_number,issue_date,due_date,currency,net_amount,vat_amount,deductible_vat_amount,description,created_at";
+
+/// An invoice an organization received from one of its suppliers
+#[derive(Debug, Clone)]
+pub struct PurchaseInvoice {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub supplier_id: String,
+    /// The number the supplier gave the invoice, unique per supplier
+    pub invoice_number: String,
+    /// UNIX timestamp of the day the invoice was issued, in UTC
+    pub issue_date: i64,
+    pub due_date: Option<i64>,
+    /// ISO 4217 currency code
+    pub currency: String,
+    pub net_amount: f32,
+    pub vat_amount: f32,
+    /// The part of `vat_amount` which can be deducted in the VAT return
+    pub deductible_vat_amount: f32,
+    pub description: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PurchaseInvoiceBuilder<'a> {
+    pub org: &'a Org,
+    pub supplier: &'a Supplier,
+    pub details: PurchaseInvoiceDetails,
+}
+
+/// The details of a purchase invoice, as written on the invoice
+#[derive(Debug, Clone)]
+pub struct PurchaseInvoiceDetails {
+    pub invoice_number: String,
+    pub issue_date: i64,
+    pub due_date: Option<i64>,
+    pub currency: String,
+    pub net_amount: f32,
+    pub vat_amount: f32,
+    pub deductible_vat_amount: f32,
+    pub description: Option<String>,
+}
+
+/// The supplier of an imported purchase invoice
+#[derive(Debug, Clone)]
+pub struct SupplierDetails {
+    pub name: String,
+    pub vat_number: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+}
+
+/// The result of [PurchaseInvoice::import]
+#[derive(Debug, Clone)]
+pub struct ImportedPurchaseInvoice {
+    pub supplier: Supplier,
+    /// Whether the supplier did not exist yet and was created by the import
+    pub supplier_created: bool,
+    pub invoice: PurchaseInvoice,
+}
+
+/// Fields purchase invoices can be sorted on when listing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum PurchaseInvoiceSort {
+    #[default]
+    IssueDate,
+    CreatedAt,
+}
+
+impl SortKey for PurchaseInvoiceSort {
+    type Item = PurchaseInvoice;
+    const ID_COLUMN: &'static str = "id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::IssueDate => "issue_date",
+            Self::CreatedAt => "created_at",
+        }
+    }
+
+    fn key(&self, item: &PurchaseInvoice) -> Value {
+        match self {
+            Self::IssueDate => item.issue_date.into(),
+            Self::CreatedAt => item.created_at.into(),
+        }
+    }
+
+    fn id(item: &PurchaseInvoice) -> String {
+        item.id.clone()
+    }
+}
+
+/// Filters applied when listing purchase invoices. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct PurchaseInvoiceFilter {
+    pub supplier_id: Option<String>,
+    /// Only invoices issued on this day or later
+    pub from: Option<i64>,
+    /// Only invoices issued on this day or earlier
+    pub until: Option<i64>,
+}
+
+/// The totals of the purchase invoices of an organization in a single currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurchaseVatTotals {
+    pub currency: String,
+    pub invoice_count: u32,
+    pub net_amount: f64,
+    pub vat_amount: f64,
+    pub deductible_vat_amount: f64,
+}
+
+impl Entity for PurchaseInvoice {
+    type Information<'a> = PurchaseInvoiceBuilder<'a>;
+
+    /// Create the purchase invoice. Fails with [Error::DuplicatePurchaseInvoice] if the supplier
+    /// already has an invoice with the same number
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let invoice = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+
+        Ok(invoice)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the invoice. The supplier cannot be changed
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM purchase_invoices WHERE id = :id"), params! {
+            "id" => &id
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
+
+impl PurchaseInvoice {
+    /// Remove the purchase invoice using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM purchase_invoices WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the purchase invoice using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        if let Some(existing) = Self::find_by_number(tx, &self.driver, &self.org_id, &self.supplier_id, &self.invoice_number).await? {
+            if existing.id != self.id {
+                return Err(Error::DuplicatePurchaseInvoice);
+            }
+        }
+
+        tx.exec_drop("UPDATE purchase_invoices SET invoice_number = :invoice_number, issue_date = :issue_date, due_date = :due_date, currency = :currency, \
+            net_amount = :net_amount, vat_amount = :vat_amount, deductible_vat_amount = :deductible_vat_amount, description = :description WHERE id = :id", params! {
+            "invoice_number" => &self.invoice_number,
+            "issue_date" => self.issue_date,
+            "due_date" => self.due_date,
+            "currency" => &self.currency,
+            "net_amount" => self.net_amount,
+            "vat_amount" => self.vat_amount,
+            "deductible_vat_amount" => self.deductible_vat_amount,
+            "description" => &self.description,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    fn from_row(driver: &Driver, row: Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            supplier_id: row.get("supplier_id").unwrap(),
+            invoice_number: row.get("invoice_number").unwrap(),
+            issue_date: row.get("issue_date").unwrap(),
+            due_date: row.get("due_date").unwrap(),
+            currency: row.get("currency").unwrap(),
+            net_amount: row.get("net_amount").unwrap(),
+            vat_amount: row.get("vat_amount").unwrap(),
+            deductible_vat_amount: row.get("deductible_vat_amount").unwrap(),
+            description: row.get("description").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+        }
+    }
+
+    async fn find_by_number(conn: &mut impl Queryable, driver: &Driver, org_id: &str, supplier_id: &str, invoice_number: &str) -> crate::Result<Option<Self>> {
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM purchase_invoices \
+            WHERE org_id = :org_id AND supplier_id = :supplier_id AND invoice_number = :invoice_number"), params! {
+            "org_id" => org_id,
+            "supplier_id" => supplier_id,
+            "invoice_number" => invoice_number,
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+
+    /// Create a purchase invoice using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: PurchaseInvoiceBuilder<'_>) -> crate::Result<Self> {
+        Self::insert(tx, driver, &builder.org.id, &builder.supplier.id, builder.details).await
+    }
+
+    async fn insert(tx: &mut impl Queryable, driver: &Driver, org_id: &str, supplier_id: &str, details: PurchaseInvoiceDetails) -> crate::Result<Self> {
+        if Self::find_by_number(tx, driver, org_id, supplier_id, &details.invoice_number).await?.is_some() {
+            return Err(Error::DuplicatePurchaseInvoice);
+        }
+
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        tx.exec_drop("INSERT INTO purchase_invoices (id, org_id, supplier_id, invoice_number, issue_date, due_date, currency, net_amount, vat_amount, deductible_vat_amount, description, created_at) \
+            VALUES (:id, :org_id, :supplier_id, :invoice_number, :issue_date, :due_date, :currency, :net_amount, :vat_amount, :deductible_vat_amount, :description, :created_at)", params! {
+            "id" => &id,
+            "org_id" => org_id,
+            "supplier_id" => supplier_id,
+            "invoice_number" => &details.invoice_number,
+            "issue_date" => details.issue_date,
+            "due_date" => details.due_date,
+            "currency" => &details.currency,
+            "net_amount" => details.net_amount,
+            "vat_amount" => details.vat_amount,
+            "deductible_vat_amount" => details.deductible_vat_amount,
+            "description" => &details.description,
+            "created_at" => created_at,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: org_id.to_string(),
+            supplier_id: supplier_id.to_string(),
+            invoice_number: details.invoice_number,
+            issue_date: details.issue_date,
+            due_date: details.due_date,
+            currency: details.currency,
+            net_amount: details.net_amount,
+            vat_amount: details.vat_amount,
+            deductible_vat_amount: details.deductible_vat_amount,
+            description: details.description,
+            created_at,
+        })
+    }
+
+    /// List the purchase invoices of the organization
+    pub async fn list_for_org(driver: &Driver, org: &Org, filter: &PurchaseInvoiceFilter, page: &PageRequest<PurchaseInvoiceSort>) -> crate::Result<Page<Self>> {
+        let mut query = format!("SELECT {COLUMNS} FROM purchase_invoices WHERE org_id = :org_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), org.id.clone().into()),
+        ];
+
+        if let Some(supplier_id) = &filter.supplier_id {
+            query.push_str(" AND supplier_id = :supplier_id");
+            params.push(("supplier_id".to_string(), supplier_id.into()));
+        }
+
+        if let Some(from) = filter.from {
+            query.push_str(" AND issue_date >= :from");
+            params.push(("from".to_string(), from.into()));
+        }
+
+        if let Some(until) = filter.until {
+            query.push_str(" AND issue_date <= :until");
+            params.push(("until".to_string(), until.into()));
+        }
+
+        page.apply(&mut query, &mut params);
+
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
+        let invoices = rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect::<Vec<_>>();
+
+        page.finish(invoices)
+    }
+
+    /// Whether any purchase invoices of the supplier exist
+    pub async fn exists_for_supplier(driver: &Driver, supplier: &Supplier) -> crate::Result<bool> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT id FROM purchase_invoices WHERE supplier_id = :supplier_id LIMIT 1", params! {
+            "supplier_id" => &supplier.id
+        }).await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Sum the purchase invoices of the organization issued between `from` and `until`, both inclusive,
+    /// per currency. The deductible VAT amounts are the input VAT of the VAT return
+    pub async fn vat_totals(driver: &Driver, org: &Org, from: i64, until: i64) -> crate::Result<Vec<PurchaseVatTotals>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT currency, COUNT(*) AS invoice_count, COALESCE(SUM(net_amount), 0.0) AS net_amount, \
+            COALESCE(SUM(vat_amount), 0.0) AS vat_amount, COALESCE(SUM(deductible_vat_amount), 0.0) AS deductible_vat_amount \
+            FROM purchase_invoices WHERE org_id = :org_id AND issue_date >= :from AND issue_date <= :until GROUP BY currency ORDER BY currency", params! {
+            "org_id" => &org.id,
+            "from" => from,
+            "until" => until,
+        }).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| PurchaseVatTotals {
+                currency: row.get("currency").unwrap(),
+                invoice_count: row.get::<i64, _>("invoice_count").unwrap() as u32,
+                net_amount: row.get("net_amount").unwrap(),
+                vat_amount: row.get("vat_amount").unwrap(),
+                deductible_vat_amount: row.get("deductible_vat_amount").unwrap(),
+            })
+            .collect())
+    }
+
+    /// Record a purchase invoice together with its supplier, e.g. from an e-invoice.
+    /// The supplier is looked up by VAT number, or by name if it has none, and created if it does not exist yet.
+    /// With `dry_run`, nothing is stored
+    pub async fn import(driver: &Driver, org: &Org, supplier: SupplierDetails, details: PurchaseInvoiceDetails, dry_run: bool) -> crate::Result<ImportedPurchaseInvoice> {
+        let mut tx = driver.start_transaction().await?;
+        let imported = Self::import_with_tx(&mut tx, driver, org, supplier, details).await?;
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Import a purchase invoice using the provided transaction, see [Self::import]
+    pub async fn import_with_tx(tx: &mut Transaction, driver: &Driver, org: &Org, supplier: SupplierDetails, details: PurchaseInvoiceDetails) -> crate::Result<ImportedPurchaseInvoice> {
+        let existing = match &supplier.vat_number {
+            Some(vat_number) => Supplier::find(tx, driver, &org.id, "vat_number", vat_number).await?,
+            None => Supplier::find(tx, driver, &org.id, "name", &supplier.name).await?,
+        };
+
+        let supplier_created = existing.is_none();
+        let supplier = match existing {
+            Some(supplier) => supplier,
+            None => Supplier::create_with_tx(tx, driver, SupplierBuilder {
+                org,
+                name: supplier.name,
+                vat_number: supplier.vat_number,
+                email: supplier.email,
+                address: supplier.address,
+            }).await?,
+        };
+
+        let invoice = Self::insert(tx, driver, &org.id, &supplier.id, details).await?;
+
+        Ok(ImportedPurchaseInvoice {
+            supplier,
+            supplier_created,
+            invoice,
+        })
+    }
+}
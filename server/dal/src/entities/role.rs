@@ -0,0 +1,245 @@
+use std::str::FromStr;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, Error, gen_id};
+use crate::entities::{Entity, Org, OrgScope, User};
+
+/// A named set of scopes within an organization.
+/// Users assigned a role are granted all of its scopes, in addition to their directly granted scopes
+#[derive(Debug, Clone)]
+pub struct OrgRole {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    pub scopes: Vec<OrgScope>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrgRoleBuilder<'a> {
+    pub org: &'a Org,
+    pub name: String,
+    pub scopes: Vec<OrgScope>,
+}
+
+impl Entity for OrgRole {
+    type Information<'a> = OrgRoleBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let role = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(role)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT org_id,name FROM org_roles WHERE id = :id", params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let scopes = Self::list_scopes(&mut conn, &id).await?;
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id,
+            org_id: row.get("org_id").unwrap(),
+            name: row.get("name").unwrap(),
+            scopes,
+        }))
+    }
+}
+
+impl OrgRole {
+    /// Create a role using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: OrgRoleBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+
+        tx.exec_drop("INSERT INTO org_roles (id, org_id, name) VALUES (:id, :org_id, :name)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "name" => &builder.name,
+        }).await?;
+
+        Self::insert_scopes_with_tx(tx, &id, &builder.scopes).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            name: builder.name,
+            scopes: builder.scopes,
+        })
+    }
+
+    /// Remove the role using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM org_user_roles WHERE role_id = :role_id", params! {
+            "role_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM org_role_scopes WHERE role_id = :role_id", params! {
+            "role_id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM org_roles WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the role using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE org_roles SET name = :name WHERE id = :id", params! {
+            "name" => &self.name,
+            "id" => &self.id
+        }).await?;
+
+        tx.exec_drop("DELETE FROM org_role_scopes WHERE role_id = :role_id", params! {
+            "role_id" => &self.id
+        }).await?;
+
+        Self::insert_scopes_with_tx(tx, &self.id, &self.scopes).await?;
+        Ok(())
+    }
+
+    async fn insert_scopes_with_tx(tx: &mut Transaction, role_id: &str, scopes: &[OrgScope]) -> crate::Result<()> {
+        for scope in scopes {
+            tx.exec_drop("INSERT INTO org_role_scopes (role_id, scope_name) VALUES (:role_id, :scope_name)", params! {
+                "role_id" => role_id,
+                "scope_name" => scope.to_string(),
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_scopes(conn: &mut impl Queryable, role_id: &str) -> crate::Result<Vec<OrgScope>> {
+        let rows: Vec<Row> = conn.exec("SELECT scope_name FROM org_role_scopes WHERE role_id = :role_id", params! {
+            "role_id" => role_id
+        }).await?;
+
+        let scopes = rows.into_iter()
+            .map(|x| {
+                let scope_name: String = x.get("scope_name").unwrap();
+                OrgScope::from_str(&scope_name).map_err(|_| Error::UnknownEnumVariant)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(scopes)
+    }
+
+    /// Get a role of the organization by its name
+    pub async fn get_by_name(driver: &Driver, org: &Org, name: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,name FROM org_roles WHERE org_id = :org_id AND name = :name", params! {
+            "org_id" => &org.id,
+            "name" => name,
+        }).await?;
+
+        Ok(Self::from_rows(&mut conn, driver, &org.id, rows).await?.pop())
+    }
+
+    /// List all roles defined in the organization
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,name FROM org_roles WHERE org_id = :org_id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        Self::from_rows(&mut conn, driver, &org.id, rows).await
+    }
+
+    /// List all roles assigned to the user in the organization
+    pub async fn list_for_user(driver: &Driver, org: &Org, user: &User) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT r.id,r.name FROM org_roles r INNER JOIN org_user_roles ur ON ur.role_id = r.id WHERE ur.org_id = :org_id AND ur.user_id = :user_id", params! {
+            "org_id" => &org.id,
+            "user_id" => &user.id,
+        }).await?;
+
+        Self::from_rows(&mut conn, driver, &org.id, rows).await
+    }
+
+    async fn from_rows(conn: &mut impl Queryable, driver: &Driver, org_id: &str, rows: Vec<Row>) -> crate::Result<Vec<Self>> {
+        let mut roles = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: String = row.get("id").unwrap();
+            let scopes = Self::list_scopes(conn, &id).await?;
+            roles.push(Self {
+                driver: driver.clone(),
+                id,
+                org_id: org_id.to_string(),
+                name: row.get("name").unwrap(),
+                scopes,
+            });
+        }
+
+        Ok(roles)
+    }
+
+    /// Assign the role to a user. The user must be part of the role's organization
+    pub async fn assign(&self, user: &User) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.assign_with_tx(&mut tx, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Assign the role to a user using the provided transaction
+    pub async fn assign_with_tx(&self, tx: &mut Transaction, user: &User) -> crate::Result<()> {
+        tx.exec_drop("INSERT INTO org_user_roles (org_id, user_id, role_id) VALUES (:org_id, :user_id, :role_id)", params! {
+            "org_id" => &self.org_id,
+            "user_id" => &user.id,
+            "role_id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Remove the role from a user
+    pub async fn unassign(&self, user: &User) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.unassign_with_tx(&mut tx, user).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove the role from a user using the provided transaction
+    pub async fn unassign_with_tx(&self, tx: &mut Transaction, user: &User) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM org_user_roles WHERE user_id = :user_id AND role_id = :role_id", params! {
+            "user_id" => &user.id,
+            "role_id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Check whether the role is assigned to the user
+    pub async fn is_assigned(&self, user: &User) -> crate::Result<bool> {
+        let mut conn = self.driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT role_id FROM org_user_roles WHERE user_id = :user_id AND role_id = :role_id", params! {
+            "user_id" => &user.id,
+            "role_id" => &self.id,
+        }).await?;
+
+        Ok(row.is_some())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,157 @@
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, gen_id};
+use crate::entities::{Entity, Org};
+
+const COLUMNS: &str = "id,org_id,name,vat_number,email,address,created_at";
+
+/// A supplier an organization receives purchase invoices from
+#[derive(Debug, Clone)]
+pub struct Supplier {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    pub vat_number: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SupplierBuilder<'a> {
+    pub org: &'a Org,
+    pub name: String,
+    pub vat_number: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+}
+
+impl Entity for Supplier {
+    type Information<'a> = SupplierBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let supplier = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+
+        Ok(supplier)
+    }
+
+    /// Remove the supplier. Callers should make sure no purchase invoices of the supplier exist
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM suppliers WHERE id = :id"), params! {
+            "id" => &id
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
+
+impl Supplier {
+    /// Remove the supplier using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM suppliers WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the supplier using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE suppliers SET name = :name, vat_number = :vat_number, email = :email, address = :address WHERE id = :id", params! {
+            "name" => &self.name,
+            "vat_number" => &self.vat_number,
+            "email" => &self.email,
+            "address" => &self.address,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+
+    fn from_row(driver: &Driver, row: Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            name: row.get("name").unwrap(),
+            vat_number: row.get("vat_number").unwrap(),
+            email: row.get("email").unwrap(),
+            address: row.get("address").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+        }
+    }
+
+    /// Create a supplier using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: SupplierBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        tx.exec_drop("INSERT INTO suppliers (id, org_id, name, vat_number, email, address, created_at) \
+            VALUES (:id, :org_id, :name, :vat_number, :email, :address, :created_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "name" => &builder.name,
+            "vat_number" => &builder.vat_number,
+            "email" => &builder.email,
+            "address" => &builder.address,
+            "created_at" => created_at,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            name: builder.name,
+            vat_number: builder.vat_number,
+            email: builder.email,
+            address: builder.address,
+            created_at,
+        })
+    }
+
+    /// List all suppliers of the organization, ordered by name
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(format!("SELECT {COLUMNS} FROM suppliers WHERE org_id = :org_id ORDER BY name, id"), params! {
+            "org_id" => &org.id
+        }).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect())
+    }
+
+    /// Get a supplier of the organization by its VAT number
+    pub async fn get_by_vat_number(driver: &Driver, org: &Org, vat_number: &str) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        Self::find(&mut conn, driver, &org.id, "vat_number", vat_number).await
+    }
+
+    /// Find a supplier of the organization by the value of a column
+    pub(crate) async fn find(conn: &mut impl Queryable, driver: &Driver, org_id: &str, column: &str, value: &str) -> crate::Result<Option<Self>> {
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM suppliers WHERE org_id = :org_id AND {column} = :value ORDER BY created_at, id"), params! {
+            "org_id" => org_id,
+            "value" => value,
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
@@ -0,0 +1,169 @@
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
+use crate::{Driver, gen_id};
+use crate::entities::{Entity, Org};
+
+/// A template invoice documents of an organization are rendered with.
+/// The DAL stores the source as-is, parsing and rendering it is up to the caller
+#[derive(Debug, Clone)]
+pub struct InvoiceTemplate {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    pub content: String,
+    /// Whether invoices of the organization are rendered with this template.
+    /// At most one template of an organization is the default
+    pub is_default: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct InvoiceTemplateBuilder<'a> {
+    pub org: &'a Org,
+    pub name: String,
+    pub content: String,
+    pub is_default: bool,
+}
+
+impl Entity for InvoiceTemplate {
+    type Information<'a> = InvoiceTemplateBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let template = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(template)
+    }
+
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the template. If it is made the default, the previous default of the organization no longer is
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT id,org_id,name,content,is_default,created_at,updated_at FROM invoice_templates WHERE id = :id", params! {
+            "id" => &id
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
+
+impl InvoiceTemplate {
+    /// Create a template using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: InvoiceTemplateBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        if builder.is_default {
+            Self::clear_default(tx, &builder.org.id).await?;
+        }
+
+        tx.exec_drop("INSERT INTO invoice_templates (id, org_id, name, content, is_default, created_at, updated_at) \
+            VALUES (:id, :org_id, :name, :content, :is_default, :created_at, :updated_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "name" => &builder.name,
+            "content" => &builder.content,
+            "is_default" => builder.is_default,
+            "created_at" => created_at,
+            "updated_at" => created_at,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            name: builder.name,
+            content: builder.content,
+            is_default: builder.is_default,
+            created_at,
+            updated_at: created_at,
+        })
+    }
+
+    /// Remove the template using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM invoice_templates WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the template using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        let updated_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        if self.is_default {
+            Self::clear_default(tx, &self.org_id).await?;
+        }
+
+        tx.exec_drop("UPDATE invoice_templates SET name = :name, content = :content, is_default = :is_default, updated_at = :updated_at WHERE id = :id", params! {
+            "name" => &self.name,
+            "content" => &self.content,
+            "is_default" => self.is_default,
+            "updated_at" => updated_at,
+            "id" => &self.id,
+        }).await?;
+
+        self.updated_at = updated_at;
+        Ok(())
+    }
+
+    fn from_row(driver: &Driver, row: Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            name: row.get("name").unwrap(),
+            content: row.get("content").unwrap(),
+            is_default: row.get("is_default").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+            updated_at: row.get("updated_at").unwrap(),
+        }
+    }
+
+    async fn clear_default(tx: &mut Transaction, org_id: &str) -> crate::Result<()> {
+        tx.exec_drop("UPDATE invoice_templates SET is_default = false WHERE org_id = :org_id", params! {
+            "org_id" => org_id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// List all templates of the organization, ordered by name
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,org_id,name,content,is_default,created_at,updated_at FROM invoice_templates WHERE org_id = :org_id ORDER BY name, id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect())
+    }
+
+    /// Get the default template of the organization, if it has one
+    pub async fn get_default(driver: &Driver, org: &Org) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first("SELECT id,org_id,name,content,is_default,created_at,updated_at FROM invoice_templates WHERE org_id = :org_id AND is_default = true", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
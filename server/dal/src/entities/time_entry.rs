@@ -0,0 +1,448 @@
+use crate::driver::{Params, Queryable, Row, Transaction, Value};
+use crate::params;
+use crate::{Driver, Error, gen_id};
+use crate::entities::{Entity, Org, User};
+use crate::pagination::{Page, PageRequest, SortKey};
+use proc::{Stringify, Variants};
+
+const COLUMNS: &str = "id,org_id,user_id,project,work_date,duration_minutes,description,hourly_rate,billed_at,billing_id,created_at";
+
+/// Time a user of an organization has worked, which can be invoiced.
+/// Once billed, an entry is never billed again
+#[derive(Debug, Clone)]
+pub struct TimeEntry {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    /// The user who did the work
+    pub user_id: String,
+    pub project: String,
+    /// UNIX timestamp of the start of the day the work was done, in UTC
+    pub work_date: i64,
+    pub duration_minutes: u32,
+    pub description: String,
+    pub hourly_rate: f32,
+    /// UNIX timestamp at which the entry was billed, `None` while unbilled
+    pub billed_at: Option<i64>,
+    /// Shared by all entries billed together, `None` while unbilled
+    pub billing_id: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimeEntryBuilder<'a> {
+    pub org: &'a Org,
+    pub user: &'a User,
+    pub project: String,
+    pub work_date: i64,
+    pub duration_minutes: u32,
+    pub description: String,
+    pub hourly_rate: f32,
+}
+
+/// Fields time entries can be sorted on when listing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum TimeEntrySort {
+    #[default]
+    WorkDate,
+    Project,
+}
+
+impl SortKey for TimeEntrySort {
+    type Item = TimeEntry;
+    const ID_COLUMN: &'static str = "id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::WorkDate => "work_date",
+            Self::Project => "project",
+        }
+    }
+
+    fn key(&self, item: &TimeEntry) -> Value {
+        match self {
+            Self::WorkDate => item.work_date.into(),
+            Self::Project => item.project.clone().into(),
+        }
+    }
+
+    fn id(item: &TimeEntry) -> String {
+        item.id.clone()
+    }
+}
+
+/// Filters applied when listing time entries. Fields set to `None` are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct TimeEntryFilter {
+    pub user_id: Option<String>,
+    pub project: Option<String>,
+    /// Only entries of this day or later
+    pub from: Option<i64>,
+    /// Only entries of this day or earlier
+    pub until: Option<i64>,
+    pub billed: Option<bool>,
+}
+
+/// How billed time entries are combined into invoice lines.
+/// Entries are only combined if they have the same hourly rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum TimeEntryGrouping {
+    /// One line per project
+    #[default]
+    Project,
+    /// One line per day
+    Day,
+}
+
+/// Time entries combined into a single invoice line
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEntryLine {
+    /// The projects of the combined entries, sorted
+    pub projects: Vec<String>,
+    /// The day of the combined entries, only set when grouping by day
+    pub work_date: Option<i64>,
+    pub duration_minutes: u32,
+    pub hourly_rate: f32,
+    /// The descriptions of the combined entries, in order of date
+    pub descriptions: Vec<String>,
+    pub time_entry_ids: Vec<String>,
+}
+
+impl TimeEntryLine {
+    /// The duration in hours, the quantity of the invoice line
+    pub fn hours(&self) -> f32 {
+        self.duration_minutes as f32 / 60.0
+    }
+
+    pub fn amount(&self) -> f32 {
+        self.hours() * self.hourly_rate
+    }
+}
+
+impl Entity for TimeEntry {
+    type Information<'a> = TimeEntryBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let entry = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(entry)
+    }
+
+    /// Remove the entry. Billed entries cannot be removed
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the entry. Billed entries cannot be updated, and the billing state is not changed
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Option<Row> = conn.exec_first(format!("SELECT {COLUMNS} FROM time_entries WHERE id = :id"), params! {
+            "id" => &id
+        }).await?;
+
+        Ok(row.map(|row| Self::from_row(driver, row)))
+    }
+}
+
+impl TimeEntry {
+    /// Create a time entry using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: TimeEntryBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        tx.exec_drop("INSERT INTO time_entries (id, org_id, user_id, project, work_date, duration_minutes, description, hourly_rate, created_at) \
+            VALUES (:id, :org_id, :user_id, :project, :work_date, :duration_minutes, :description, :hourly_rate, :created_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "user_id" => &builder.user.id,
+            "project" => &builder.project,
+            "work_date" => builder.work_date,
+            "duration_minutes" => builder.duration_minutes,
+            "description" => &builder.description,
+            "hourly_rate" => builder.hourly_rate,
+            "created_at" => created_at,
+        }).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            user_id: builder.user.id.clone(),
+            project: builder.project,
+            work_date: builder.work_date,
+            duration_minutes: builder.duration_minutes,
+            description: builder.description,
+            hourly_rate: builder.hourly_rate,
+            billed_at: None,
+            billing_id: None,
+            created_at,
+        })
+    }
+
+    /// Remove the time entry using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("DELETE FROM time_entries WHERE id = :id AND billed_at IS NULL", params! {
+            "id" => &self.id
+        }).await?;
+
+        Self::require_unbilled(tx, &self.id).await?;
+        Ok(())
+    }
+
+    /// Update the time entry using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE time_entries SET project = :project, work_date = :work_date, duration_minutes = :duration_minutes, \
+            description = :description, hourly_rate = :hourly_rate WHERE id = :id AND billed_at IS NULL", params! {
+            "project" => &self.project,
+            "work_date" => self.work_date,
+            "duration_minutes" => self.duration_minutes,
+            "description" => &self.description,
+            "hourly_rate" => self.hourly_rate,
+            "id" => &self.id,
+        }).await?;
+
+        Self::require_unbilled(tx, &self.id).await?;
+        Ok(())
+    }
+
+    fn from_row(driver: &Driver, row: Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            org_id: row.get("org_id").unwrap(),
+            user_id: row.get("user_id").unwrap(),
+            project: row.get("project").unwrap(),
+            work_date: row.get("work_date").unwrap(),
+            duration_minutes: row.get("duration_minutes").unwrap(),
+            description: row.get("description").unwrap(),
+            hourly_rate: row.get("hourly_rate").unwrap(),
+            billed_at: row.get("billed_at").unwrap(),
+            billing_id: row.get("billing_id").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+        }
+    }
+
+    /// Called after a write guarded with `billed_at IS NULL`, in the same transaction. If the entry has been billed
+    /// in the meantime, the write did not match it and the transaction has to be rolled back
+    async fn require_unbilled(conn: &mut impl Queryable, id: &str) -> crate::Result<()> {
+        let row: Option<Row> = conn.exec_first("SELECT billed_at FROM time_entries WHERE id = :id", params! {
+            "id" => id
+        }).await?;
+
+        let billed_at: Option<i64> = row.and_then(|row| row.get("billed_at").unwrap());
+        match billed_at {
+            Some(_) => Err(Error::AlreadyBilled),
+            None => Ok(()),
+        }
+    }
+
+    /// List the time entries of the organization
+    pub async fn list_for_org(driver: &Driver, org: &Org, filter: &TimeEntryFilter, page: &PageRequest<TimeEntrySort>) -> crate::Result<Page<Self>> {
+        let mut query = format!("SELECT {COLUMNS} FROM time_entries WHERE org_id = :org_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), org.id.clone().into()),
+        ];
+
+        if let Some(user_id) = &filter.user_id {
+            query.push_str(" AND user_id = :user_id");
+            params.push(("user_id".to_string(), user_id.into()));
+        }
+
+        if let Some(project) = &filter.project {
+            query.push_str(" AND project = :project");
+            params.push(("project".to_string(), project.into()));
+        }
+
+        if let Some(from) = filter.from {
+            query.push_str(" AND work_date >= :from");
+            params.push(("from".to_string(), from.into()));
+        }
+
+        if let Some(until) = filter.until {
+            query.push_str(" AND work_date <= :until");
+            params.push(("until".to_string(), until.into()));
+        }
+
+        match filter.billed {
+            Some(true) => query.push_str(" AND billed_at IS NOT NULL"),
+            Some(false) => query.push_str(" AND billed_at IS NULL"),
+            None => {},
+        }
+
+        page.apply(&mut query, &mut params);
+
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
+        let entries = rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect::<Vec<_>>();
+
+        page.finish(entries)
+    }
+
+    /// Get the time entries of the organization with the provided IDs, to be billed together.
+    /// Fails if any of them does not exist, belongs to another organization or has been billed already.
+    /// The entries are not marked as billed: until invoices exist, the lines generated from them are not stored
+    pub async fn list_billable(driver: &Driver, org: &Org, ids: &[String]) -> crate::Result<Vec<Self>> {
+        let mut ids = ids.to_vec();
+        ids.sort();
+        ids.dedup();
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut params: Vec<(String, Value)> = vec![
+            ("org_id".to_string(), org.id.clone().into()),
+        ];
+        let placeholders = ids.iter()
+            .enumerate()
+            .map(|(idx, id)| {
+                params.push((format!("id_{idx}"), id.into()));
+                format!(":id_{idx}")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(format!("SELECT {COLUMNS} FROM time_entries WHERE org_id = :org_id AND id IN ({placeholders}) ORDER BY work_date, id"),
+            Params::from(params)).await?;
+        let entries = rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect::<Vec<_>>();
+
+        if entries.len() != ids.len() {
+            return Err(Error::UnknownTimeEntry);
+        }
+
+        if entries.iter().any(|x| x.billed_at.is_some()) {
+            return Err(Error::AlreadyBilled);
+        }
+
+        Ok(entries)
+    }
+}
+
+/// The fields of a time entry which determine the invoice line it ends up on
+struct Billable<'a> {
+    id: &'a str,
+    project: &'a str,
+    work_date: i64,
+    duration_minutes: u32,
+    hourly_rate: f32,
+    description: &'a str,
+}
+
+impl<'a> From<&'a TimeEntry> for Billable<'a> {
+    fn from(x: &'a TimeEntry) -> Self {
+        Self {
+            id: &x.id,
+            project: &x.project,
+            work_date: x.work_date,
+            duration_minutes: x.duration_minutes,
+            hourly_rate: x.hourly_rate,
+            description: &x.description,
+        }
+    }
+}
+
+/// Combine time entries into invoice lines.
+/// Lines are ordered by project or day, and then by hourly rate
+pub fn group_time_entries(entries: &[TimeEntry], grouping: TimeEntryGrouping) -> Vec<TimeEntryLine> {
+    group(entries.iter().map(Billable::from).collect(), grouping)
+}
+
+fn group(mut entries: Vec<Billable>, grouping: TimeEntryGrouping) -> Vec<TimeEntryLine> {
+    entries.sort_by(|a, b| a.work_date.cmp(&b.work_date).then_with(|| a.id.cmp(b.id)));
+
+    let mut lines: Vec<TimeEntryLine> = Vec::new();
+    for entry in entries {
+        let same_group = |line: &TimeEntryLine| line.hourly_rate == entry.hourly_rate && match grouping {
+            TimeEntryGrouping::Project => line.projects[0] == entry.project,
+            TimeEntryGrouping::Day => line.work_date == Some(entry.work_date),
+        };
+
+        let line = match lines.iter_mut().find(|x| same_group(x)) {
+            Some(line) => line,
+            None => {
+                lines.push(TimeEntryLine {
+                    projects: Vec::new(),
+                    work_date: (grouping == TimeEntryGrouping::Day).then_some(entry.work_date),
+                    duration_minutes: 0,
+                    hourly_rate: entry.hourly_rate,
+                    descriptions: Vec::new(),
+                    time_entry_ids: Vec::new(),
+                });
+                lines.last_mut().unwrap()
+            }
+        };
+
+        if !line.projects.iter().any(|x| x == entry.project) {
+            line.projects.push(entry.project.to_string());
+            line.projects.sort();
+        }
+
+        line.duration_minutes += entry.duration_minutes;
+        line.time_entry_ids.push(entry.id.to_string());
+        if !entry.description.is_empty() {
+            line.descriptions.push(entry.description.to_string());
+        }
+    }
+
+    lines.sort_by(|a, b| a.work_date.cmp(&b.work_date)
+        .then_with(|| a.projects.cmp(&b.projects))
+        .then_with(|| a.hourly_rate.total_cmp(&b.hourly_rate)));
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn group_by_project_and_day() {
+        const DAY: i64 = 24 * 60 * 60;
+        let entry = |id, project, work_date, duration_minutes, hourly_rate| Billable {
+            id,
+            project,
+            work_date,
+            duration_minutes,
+            hourly_rate,
+            description: id,
+        };
+        let entries = || vec![
+            entry("a", "Website", DAY, 90, 100.0),
+            entry("b", "Backend", DAY, 60, 100.0),
+            entry("c", "Website", 2 * DAY, 30, 100.0),
+            entry("d", "Website", 2 * DAY, 60, 120.0),
+        ];
+
+        let lines = group(entries(), TimeEntryGrouping::Project);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].projects, vec!["Backend"]);
+        assert_eq!(lines[1].projects, vec!["Website"]);
+        assert_eq!(lines[1].time_entry_ids, vec!["a", "c"]);
+        assert_eq!(lines[1].hours(), 2.0);
+        assert_eq!(lines[1].amount(), 200.0);
+        assert_eq!(lines[2].hourly_rate, 120.0);
+
+        let lines = group(entries(), TimeEntryGrouping::Day);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].work_date, Some(DAY));
+        assert_eq!(lines[0].projects, vec!["Backend", "Website"]);
+        assert_eq!(lines[0].duration_minutes, 150);
+        assert_eq!(lines[1].time_entry_ids, vec!["c"]);
+        assert_eq!(lines[2].time_entry_ids, vec!["d"]);
+    }
+}
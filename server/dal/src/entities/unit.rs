@@ -0,0 +1,61 @@
+use proc::{Stringify, Variants};
+
+/// Unit of measure of a product, identified by its UN/ECE Recommendation 20 code.
+/// Only the codes commonly used on invoices are supported
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum UnitCode {
+    /// One, for products counted without a dimension
+    #[default]
+    C62,
+    /// Piece
+    H87,
+    /// Each
+    EA,
+    /// Set
+    SET,
+    /// Pair
+    PR,
+    /// Lump sum
+    LS,
+    /// Service unit
+    E48,
+    /// Second
+    SEC,
+    /// Minute
+    MIN,
+    /// Hour
+    HUR,
+    /// Day
+    DAY,
+    /// Week
+    WEE,
+    /// Month
+    MON,
+    /// Year
+    ANN,
+    /// Gram
+    GRM,
+    /// Kilogram
+    KGM,
+    /// Tonne
+    TNE,
+    /// Millimetre
+    MMT,
+    /// Centimetre
+    CMT,
+    /// Metre
+    MTR,
+    /// Kilometre
+    KMT,
+    /// Square metre
+    MTK,
+    /// Millilitre
+    MLT,
+    /// Litre
+    LTR,
+    /// Cubic metre
+    MTQ,
+    /// Kilowatt hour
+    KWH,
+}
@@ -1,15 +1,16 @@
-use mysql::{params, Row, TxOpts};
-use mysql::prelude::Queryable;
+use crate::driver::{Queryable, Row, Transaction};
+use crate::params;
 use crate::{Driver, Error, gen_id, Result};
-use crate::entities::Entity;
+use crate::entities::{Entity, Org, OrgInvitation};
 use proc::Stringify;
 use std::str::FromStr;
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 use crate::hashing::{hash, verify};
 
 #[derive(Debug, Clone)]
-pub struct User<'a> {
-    driver: &'a Driver,
+pub struct User {
+    driver: Driver,
     pub id: String,
     pub name: String,
     pub email: String
@@ -21,98 +22,65 @@ pub struct UserBuilder {
     pub authentication: Authentication,
 }
 
-impl<'a> Entity<'a> for User<'a> {
-    type Information = UserBuilder;
+impl Entity for User {
+    type Information<'a> = UserBuilder;
 
-    fn create(driver: &'a Driver, builder: Self::Information) -> Result<Self> {
-        let id = gen_id();
-
-        let mut tx = driver.start_transaction(TxOpts::default())?;
-        tx.exec_drop("INSERT INTO users (id, name, email) VALUES (:id, :name, :email) VALUES (:id, :name, :email)", params! {
-            "id" => &id,
-            "name" => &builder.name,
-            "email" => &builder.email
-        })?;
-
-        let auth_method = AuthenticationMethod::from(&builder.authentication);
-        tx.exec_drop("INSERT INTO user_authentication_methods (id, method) VALUES (:id, :method)", params! {
-            "id" => &id,
-            "method" => auth_method.to_string(),
-        })?;
-
-        match builder.authentication {
-            Authentication::Password { password, pepper } => {
-                let mut salt = [0u8; 16];
-                rand::thread_rng().fill_bytes(&mut salt);
-
-                let hash = hash(&password, salt, &pepper)?;
-
-                tx.exec_drop("INSERT INTO user_passwords (id, hash) VALUES (:id, :hash)", params! {
-                    "id" => &id,
-                    "hash" => &hash,
-                })?;
-            }
-        }
-
-        tx.commit()?;
-
-        Ok(Self {
-            driver,
-            id,
-            name: builder.name,
-            email: builder.email,
-        })
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let user = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(user)
     }
 
-    fn remove(self) -> Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    async fn remove(self) -> Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
 
         tx.exec_drop("DELETE FROM users WHERE id = :id", params! {
             "id" => &self.id
-        })?;
+        }).await?;
 
         tx.exec_drop("DELETE FROM user_passwords WHERE id = :id", params! {
             "id" => &self.id
-        })?;
+        }).await?;
 
         tx.exec_drop("DELETE FROM user_authentication_methods WHERE id = :id", params! {
             "id" => &self.id
-        })?;
+        }).await?;
 
         tx.exec_drop("DELETE FROM user_sessions WHERE id = :id", params! {
             "id" => &self.id,
-        })?;
+        }).await?;
 
         tx.exec_drop("DELETE FROM service_tokens WHERE associated_user_id = :id", params! {
             "id" => &self.id
-        })?;
+        }).await?;
 
-        tx.commit()?;
+        tx.commit().await?;
         Ok(())
     }
 
-    fn update(&mut self) -> Result<()> {
-        let mut conn = self.driver.get_conn()?;
+    async fn update(&mut self) -> Result<()> {
+        let mut conn = self.driver.get_conn().await?;
         conn.exec_drop("UPDATE users SET email = :email, name = :name WHERE id = :id", params! {
             "email" => &self.email,
             "name" => &self.name,
             "id" => &self.id
-        })?;
+        }).await?;
 
         Ok(())
     }
 
-    fn get(driver: &'a Driver, id: String) -> Result<Option<Self>> {
-        let mut conn = driver.get_conn()?;
+    async fn get(driver: &Driver, id: String) -> Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT email,name FROM users WHERE id = :id", params! {
             "id" => &id
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(None)
         };
 
         Ok(Some(Self {
-            driver,
+            driver: driver.clone(),
             id,
             name: row.get("name").unwrap(),
             email: row.get("email").unwrap()
@@ -142,9 +110,24 @@ impl From<&Authentication> for AuthenticationMethod {
 
 #[derive(Debug, Clone)]
 pub struct SessionDescription {
+    /// The session token. This is a secret and must only be shown to the client creating the session
     pub id: String,
+    /// A non-secret identifier of the session, see [session_public_id]
+    pub public_id: String,
     pub expires_at: i64,
     pub last_used: i64,
+    pub created_at: i64,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// Information about the device a session is created from
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadata {
+    /// The `User-Agent` header provided by the client
+    pub user_agent: Option<String>,
+    /// The IP address of the client
+    pub ip_address: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -153,37 +136,142 @@ pub struct EmailAssociation {
     pub expires_at: i64,
 }
 
-impl<'a> User<'a> {
-    pub fn get_by_email(driver: &'a Driver, email: &str) -> Result<Option<Self>> {
-        let mut conn = driver.get_conn()?;
+/// The public ID of a session: the hex encoded SHA-256 hash of its token.
+/// It identifies the session to its user without revealing the token
+pub fn session_public_id(session: &str) -> String {
+    format!("{:x}", Sha256::digest(session.as_bytes()))
+}
+
+impl User {
+    /// Create a user using the provided transaction
+    async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: UserBuilder) -> Result<Self> {
+        let id = gen_id();
+
+        tx.exec_drop("INSERT INTO users (id, name, email) VALUES (:id, :name, :email)", params! {
+            "id" => &id,
+            "name" => &builder.name,
+            "email" => &builder.email
+        }).await?;
+
+        let auth_method = AuthenticationMethod::from(&builder.authentication);
+        tx.exec_drop("INSERT INTO user_authentication_methods (id, method) VALUES (:id, :method)", params! {
+            "id" => &id,
+            "method" => auth_method.to_string(),
+        }).await?;
+
+        match builder.authentication {
+            Authentication::Password { password, pepper } => {
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+
+                let hash = hash(&password, salt, &pepper)?;
+
+                tx.exec_drop("INSERT INTO user_passwords (id, hash) VALUES (:id, :hash)", params! {
+                    "id" => &id,
+                    "hash" => &hash,
+                }).await?;
+            }
+        }
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            name: builder.name,
+            email: builder.email,
+        })
+    }
+
+    /// Register a new user: create the user, associate its email address and,
+    /// if an invitation is provided, accept it on behalf of the user.
+    /// Everything is stored in a single transaction, so no account remains if any step fails.
+    ///
+    /// # Errors
+    ///
+    /// See [OrgInvitation::accept]
+    pub async fn register(driver: &Driver, builder: UserBuilder, invitation: Option<&OrgInvitation>) -> Result<(Self, EmailAssociation)> {
+        let email = builder.email.clone();
+        let org = match invitation {
+            Some(invitation) => Some(invitation.prepare_accept(&email).await?),
+            None => None,
+        };
+
+        let mut tx = driver.start_transaction().await?;
+        let registered = Self::register_with_tx(&mut tx, driver, builder, invitation.zip(org)).await?;
+        tx.commit().await?;
+        Ok(registered)
+    }
+
+    /// Register a new user using the provided transaction, see [Self::register].
+    /// The invitation must have been checked with [OrgInvitation::prepare_accept], which returned its organization
+    pub async fn register_with_tx(tx: &mut Transaction, driver: &Driver, builder: UserBuilder, invitation: Option<(&OrgInvitation, Org)>) -> Result<(Self, EmailAssociation)> {
+        let email = builder.email.clone();
+        let mut user = Self::create_with_tx(tx, driver, builder).await?;
+        let association = user.associate_email_with_tx(tx, &email).await?;
+
+        if let Some((invitation, mut org)) = invitation {
+            invitation.accept_with_tx(tx, &mut org, &user).await?;
+        }
+
+        Ok((user, association))
+    }
+
+    /// Create a user from a row containing the `id`, `name` and `email` columns of the users table
+    pub(crate) fn from_row(driver: &Driver, row: &Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            name: row.get("name").unwrap(),
+            email: row.get("email").unwrap(),
+        }
+    }
+
+    pub async fn get_by_email(driver: &Driver, email: &str) -> Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT id,name FROM users WHERE email = :email", params! {
             "email" => email,
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(None),
         };
 
         Ok(Some(Self {
-            driver,
+            driver: driver.clone(),
             id: row.get("id").unwrap(),
             name: row.get("name").unwrap(),
             email: email.to_string(),
         }))
     }
 
-    pub fn delete_session(&mut self, session: &str) -> Result<()> {
-        let mut conn = self.driver.get_conn()?;
-        conn.exec_drop("DELETE FROM user_sessions WHERE id = :id", params! {
-            "id" => &session
-        })?;
+    pub async fn delete_session(&mut self, session: &str) -> Result<()> {
+        let mut conn = self.driver.get_conn().await?;
+        conn.exec_drop("DELETE FROM user_sessions WHERE id = :id AND user_id = :user_id", params! {
+            "id" => &session,
+            "user_id" => &self.id,
+        }).await?;
         Ok(())
     }
 
-    pub fn is_password_correct(&self, provided_password: &str, pepper: &str) -> Result<bool> {
-        let mut conn = self.driver.get_conn()?;
+    /// Remove the session of the user with the provided public ID.
+    /// Returns whether the user had such a session
+    pub async fn delete_session_by_public_id(&mut self, public_id: &str) -> Result<bool> {
+        let session = self.list_sessions().await?
+            .into_iter()
+            .find(|x| x.public_id.eq(public_id));
+
+        match session {
+            Some(session) => {
+                self.delete_session(&session.id).await?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    pub async fn is_password_correct(&self, provided_password: &str, pepper: &str) -> Result<bool> {
+        let mut conn = self.driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT hash FROM user_passwords WHERE id = :id", params! {
             "id" => &self.id
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(false),
         };
@@ -193,27 +281,35 @@ impl<'a> User<'a> {
         Ok(is_valid)
     }
 
-    pub fn list_sessions(&self) -> Result<Vec<SessionDescription>> {
-        let mut conn = self.driver.get_conn()?;
-        let rows: Vec<Row> = conn.exec("SELECT id,expires_at,last_used FROM user_sessions WHERE user_id = :user_id", params! {
-            "user_id" => &self.id
-        })?;
+    pub async fn list_sessions(&self) -> Result<Vec<SessionDescription>> {
+        let mut conn = self.driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,expires_at,last_used,created_at,user_agent,ip_address FROM user_sessions WHERE user_id = :user_id AND expires_at >= :now", params! {
+            "user_id" => &self.id,
+            "now" => time::OffsetDateTime::now_utc().unix_timestamp(),
+        }).await?;
 
         let result = rows.into_iter()
             .map(|x| SessionDescription {
+                public_id: session_public_id(&x.get::<String, _>("id").unwrap()),
                 id: x.get("id").unwrap(),
                 expires_at: x.get("expires_at").unwrap(),
                 last_used: x.get("last_used").unwrap(),
+                created_at: x.get("created_at").unwrap(),
+                user_agent: x.get("user_agent").unwrap(),
+                ip_address: x.get("ip_address").unwrap(),
             })
             .collect::<Vec<_>>();
         Ok(result)
     }
 
-    pub fn get_by_session(driver: &'a Driver, session: &str) -> Result<Option<Self>> {
-        let mut conn = driver.get_conn()?;
+    /// Get the user associated with a session.
+    /// Every time a session is used, its expiry is moved forward by `expiry`.
+    /// Expired sessions are not removed here, see [Self::purge_expired_sessions].
+    pub async fn get_by_session(driver: &Driver, session: &str, expiry: std::time::Duration) -> Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT user_id,expires_at FROM user_sessions WHERE id = :id", params! {
             "id" => &session,
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(None),
         };
@@ -221,29 +317,37 @@ impl<'a> User<'a> {
         let user_id: String = row.get("user_id").unwrap();
         let expires_at: i64 = row.get("expires_at").unwrap();
 
-        if time::OffsetDateTime::now_utc().unix_timestamp() > expires_at {
-            conn.exec_drop("DELETE FROM user_sessions WHERE id = :id", params! {
-                "id" => &session
-            })?;
-
+        let now = time::OffsetDateTime::now_utc();
+        if now.unix_timestamp() > expires_at {
             return Ok(None);
         }
 
-        conn.exec_drop("UPDATE user_sessions SET last_used = :last_used WHERE id = :id", params! {
+        conn.exec_drop("UPDATE user_sessions SET last_used = :last_used, expires_at = :expires_at WHERE id = :id", params! {
             "id" => &session,
-            "last_used" => time::OffsetDateTime::now_utc().unix_timestamp(),
-        })?;
+            "last_used" => now.unix_timestamp(),
+            "expires_at" => (now + expiry).unix_timestamp(),
+        }).await?;
 
-        let user = Self::get(driver, user_id)?;
+        let user = Self::get(driver, user_id).await?;
         Ok(user)
     }
 
-    pub fn is_email_verified(&self, email: &str) -> Result<bool> {
-        let mut conn = self.driver.get_conn()?;
+    /// Remove all sessions which have expired, for all users
+    pub async fn purge_expired_sessions(driver: &Driver) -> Result<()> {
+        let mut conn = driver.get_conn().await?;
+        conn.exec_drop("DELETE FROM user_sessions WHERE expires_at < :now", params! {
+            "now" => time::OffsetDateTime::now_utc().unix_timestamp(),
+        }).await?;
+
+        Ok(())
+    }
+
+    pub async fn is_email_verified(&self, email: &str) -> Result<bool> {
+        let mut conn = self.driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT verified FROM user_emails WHERE email = :email AND user_id = :user_id", params! {
             "email" => &email,
             "user_id" => &self.id
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(false)
         };
@@ -252,11 +356,11 @@ impl<'a> User<'a> {
         Ok(verified)
     }
 
-    pub fn verifiy_email(&mut self, verification_token: &str) -> Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    pub async fn verifiy_email(&mut self, verification_token: &str) -> Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
         let row: Row = match tx.exec_first("SELECT user_id,expires_at,email FROM user_email_verification_tokens WHERE token = :token", params! {
             "token" => verification_token
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Err(Error::UnknownToken),
         };
@@ -277,29 +381,35 @@ impl<'a> User<'a> {
         tx.exec_drop("UPDATE user_emails SET verified = true WHERE email = :email AND user_id = :user_id", params! {
             "email" => &email,
             "user_id" => &self.id
-        })?;
+        }).await?;
 
         tx.exec_drop("UPDATE users SET email = :email WHERE id = :id", params! {
             "id" => &self.id,
             "email" => &email
-        })?;
+        }).await?;
 
         // Email is now active. Next step is cleanup
         tx.exec_drop("DELETE FROM user_email_verification_tokens WHERE token = :token", params! {
             "token" => verification_token
-        })?;
+        }).await?;
 
-        tx.commit()?;
+        tx.commit().await?;
 
         Ok(())
     }
 
-    pub fn associate_email(&mut self, email: &str) -> Result<EmailAssociation> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    pub async fn associate_email(&mut self, email: &str) -> Result<EmailAssociation> {
+        let mut tx = self.driver.start_transaction().await?;
+        let association = self.associate_email_with_tx(&mut tx, email).await?;
+        tx.commit().await?;
+        Ok(association)
+    }
+
+    async fn associate_email_with_tx(&mut self, tx: &mut Transaction, email: &str) -> Result<EmailAssociation> {
         tx.exec_drop("INSERT INTO user_emails (email, user_id) VALUES (:email, :user_id)", params! {
             "email" => email,
             "user_id" => &self.id
-        })?;
+        }).await?;
 
         let expires_at = (time::OffsetDateTime::now_utc() + time::Duration::days(7)).unix_timestamp();
         let token = gen_id();
@@ -309,9 +419,7 @@ impl<'a> User<'a> {
             "email" => email,
             "user_id" => &self.id,
             "expires_at" => expires_at
-        })?;
-
-        tx.commit()?;
+        }).await?;
 
         Ok(EmailAssociation {
             verification_token: token,
@@ -319,35 +427,44 @@ impl<'a> User<'a> {
         })
     }
 
-    pub fn create_session(&mut self) -> Result<SessionDescription> {
+    /// Create a new session for the user. The session expires after it has not been used for `expiry`
+    pub async fn create_session(&mut self, expiry: std::time::Duration, metadata: SessionMetadata) -> Result<SessionDescription> {
         let id = gen_id();
         // A user session ID is prefixed with US_, add the prefix
         let id = format!("US_{id}");
 
-        let mut conn = self.driver.get_conn()?;
+        let mut conn = self.driver.get_conn().await?;
 
-        let expires_at = (time::OffsetDateTime::now_utc() + time::Duration::days(30)).unix_timestamp();
-        let last_used = time::OffsetDateTime::now_utc().unix_timestamp();
+        let now = time::OffsetDateTime::now_utc();
+        let expires_at = (now + expiry).unix_timestamp();
+        let created_at = now.unix_timestamp();
 
-        conn.exec_drop("INSERT INTO user_sessions (id, user_id, last_used, expires_at)", params! {
+        conn.exec_drop("INSERT INTO user_sessions (id, user_id, last_used, expires_at, created_at, user_agent, ip_address) VALUES (:id, :user_id, :last_used, :expires_at, :created_at, :user_agent, :ip_address)", params! {
             "id" => &id,
             "user_id" => &self.id,
-            "last_used" => last_used,
-            "expires_at" => expires_at
-        })?;
+            "last_used" => created_at,
+            "expires_at" => expires_at,
+            "created_at" => created_at,
+            "user_agent" => &metadata.user_agent,
+            "ip_address" => &metadata.ip_address,
+        }).await?;
 
         Ok(SessionDescription {
+            public_id: session_public_id(&id),
             id,
             expires_at,
-            last_used
+            last_used: created_at,
+            created_at,
+            user_agent: metadata.user_agent,
+            ip_address: metadata.ip_address,
         })
     }
 
-    pub fn get_authentication_method(&self) -> Result<Option<AuthenticationMethod>> {
-        let mut conn = self.driver.get_conn()?;
+    pub async fn get_authentication_method(&self) -> Result<Option<AuthenticationMethod>> {
+        let mut conn = self.driver.get_conn().await?;
         let row: Row = match conn.exec_first("SELECT method FROM user_authentication_methods WHERE id = :id", params! {
             "id" => &self.id
-        })? {
+        }).await? {
             Some(x) => x,
             None => return Ok(None)
         };
@@ -357,13 +474,13 @@ impl<'a> User<'a> {
         ).map_err(|_| Error::UnknownEnumVariant)?))
     }
 
-    pub fn set_authentication(&mut self, auth: Authentication) -> Result<()> {
-        let mut tx = self.driver.start_transaction(TxOpts::default())?;
+    pub async fn set_authentication(&mut self, auth: Authentication) -> Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
 
         tx.exec_drop("UPDATE user_authentication_methods SET method = :method WHERE id = :id", params! {
             "method" => AuthenticationMethod::from(&auth).to_string(),
             "id" => &self.id
-        })?;
+        }).await?;
 
         match auth {
             Authentication::Password { password, pepper } => {
@@ -374,10 +491,72 @@ impl<'a> User<'a> {
                 tx.exec_drop("UPDATE user_passwords SET hash = :hash WHERE id = :id", params! {
                     "hash" => &hash,
                     "id" => &self.id
-                })?;
+                }).await?;
             }
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use crate::driver::Backend;
+    use crate::entities::{Org, OrgBuilder, OrgInvitationBuilder, Language, OrgScope};
+    use crate::DriverConfig;
+    use super::*;
+
+    async fn memory_driver() -> Driver {
+        let driver = Driver::new(DriverConfig {
+            backend: Backend::Sqlite,
+            host: String::default(),
+            database: ":memory:".to_string(),
+            username: String::default(),
+            password: String::default(),
+        }).unwrap();
+
+        crate::init(&driver).await.unwrap();
+        driver
+    }
+
+    fn builder(email: &str) -> UserBuilder {
+        UserBuilder {
+            name: "Test".to_string(),
+            email: email.to_string(),
+            authentication: Authentication::Password {
+                password: "password".to_string(),
+                pepper: "pepper".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn register_with_invitation() {
+        let driver = memory_driver().await;
+        let owner = User::create(&driver, builder("owner@example.com")).await.unwrap();
+        let org = Org::create(&driver, OrgBuilder {
+            name: "Org".to_string(),
+            creator: &owner,
+        }).await.unwrap();
+
+        let invitation = OrgInvitation::create(&driver, OrgInvitationBuilder {
+            org: &org,
+            email: "invitee@example.com".to_string(),
+            is_org_admin: false,
+            scopes: vec![OrgScope::GetOrg],
+            invited_by: &owner,
+            language: Language::default(),
+            expiry: std::time::Duration::from_secs(60),
+        }).await.unwrap();
+
+        // An invitation for another address is rejected, without leaving an account behind
+        let rejected = User::register(&driver, builder("other@example.com"), Some(&invitation)).await;
+        assert!(matches!(rejected, Err(Error::UnknownToken)));
+        assert!(User::get_by_email(&driver, "other@example.com").await.unwrap().is_none());
+
+        let (user, _) = User::register(&driver, builder("invitee@example.com"), Some(&invitation)).await.unwrap();
+        let org_user = org.get_user(&user).await.unwrap().unwrap();
+        assert!(!org_user.is_org_admin);
+        assert_eq!(org_user.scopes, vec![OrgScope::GetOrg]);
+        assert!(OrgInvitation::get_by_token(&driver, &invitation.token).await.unwrap().is_none());
+    }
+}
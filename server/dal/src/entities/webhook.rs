@@ -0,0 +1,428 @@
+use rand::Rng;
+use crate::driver::{Params, Queryable, Row, Transaction, Value};
+use crate::params;
+use crate::{Driver, gen_id};
+use crate::entities::{AuditAction, AuditEntityType, AuditLogEntry, Entity, Org};
+use crate::pagination::{Page, PageRequest, SortKey};
+use proc::{Stringify, Variants};
+
+/// The number of delivery attempts after which a delivery is given up on
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 12;
+/// The delay before the first retry, doubled after every failed attempt
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+/// The maximum delay between two attempts
+const RETRY_MAX_DELAY_SECS: i64 = 6 * 60 * 60;
+
+/// A subscription of an organization to events, which are delivered to `url` as HTTP POST requests.
+///
+/// Every entry in the audit log is an event. Its type is `<entity type>.<action>`, e.g. `Product.Create`,
+/// see [webhook_event_types]
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    driver: Driver,
+    pub id: String,
+    pub org_id: String,
+    pub url: String,
+    /// Key with which deliveries are signed. Generated when the webhook is created
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookBuilder<'a> {
+    pub org: &'a Org,
+    pub url: String,
+    pub event_types: Vec<String>,
+}
+
+/// A single event to be delivered to a single webhook, and the outcome of delivering it
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    driver: Driver,
+    pub id: String,
+    pub webhook_id: String,
+    /// The ID of the audit log entry the event originates from
+    pub event_id: String,
+    pub event_type: String,
+    /// The JSON request body
+    pub payload: String,
+    pub attempts: u32,
+    /// UNIX timestamp of the next attempt. `None` once delivered, or once [MAX_DELIVERY_ATTEMPTS] is reached
+    pub next_attempt_at: Option<i64>,
+    pub delivered_at: Option<i64>,
+    /// The HTTP status code of the last attempt, `None` if no response was received
+    pub last_status: Option<u32>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+/// Fields deliveries can be sorted on when listing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify, Variants)]
+pub enum WebhookDeliverySort {
+    #[default]
+    CreatedAt,
+}
+
+impl SortKey for WebhookDeliverySort {
+    type Item = WebhookDelivery;
+    const ID_COLUMN: &'static str = "id";
+
+    fn column(&self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+        }
+    }
+
+    fn key(&self, item: &WebhookDelivery) -> Value {
+        match self {
+            Self::CreatedAt => item.created_at.into(),
+        }
+    }
+
+    fn id(item: &WebhookDelivery) -> String {
+        item.id.clone()
+    }
+}
+
+/// All event types webhooks can subscribe to
+pub fn webhook_event_types() -> Vec<String> {
+    AuditEntityType::variants().iter()
+        .flat_map(|entity_type| AuditAction::variants().iter()
+            .filter(move |action| is_deliverable(entity_type, action))
+            .map(move |action| event_type(entity_type, action)))
+        .collect()
+}
+
+/// Whether an event can be delivered to webhooks. The removal of an organization is not,
+/// as its webhooks are removed with it
+fn is_deliverable(entity_type: &AuditEntityType, action: &AuditAction) -> bool {
+    !matches!((entity_type, action), (AuditEntityType::Org, AuditAction::Remove))
+}
+
+fn event_type(entity_type: &AuditEntityType, action: &AuditAction) -> String {
+    format!("{}.{}", entity_type.to_string(), action.to_string())
+}
+
+impl Entity for Webhook {
+    type Information<'a> = WebhookBuilder<'a>;
+
+    async fn create(driver: &Driver, builder: Self::Information<'_>) -> crate::Result<Self> {
+        let mut tx = driver.start_transaction().await?;
+        let webhook = Self::create_with_tx(&mut tx, driver, builder).await?;
+        tx.commit().await?;
+        Ok(webhook)
+    }
+
+    /// Remove the webhook, including its delivery log. Pending deliveries are not attempted anymore
+    async fn remove(self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.remove_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Update the URL and event types. The secret cannot be changed
+    async fn update(&mut self) -> crate::Result<()> {
+        let mut tx = self.driver.start_transaction().await?;
+        self.update_with_tx(&mut tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get(driver: &Driver, id: String) -> crate::Result<Option<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let row: Row = match conn.exec_first("SELECT org_id,url,secret,created_at FROM webhooks WHERE id = :id", params! {
+            "id" => &id
+        }).await? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let rows: Vec<Row> = conn.exec("SELECT event_type FROM webhook_event_types WHERE webhook_id = :id ORDER BY event_type", params! {
+            "id" => &id
+        }).await?;
+
+        Ok(Some(Self {
+            driver: driver.clone(),
+            id,
+            org_id: row.get("org_id").unwrap(),
+            url: row.get("url").unwrap(),
+            secret: row.get("secret").unwrap(),
+            event_types: rows.into_iter().map(|row| row.get("event_type").unwrap()).collect(),
+            created_at: row.get("created_at").unwrap(),
+        }))
+    }
+}
+
+impl Webhook {
+    /// Create a webhook using the provided transaction
+    pub async fn create_with_tx(tx: &mut Transaction, driver: &Driver, builder: WebhookBuilder<'_>) -> crate::Result<Self> {
+        let id = gen_id();
+        let secret: String = rand::thread_rng().sample_iter(rand::distributions::Alphanumeric).take(64).map(char::from).collect();
+        let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        tx.exec_drop("INSERT INTO webhooks (id, org_id, url, secret, created_at) VALUES (:id, :org_id, :url, :secret, :created_at)", params! {
+            "id" => &id,
+            "org_id" => &builder.org.id,
+            "url" => &builder.url,
+            "secret" => &secret,
+            "created_at" => created_at,
+        }).await?;
+
+        Self::insert_event_types(tx, &id, &builder.event_types).await?;
+
+        Ok(Self {
+            driver: driver.clone(),
+            id,
+            org_id: builder.org.id.clone(),
+            url: builder.url,
+            secret,
+            event_types: builder.event_types,
+            created_at,
+        })
+    }
+
+    /// Remove the webhook using the provided transaction
+    pub async fn remove_with_tx(&self, tx: &mut Transaction) -> crate::Result<()> {
+        for table in ["webhook_deliveries", "webhook_event_types"] {
+            tx.exec_drop(format!("DELETE FROM {table} WHERE webhook_id = :id"), params! {
+                "id" => &self.id
+            }).await?;
+        }
+
+        tx.exec_drop("DELETE FROM webhooks WHERE id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Update the webhook using the provided transaction
+    pub async fn update_with_tx(&mut self, tx: &mut Transaction) -> crate::Result<()> {
+        tx.exec_drop("UPDATE webhooks SET url = :url WHERE id = :id", params! {
+            "url" => &self.url,
+            "id" => &self.id,
+        }).await?;
+
+        tx.exec_drop("DELETE FROM webhook_event_types WHERE webhook_id = :id", params! {
+            "id" => &self.id
+        }).await?;
+
+        Self::insert_event_types(tx, &self.id, &self.event_types).await
+    }
+
+    async fn insert_event_types(tx: &mut Transaction, webhook_id: &str, event_types: &[String]) -> crate::Result<()> {
+        for event_type in event_types {
+            tx.exec_drop("INSERT INTO webhook_event_types (webhook_id, event_type) VALUES (:webhook_id, :event_type)", params! {
+                "webhook_id" => webhook_id,
+                "event_type" => event_type,
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// List all webhooks of the organization
+    pub async fn list_for_org(driver: &Driver, org: &Org) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec("SELECT id,url,secret,created_at FROM webhooks WHERE org_id = :org_id ORDER BY created_at, id", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        let mut webhooks = rows.into_iter()
+            .map(|row| Self {
+                driver: driver.clone(),
+                id: row.get("id").unwrap(),
+                org_id: org.id.clone(),
+                url: row.get("url").unwrap(),
+                secret: row.get("secret").unwrap(),
+                event_types: Vec::new(),
+                created_at: row.get("created_at").unwrap(),
+            })
+            .collect::<Vec<_>>();
+
+        let rows: Vec<Row> = conn.exec("SELECT e.webhook_id,e.event_type FROM webhook_event_types e \
+            INNER JOIN webhooks w ON w.id = e.webhook_id WHERE w.org_id = :org_id ORDER BY e.event_type", params! {
+            "org_id" => &org.id
+        }).await?;
+
+        for row in rows {
+            let webhook_id: String = row.get("webhook_id").unwrap();
+            if let Some(webhook) = webhooks.iter_mut().find(|x| x.id.eq(&webhook_id)) {
+                webhook.event_types.push(row.get("event_type").unwrap());
+            }
+        }
+
+        Ok(webhooks)
+    }
+
+    /// Queue the delivery of an audit log entry to every webhook of its organization subscribed to its event type.
+    /// Called from [AuditLogEntry::create], in the transaction the entry is written in
+    pub(crate) async fn enqueue(tx: &mut Transaction, entry: &AuditLogEntry) -> crate::Result<()> {
+        if !is_deliverable(&entry.entity_type, &entry.action) {
+            return Ok(());
+        }
+
+        let event_type = event_type(&entry.entity_type, &entry.action);
+        let rows: Vec<Row> = tx.exec("SELECT w.id FROM webhooks w INNER JOIN webhook_event_types e ON e.webhook_id = w.id \
+            WHERE w.org_id = :org_id AND e.event_type = :event_type", params! {
+            "org_id" => &entry.org_id,
+            "event_type" => &event_type,
+        }).await?;
+
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let payload = serde_json::json!({
+            "id": &entry.id,
+            "type": &event_type,
+            "orgId": &entry.org_id,
+            "entityType": entry.entity_type.to_string(),
+            "entityId": &entry.entity_id,
+            "action": entry.action.to_string(),
+            "actorType": entry.actor.actor_type().to_string(),
+            "actorId": entry.actor.id(),
+            "diff": &entry.diff,
+            "createdAt": entry.created_at,
+        }).to_string();
+
+        for row in rows {
+            let webhook_id: String = row.get("id").unwrap();
+            tx.exec_drop("INSERT INTO webhook_deliveries (id, webhook_id, event_id, event_type, payload, attempts, next_attempt_at, created_at) \
+                VALUES (:id, :webhook_id, :event_id, :event_type, :payload, 0, :next_attempt_at, :created_at)", params! {
+                "id" => gen_id(),
+                "webhook_id" => &webhook_id,
+                "event_id" => &entry.id,
+                "event_type" => &event_type,
+                "payload" => &payload,
+                "next_attempt_at" => entry.created_at,
+                "created_at" => entry.created_at,
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// List a page of the delivery log of this webhook
+    pub async fn list_deliveries(&self, page: &PageRequest<WebhookDeliverySort>) -> crate::Result<Page<WebhookDelivery>> {
+        let mut query = format!("SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries WHERE webhook_id = :webhook_id");
+        let mut params: Vec<(String, Value)> = vec![
+            ("webhook_id".to_string(), self.id.clone().into()),
+        ];
+
+        page.apply(&mut query, &mut params);
+
+        let mut conn = self.driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(query, Params::from(params)).await?;
+        let deliveries = rows.into_iter()
+            .map(|row| WebhookDelivery::from_row(&self.driver, row))
+            .collect();
+
+        page.finish(deliveries)
+    }
+}
+
+const DELIVERY_COLUMNS: &str = "id,webhook_id,event_id,event_type,payload,attempts,next_attempt_at,delivered_at,last_status,last_error,created_at";
+
+impl WebhookDelivery {
+    /// Create a delivery from a row containing [DELIVERY_COLUMNS]
+    fn from_row(driver: &Driver, row: Row) -> Self {
+        Self {
+            driver: driver.clone(),
+            id: row.get("id").unwrap(),
+            webhook_id: row.get("webhook_id").unwrap(),
+            event_id: row.get("event_id").unwrap(),
+            event_type: row.get("event_type").unwrap(),
+            payload: row.get("payload").unwrap(),
+            attempts: row.get("attempts").unwrap(),
+            next_attempt_at: row.get("next_attempt_at").unwrap(),
+            delivered_at: row.get("delivered_at").unwrap(),
+            last_status: row.get("last_status").unwrap(),
+            last_error: row.get("last_error").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+        }
+    }
+
+    /// List deliveries of which the next attempt is due at the provided UNIX timestamp, oldest first
+    pub async fn list_due(driver: &Driver, now: i64, limit: u32) -> crate::Result<Vec<Self>> {
+        let mut conn = driver.get_conn().await?;
+        let rows: Vec<Row> = conn.exec(format!("SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries WHERE next_attempt_at <= :now ORDER BY next_attempt_at, id LIMIT :limit"), params! {
+            "now" => now,
+            "limit" => limit,
+        }).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| Self::from_row(driver, row))
+            .collect())
+    }
+
+    /// Record a successful attempt
+    pub async fn record_success(&mut self, status: u32) -> crate::Result<()> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        self.attempts += 1;
+        self.last_status = Some(status);
+        self.last_error = None;
+        self.delivered_at = Some(now);
+        self.next_attempt_at = None;
+        self.save_attempt().await
+    }
+
+    /// Record a failed attempt and schedule the next attempt with exponential backoff,
+    /// unless [MAX_DELIVERY_ATTEMPTS] is reached
+    pub async fn record_failure(&mut self, status: Option<u32>, error: String) -> crate::Result<()> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        self.attempts += 1;
+        self.last_status = status;
+        self.last_error = Some(error);
+        self.next_attempt_at = next_attempt_at(self.attempts, now);
+        self.save_attempt().await
+    }
+
+    async fn save_attempt(&self) -> crate::Result<()> {
+        let mut conn = self.driver.get_conn().await?;
+        conn.exec_drop("UPDATE webhook_deliveries SET attempts = :attempts, next_attempt_at = :next_attempt_at, delivered_at = :delivered_at, last_status = :last_status, last_error = :last_error WHERE id = :id", params! {
+            "attempts" => self.attempts,
+            "next_attempt_at" => self.next_attempt_at,
+            "delivered_at" => self.delivered_at,
+            "last_status" => self.last_status,
+            "last_error" => &self.last_error,
+            "id" => &self.id,
+        }).await?;
+
+        Ok(())
+    }
+}
+
+/// When to attempt a delivery again after `attempts` failed attempts. `None` if it should be given up on
+fn next_attempt_at(attempts: u32, now: i64) -> Option<i64> {
+    if attempts >= MAX_DELIVERY_ATTEMPTS {
+        return None;
+    }
+
+    let delay = RETRY_BASE_DELAY_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(30));
+    Some(now + delay.min(RETRY_MAX_DELAY_SECS))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        assert_eq!(next_attempt_at(1, 0), Some(30));
+        assert_eq!(next_attempt_at(2, 0), Some(60));
+        assert_eq!(next_attempt_at(4, 0), Some(240));
+        assert_eq!(next_attempt_at(MAX_DELIVERY_ATTEMPTS - 1, 0), Some(RETRY_MAX_DELAY_SECS));
+        assert_eq!(next_attempt_at(MAX_DELIVERY_ATTEMPTS, 0), None);
+    }
+
+    #[test]
+    fn event_types() {
+        let event_types = webhook_event_types();
+        assert!(event_types.contains(&"Product.Create".to_string()));
+        assert!(!event_types.contains(&"Org.Remove".to_string()));
+        assert_eq!(event_types.len(), AuditEntityType::variants().len() * AuditAction::variants().len() - 1);
+    }
+}
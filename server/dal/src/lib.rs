@@ -1,19 +1,36 @@
-use mysql::{OptsBuilder, Pool};
 use rand::Rng;
 use thiserror::Error;
 
 mod hashing;
+pub mod driver;
 pub mod entities;
+pub mod pagination;
 
-pub type Driver = mysql::Pool;
+pub use driver::{Backend, Driver};
 type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[cfg(feature = "mysql")]
     #[error("{0}")]
     Mysql(#[from] mysql::Error),
+    #[cfg(feature = "postgres")]
+    #[error("{0}")]
+    Postgres(#[from] postgres::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("{0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    #[error("{0}")]
+    Pool(#[from] r2d2::Error),
     #[error("{0}")]
     Refinery(#[from] refinery::Error),
+    #[error("The {0:?} backend is not enabled")]
+    BackendDisabled(Backend),
+    #[error("{0}")]
+    Blocking(#[from] tokio::task::JoinError),
+    #[error("Missing value for query parameter '{0}'")]
+    MissingParameter(String),
     #[error("Unknown enum variant")]
     UnknownEnumVariant,
     #[error("{0}")]
@@ -22,41 +39,39 @@ pub enum Error {
     UnknownToken,
     #[error("Token has expired")]
     ExpiredToken,
+    #[error("Unknown time entry")]
+    UnknownTimeEntry,
+    #[error("Time entry has already been billed")]
+    AlreadyBilled,
+    #[error("The supplier already has a purchase invoice with this number")]
+    DuplicatePurchaseInvoice,
+    #[error("The last admin of an organization cannot be removed")]
+    LastOrgAdmin,
+    #[error("Invalid or mismatched pagination cursor")]
+    InvalidCursor,
     #[error("Invalid state: {0}")]
     InvalidState(String),
-}
-
-mod migrations {
-    use refinery::embed_migrations;
-    embed_migrations!("./migrations/");
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
 }
 
 pub struct DriverConfig {
+    pub backend: Backend,
     pub host: String,
+    /// The name of the database. For SQLite this is the path to the database file
     pub database: String,
     pub username: String,
     pub password: String
 }
 
 pub fn get_driver(config: DriverConfig) -> Result<Driver> {
-    let opts = OptsBuilder::new()
-        .ip_or_hostname(Some(config.host))
-        .db_name(Some(config.database))
-        .user(Some(config.username))
-        .pass(Some(config.password));
-    let pool = Pool::new(opts)?;
-    Ok(pool)
+    Driver::new(config)
 }
 
-pub fn init(driver: &Driver) -> Result<()> {
-    let mut conn = driver.get_conn()?;
-    migrations::migrations::runner()
-        .set_migration_table_name("__invoicex_migrations")
-        .run(&mut conn)?;
-
-    Ok(())
+pub async fn init(driver: &Driver) -> Result<()> {
+    driver.migrate().await
 }
 
 pub(crate) fn gen_id() -> String {
     rand::thread_rng().sample_iter(rand::distributions::Alphanumeric).take(32).map(char::from).collect()
-}
+}
\ No newline at end of file
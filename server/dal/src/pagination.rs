@@ -0,0 +1,228 @@
+//! Cursor based pagination of listings.
+//!
+//! A listing is sorted on a column, with the ID of the row as tie breaker. The cursor of a page
+//! holds the sort key and ID of the last row on that page, the next page continues right after that row.
+//! Unlike an offset, a cursor stays valid when rows are inserted or removed between requests.
+
+use serde::{Deserialize, Serialize};
+use proc::Stringify;
+use crate::driver::Value;
+use crate::{Error, Result};
+
+/// The number of items on a page if no limit is requested
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+/// The maximum number of items on a page
+pub const MAX_PAGE_SIZE: u32 = 250;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Stringify)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    fn comparison(&self) -> &'static str {
+        match self {
+            Self::Asc => ">",
+            Self::Desc => "<",
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// A field a listing can be sorted on.
+/// Only columns which cannot be `NULL` may be used, as `NULL` cannot be compared with a cursor
+pub trait SortKey: ToString {
+    /// The type of the items in the listing
+    type Item;
+
+    /// The column uniquely identifying a row
+    const ID_COLUMN: &'static str;
+
+    /// The column to sort on. If the listing query joins tables, the column must be qualified
+    fn column(&self) -> &'static str;
+
+    /// The value of the sort column for an item
+    fn key(&self, item: &Self::Item) -> Value;
+
+    /// The value of [Self::ID_COLUMN] for an item
+    fn id(item: &Self::Item) -> String;
+}
+
+/// A request for a single page of a listing
+#[derive(Debug, Clone)]
+pub struct PageRequest<S> {
+    pub sort: S,
+    pub direction: SortDirection,
+    /// The maximum number of items on the page
+    pub limit: u32,
+    cursor: Option<Cursor>,
+}
+
+/// A page of a listing
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// The cursor to retrieve the next page with. `None` if this is the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Position in a listing, see the module documentation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    direction: String,
+    key: Value,
+    id: String,
+}
+
+impl Cursor {
+    fn encode(&self) -> Result<String> {
+        Ok(base64::encode_config(serde_json::to_vec(self)?, base64::URL_SAFE_NO_PAD))
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let bytes = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD).map_err(|_| Error::InvalidCursor)?;
+        serde_json::from_slice(&bytes).map_err(|_| Error::InvalidCursor)
+    }
+}
+
+impl<S: SortKey> PageRequest<S> {
+    /// Request the page following `cursor`, or the first page if no cursor is provided.
+    /// The cursor must have been returned by a listing with the same sort field and direction.
+    /// The limit is clamped to [MAX_PAGE_SIZE]
+    pub fn new(sort: S, direction: SortDirection, limit: u32, cursor: Option<&str>) -> Result<Self> {
+        let cursor = cursor.map(Cursor::decode).transpose()?;
+        if let Some(cursor) = &cursor {
+            if cursor.sort.ne(&sort.to_string()) || cursor.direction.ne(&direction.to_string()) {
+                return Err(Error::InvalidCursor);
+            }
+        }
+
+        Ok(Self {
+            sort,
+            direction,
+            limit: limit.clamp(1, MAX_PAGE_SIZE),
+            cursor,
+        })
+    }
+
+    /// Append the cursor condition, ordering and limit to a query.
+    /// The query must end with a `WHERE` clause, the cursor condition is appended with `AND`
+    pub(crate) fn apply(&self, query: &mut String, params: &mut Vec<(String, Value)>) {
+        let column = self.sort.column();
+        let id_column = S::ID_COLUMN;
+        let comparison = self.direction.comparison();
+
+        if let Some(cursor) = &self.cursor {
+            query.push_str(&format!(" AND ({column} {comparison} :cursor_key OR ({column} = :cursor_key AND {id_column} {comparison} :cursor_id))"));
+            params.push(("cursor_key".to_string(), cursor.key.clone()));
+            params.push(("cursor_id".to_string(), cursor.id.clone().into()));
+        }
+
+        // One row more than requested is fetched, to find out whether there is a next page
+        let keyword = self.direction.keyword();
+        query.push_str(&format!(" ORDER BY {column} {keyword}, {id_column} {keyword} LIMIT :page_limit"));
+        params.push(("page_limit".to_string(), (self.limit + 1).into()));
+    }
+
+    /// Create the page from the items returned by a query built with [Self::apply]
+    pub(crate) fn finish(&self, mut items: Vec<S::Item>) -> Result<Page<S::Item>> {
+        if items.len() <= self.limit as usize {
+            return Ok(Page {
+                items,
+                next_cursor: None,
+            });
+        }
+
+        items.truncate(self.limit as usize);
+        let last = items.last().ok_or_else(|| Error::InvalidState("Page is empty but has a next page".to_string()))?;
+        let cursor = Cursor {
+            sort: self.sort.to_string(),
+            direction: self.direction.to_string(),
+            key: self.sort.key(last),
+            id: S::id(last),
+        };
+
+        Ok(Page {
+            next_cursor: Some(cursor.encode()?),
+            items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Stringify)]
+    enum TestSort {
+        Name,
+    }
+
+    impl SortKey for TestSort {
+        type Item = (String, String);
+        const ID_COLUMN: &'static str = "id";
+
+        fn column(&self) -> &'static str {
+            "name"
+        }
+
+        fn key(&self, item: &Self::Item) -> Value {
+            item.1.clone().into()
+        }
+
+        fn id(item: &Self::Item) -> String {
+            item.0.clone()
+        }
+    }
+
+    fn items(n: usize) -> Vec<(String, String)> {
+        (0..n).map(|x| (format!("id{x}"), format!("name{x}"))).collect()
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let request = PageRequest::new(TestSort::Name, SortDirection::Asc, 2, None).unwrap();
+        let page = request.finish(items(2)).unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn next_page_continues_after_last_item() {
+        let request = PageRequest::new(TestSort::Name, SortDirection::Desc, 2, None).unwrap();
+        let page = request.finish(items(3)).unwrap();
+        assert_eq!(page.items.len(), 2);
+
+        let next = PageRequest::new(TestSort::Name, SortDirection::Desc, 2, page.next_cursor.as_deref()).unwrap();
+        let mut query = String::from("SELECT id,name FROM t WHERE a = :a");
+        let mut params = Vec::new();
+        next.apply(&mut query, &mut params);
+
+        assert_eq!(query, "SELECT id,name FROM t WHERE a = :a AND (name < :cursor_key OR (name = :cursor_key AND id < :cursor_id)) ORDER BY name DESC, id DESC LIMIT :page_limit");
+        assert_eq!(params, vec![
+            ("cursor_key".to_string(), Value::Text("name1".to_string())),
+            ("cursor_id".to_string(), Value::Text("id1".to_string())),
+            ("page_limit".to_string(), Value::Int(3)),
+        ]);
+    }
+
+    #[test]
+    fn cursor_must_match_sort() {
+        let page = PageRequest::new(TestSort::Name, SortDirection::Asc, 1, None).unwrap()
+            .finish(items(2))
+            .unwrap();
+
+        let result = PageRequest::new(TestSort::Name, SortDirection::Desc, 1, page.next_cursor.as_deref());
+        assert!(matches!(result, Err(Error::InvalidCursor)));
+        assert!(matches!(PageRequest::new(TestSort::Name, SortDirection::Asc, 1, Some("garbage")), Err(Error::InvalidCursor)));
+    }
+}
@@ -1,8 +1,9 @@
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rand::Rng;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::{info, trace, warn};
@@ -14,19 +15,125 @@ const CFG_FOLDER: &str = ".";
 
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
-    pub mysql: MysqlConfig,
+    #[serde(alias = "mysql")]
+    pub database: DatabaseConfig,
     pub http: HttpConfig,
     pub security: SecurityConfig,
+    /// SMTP settings used for sending emails. When absent, emails are logged instead of sent
+    pub mail: Option<MailConfig>,
+    /// Payment provider used for paying invoices online. When absent, online payments are disabled
+    pub payment: Option<PaymentConfig>,
+    /// Where uploaded files, such as attachments, are stored. Defaults to a local directory
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
-pub struct MysqlConfig {
+pub struct DatabaseConfig {
+    /// The database backend to use. Defaults to MySQL
+    #[serde(default)]
+    pub backend: DatabaseBackend,
+    /// Not used by SQLite
+    #[serde(default)]
     pub host: String,
+    /// The name of the database. For SQLite this is the path to the database file
     pub database: String,
+    /// Not used by SQLite
+    #[serde(default)]
     pub username: String,
+    /// Not used by SQLite
+    #[serde(default)]
     pub password: String,
 }
 
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    Mysql,
+    Postgres,
+    Sqlite,
+}
+
+impl From<DatabaseBackend> for dal::Backend {
+    fn from(x: DatabaseBackend) -> Self {
+        match x {
+            DatabaseBackend::Mysql => Self::Mysql,
+            DatabaseBackend::Postgres => Self::Postgres,
+            DatabaseBackend::Sqlite => Self::Sqlite,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MailConfig {
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PaymentConfig {
+    /// The base URL of the provider's API
+    #[serde(default = "default_payment_base_url")]
+    pub base_url: String,
+    pub api_key: String,
+    pub webhook_secret: String,
+}
+
+fn default_payment_base_url() -> String {
+    "https://api.mollie.com".to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum StorageConfig {
+    Local {
+        path: String,
+    },
+    /// An S3-compatible object store, such as MinIO
+    S3 {
+        endpoint: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        bucket: String,
+        access_key: String,
+        secret_key: String,
+        /// Address the bucket in the path of requests rather than in the host name
+        #[serde(default)]
+        path_style: bool,
+    },
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self::Local {
+            path: PathBuf::from(CFG_FOLDER).join("attachments").to_string_lossy().to_string(),
+        }
+    }
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    /// Hosts webhooks may be delivered to even though they resolve to an internal address, such as `localhost`.
+    /// Webhooks are never delivered to loopback, link-local or private addresses otherwise
+    #[serde(default)]
+    pub allowed_internal_hosts: Vec<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HttpConfig {
     pub port: u16,
@@ -45,16 +152,42 @@ impl Default for HttpConfig {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SecurityConfig {
     pub password_pepper: String,
+    /// The amount of days a session remains valid after it was last used
+    #[serde(default = "default_session_expiry_days")]
+    pub session_expiry_days: u64,
+    /// The interval in minutes at which expired sessions are purged. Must be at least 1
+    #[serde(default = "default_session_purge_interval_minutes")]
+    pub session_purge_interval_minutes: u64,
+}
+
+fn default_session_expiry_days() -> u64 {
+    30
+}
+
+fn default_session_purge_interval_minutes() -> u64 {
+    60
 }
 
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
-            password_pepper: rand::thread_rng().sample_iter(rand::distributions::Alphanumeric).take(64).map(char::from).collect()
+            password_pepper: rand::thread_rng().sample_iter(rand::distributions::Alphanumeric).take(64).map(char::from).collect(),
+            session_expiry_days: default_session_expiry_days(),
+            session_purge_interval_minutes: default_session_purge_interval_minutes(),
         }
     }
 }
 
+impl SecurityConfig {
+    pub fn session_expiry(&self) -> Duration {
+        Duration::from_secs(self.session_expiry_days * 24 * 60 * 60)
+    }
+
+    pub fn session_purge_interval(&self) -> Duration {
+        Duration::from_secs(self.session_purge_interval_minutes * 60)
+    }
+}
+
 impl Config {
     pub async fn new() -> Result<Self> {
         let path = PathBuf::from(CFG_FOLDER).join("config.toml");
@@ -78,6 +211,10 @@ impl Config {
         trace!("Deserializing configuration");
         let this: Self = toml::from_slice(&buf)?;
 
+        if this.security.session_purge_interval_minutes == 0 {
+            bail!("security.session_purge_interval_minutes must be at least 1");
+        }
+
         Ok(this)
     }
 
@@ -1,7 +1,13 @@
 use tracing::info;
-use crate::config::Config;
+use std::time::Duration;
+use crate::config::{Config, StorageConfig};
 
 mod config;
+mod tasks;
+mod webhook;
+
+/// The interval at which queued webhook events are delivered
+const WEBHOOK_DELIVERY_INTERVAL: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() {
@@ -12,22 +18,53 @@ async fn main() {
     info!("Reading configuration");
     let config = Config::new().await.expect("Reading configuration");
 
-    info!("Initializing Mysql driver");
+    info!("Initializing {:?} driver", config.database.backend);
     let driver = dal::get_driver(dal::DriverConfig {
-        host: config.mysql.host,
-        database: config.mysql.database,
-        username: config.mysql.username,
-        password: config.mysql.password
-    }).expect("Initializing mysql driver");
+        backend: config.database.backend.into(),
+        host: config.database.host,
+        database: config.database.database,
+        username: config.database.username,
+        password: config.database.password
+    }).expect("Initializing database driver");
 
     info!("Initializing DAL");
-    dal::init(&driver).expect("Initializing DAL");
+    dal::init(&driver).await.expect("Initializing DAL");
+
+    info!("Starting background tasks");
+    tasks::spawn_session_purge(driver.clone(), config.security.session_purge_interval());
+    tasks::spawn_webhook_delivery(driver.clone(), WEBHOOK_DELIVERY_INTERVAL, config.webhook.allowed_internal_hosts);
 
     info!("Starting web server");
     api::start(api::Config {
         frontend_host: config.http.frontend_host,
         port: config.http.port,
-        password_pepper: config.security.password_pepper
+        session_expiry: config.security.session_expiry(),
+        password_pepper: config.security.password_pepper,
+        mail: config.mail.map(|x| api::MailConfig {
+            smtp_host: x.smtp_host,
+            smtp_port: x.smtp_port,
+            username: x.username,
+            password: x.password,
+            from: x.from,
+        }),
+        payment: config.payment.map(|x| api::PaymentConfig {
+            base_url: x.base_url,
+            api_key: x.api_key,
+            webhook_secret: x.webhook_secret,
+        }),
+        storage: match config.storage {
+            StorageConfig::Local { path } => api::StorageConfig::Local {
+                path: path.into(),
+            },
+            StorageConfig::S3 { endpoint, region, bucket, access_key, secret_key, path_style } => api::StorageConfig::S3(api::S3Config {
+                endpoint,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+                path_style,
+            }),
+        },
     }, driver).await.expect("Starting web server");
     // This method doesn't return as long as the web server is running
 
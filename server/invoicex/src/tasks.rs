@@ -0,0 +1,35 @@
+use std::time::Duration;
+use dal::Driver;
+use dal::entities::User;
+use tracing::{trace, warn};
+use crate::webhook;
+
+/// Periodically remove all expired sessions from the database
+pub fn spawn_session_purge(driver: Driver, interval: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+
+            trace!("Purging expired sessions");
+            if let Err(e) = User::purge_expired_sessions(&driver).await {
+                warn!("Failed to purge expired sessions: {e}");
+            }
+        }
+    });
+}
+
+/// Periodically deliver queued webhook events. Hosts in `allowed_hosts` may resolve to internal addresses
+pub fn spawn_webhook_delivery(driver: Driver, interval: Duration, allowed_hosts: Vec<String>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+
+            trace!("Delivering webhook events");
+            if let Err(e) = webhook::deliver_due(&driver, &allowed_hosts).await {
+                warn!("Failed to deliver webhook events: {e}");
+            }
+        }
+    });
+}
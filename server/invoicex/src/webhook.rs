@@ -0,0 +1,207 @@
+//! Delivery of queued webhook events.
+//!
+//! Every delivery is a `POST` request with the event as JSON body. Deliveries are signed with HMAC-SHA256,
+//! keyed with the secret of the webhook, over `<timestamp>.<body>`. The signature is sent in the
+//! [SIGNATURE_HEADER] header as `t=<timestamp>,v1=<hex encoded signature>`. Receivers should verify the
+//! signature and reject old timestamps, to prevent replays.
+//!
+//! A delivery is retried with exponential backoff until the receiver responds with a 2xx status.
+//! Events are delivered at least once, receivers can recognize duplicates by the [DELIVERY_HEADER] header.
+//!
+//! Webhook URLs are chosen by users, so deliveries are never sent to internal addresses, such as loopback,
+//! link-local or private addresses. The host is resolved when delivering, the request is sent to the checked
+//! address and redirects are not followed. Hosts in the allowlist of the configuration are exempt.
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::{anyhow, bail};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::{debug, warn};
+use dal::Driver;
+use dal::entities::{Entity, Webhook, WebhookDelivery};
+
+pub const SIGNATURE_HEADER: &str = "X-InvoiceX-Signature";
+pub const EVENT_HEADER: &str = "X-InvoiceX-Event";
+pub const DELIVERY_HEADER: &str = "X-InvoiceX-Delivery";
+
+/// The maximum number of deliveries attempted per run
+const BATCH_SIZE: u32 = 100;
+/// How long a receiver gets to respond
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempt all deliveries which are due. Hosts in `allowed_hosts` may resolve to internal addresses
+pub async fn deliver_due(driver: &Driver, allowed_hosts: &[String]) -> Result<(), dal::Error> {
+    let due = WebhookDelivery::list_due(driver, unix_now(), BATCH_SIZE).await?;
+    for mut delivery in due {
+        // Deliveries are removed together with their webhook, but the webhook may be removed in the meantime
+        let webhook = match Webhook::get(driver, delivery.webhook_id.clone()).await? {
+            Some(x) => x,
+            None => continue,
+        };
+
+        match send(&webhook.url, allowed_hosts, &webhook.secret, &delivery.id, &delivery.event_type, &delivery.payload).await {
+            Ok(status) if (200..300).contains(&status) => {
+                debug!("Delivered event {} to webhook {}", delivery.event_id, webhook.id);
+                delivery.record_success(status).await?;
+            },
+            Ok(status) => {
+                warn!("Webhook {} responded with status {status} to delivery {}", webhook.id, delivery.id);
+                delivery.record_failure(Some(status), format!("Receiver responded with status {status}")).await?;
+            },
+            Err(e) => {
+                warn!("Failed to deliver {} to webhook {}: {e}", delivery.id, webhook.id);
+                delivery.record_failure(None, e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Send a single delivery, returning the HTTP status of the response
+async fn send(url: &str, allowed_hosts: &[String], secret: &str, delivery_id: &str, event_type: &str, payload: &str) -> anyhow::Result<u32> {
+    let client = client(url, allowed_hosts).await?;
+    let timestamp = unix_now();
+    let response = client.post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, format!("t={timestamp},v1={}", signature(secret, timestamp, payload)))
+        .header(EVENT_HEADER, event_type)
+        .header(DELIVERY_HEADER, delivery_id)
+        .body(payload.to_string())
+        .send()
+        .await?;
+
+    Ok(response.status().as_u16() as u32)
+}
+
+/// Build the client for a delivery to `url`. The host is resolved once and refused if any of its addresses is
+/// internal, unless the host is allowed. The client connects to the checked address and does not follow redirects
+async fn client(url: &str, allowed_hosts: &[String]) -> anyhow::Result<reqwest::Client> {
+    let url = reqwest::Url::parse(url)?;
+    let host = url.host_str().ok_or_else(|| anyhow!("URL has no host"))?;
+    let port = url.port_or_known_default().ok_or_else(|| anyhow!("URL has no port"))?;
+
+    let builder = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("InvoiceX/", env!("CARGO_PKG_VERSION")))
+        .redirect(reqwest::redirect::Policy::none());
+
+    if allowed_hosts.iter().any(|x| x.eq_ignore_ascii_case(host)) {
+        return Ok(builder.build()?);
+    }
+
+    // IPv6 addresses are enclosed in brackets in URLs
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = literal.parse::<IpAddr>() {
+        if is_internal(ip) {
+            bail!("Refusing to deliver to internal address {ip}");
+        }
+
+        return Ok(builder.build()?);
+    }
+
+    let addrs = tokio::net::lookup_host((host, port)).await?.collect::<Vec<_>>();
+    if let Some(addr) = addrs.iter().find(|x| is_internal(x.ip())) {
+        bail!("Refusing to deliver to {host}, it resolves to internal address {}", addr.ip());
+    }
+
+    let addr = addrs.first().ok_or_else(|| anyhow!("{host} does not resolve to any address"))?;
+    Ok(builder.resolve(host, *addr).build()?)
+}
+
+/// Whether an address is not publicly routable
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast() || ip.is_multicast()
+                // 0.0.0.0/8, this network
+                || octets[0] == 0
+                // 100.64.0.0/10, shared address space used by carrier-grade NAT
+                || (octets[0] == 100 && octets[1] & 0xc0 == 64)
+        },
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal(IpAddr::V4(ip)),
+            None => ip.is_loopback() || ip.is_unspecified() || ip.is_multicast()
+                // fc00::/7, unique local
+                || ip.segments()[0] & 0xfe00 == 0xfc00
+                // fe80::/10, link-local
+                || ip.segments()[0] & 0xffc0 == 0xfe80,
+        },
+    }
+}
+
+/// The hex encoded HMAC-SHA256 of `<timestamp>.<payload>`
+fn signature(secret: &str, timestamp: i64, payload: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{timestamp}.{payload}").as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs() as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn known_signature() {
+        // echo -n '1700000000.{}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(signature("secret", 1_700_000_000, "{}"), "b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163");
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let receiver = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            // Read until the body, which ends the request, has arrived
+            while !request.ends_with(b"{\"id\":\"event\"}") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let status = send(&url, &["127.0.0.1".to_string()], "secret", "delivery", "Product.Create", "{\"id\":\"event\"}").await.unwrap();
+        assert_eq!(status, 204);
+
+        let request = receiver.await.unwrap().to_lowercase();
+        assert!(request.starts_with("post /hook "));
+        assert!(request.contains("x-invoicex-event: product.create"));
+
+        let header = request.lines().find_map(|x| x.strip_prefix("x-invoicex-signature: ")).unwrap();
+        let (timestamp, signature_hex) = header.strip_prefix("t=").unwrap().split_once(",v1=").unwrap();
+        assert_eq!(signature_hex, signature("secret", timestamp.parse().unwrap(), "{\"id\":\"event\"}"));
+    }
+
+    #[tokio::test]
+    async fn refuses_internal_addresses() {
+        for url in ["http://127.0.0.1:8080/hook", "http://[::1]/hook", "http://localhost/hook", "http://169.254.169.254/latest"] {
+            assert!(send(url, &[], "secret", "delivery", "Product.Create", "{}").await.is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn internal_addresses() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(is_internal(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in ["1.1.1.1", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_internal(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}